@@ -23,6 +23,12 @@
   --palette ./shared/src/sprites/palette_blue.aseprite \
   --save-as ./shared/src/sprites/units/supply_crate_blue.aseprite
 
+/Applications/Aseprite.app/Contents/MacOS/aseprite \
+  -b \
+  ./shared/src/sprites/units/cavalry_red.aseprite \
+  --palette ./shared/src/sprites/palette_blue.aseprite \
+  --save-as ./shared/src/sprites/units/cavalry_blue.aseprite
+
 # Make thumbnails
 /Applications/Aseprite.app/Contents/MacOS/aseprite \
   -b \
@@ -83,6 +89,12 @@
   --palette ./shared/src/sprites/palette_red_moved.aseprite \
   --save-as ./shared/src/sprites/units/truck_red_moved.aseprite
 
+/Applications/Aseprite.app/Contents/MacOS/aseprite \
+  -b \
+  ./shared/src/sprites/units/cavalry_red.aseprite \
+  --palette ./shared/src/sprites/palette_red_moved.aseprite \
+  --save-as ./shared/src/sprites/units/cavalry_red_moved.aseprite
+
 /Applications/Aseprite.app/Contents/MacOS/aseprite \
   -b \
   ./shared/src/sprites/units/infantry_blue.aseprite \
@@ -101,6 +113,12 @@
   --palette ./shared/src/sprites/palette_blue_moved.aseprite \
   --save-as ./shared/src/sprites/units/truck_blue_moved.aseprite
 
+/Applications/Aseprite.app/Contents/MacOS/aseprite \
+  -b \
+  ./shared/src/sprites/units/cavalry_blue.aseprite \
+  --palette ./shared/src/sprites/palette_blue_moved.aseprite \
+  --save-as ./shared/src/sprites/units/cavalry_blue_moved.aseprite
+
 # Make Unit Thumbnails
 /Applications/Aseprite.app/Contents/MacOS/aseprite \
   -b \
@@ -144,6 +162,18 @@ cp ./shared/src/sprites/units/tank_blue1.png ./server/src/assets/tank_blue.png
   --save-as ./shared/src/sprites/units/truck_blue.png
 cp ./shared/src/sprites/units/tank_blue1.png ./server/src/assets/truck_blue.png
 
+/Applications/Aseprite.app/Contents/MacOS/aseprite \
+  -b \
+  --oneframe \
+  ./shared/src/sprites/units/cavalry_red.aseprite \
+  --save-as ./shared/src/sprites/units/cavalry_red.png
+
+/Applications/Aseprite.app/Contents/MacOS/aseprite \
+  -b \
+  --oneframe \
+  ./shared/src/sprites/units/cavalry_blue.aseprite \
+  --save-as ./shared/src/sprites/units/cavalry_blue.png
+
 
 # Units
 /Applications/Aseprite.app/Contents/MacOS/aseprite \
@@ -156,6 +186,8 @@ cp ./shared/src/sprites/units/tank_blue1.png ./server/src/assets/truck_blue.png
     ./shared/src/sprites/units/truck_blue.aseprite \
     ./shared/src/sprites/units/supply_crate_red.aseprite \
     ./shared/src/sprites/units/supply_crate_blue.aseprite \
+    ./shared/src/sprites/units/cavalry_red.aseprite \
+    ./shared/src/sprites/units/cavalry_blue.aseprite \
   --sheet ./shared/src/sprites/units_sheet.png
 
 # Moved Units
@@ -169,6 +201,8 @@ cp ./shared/src/sprites/units/tank_blue1.png ./server/src/assets/truck_blue.png
     ./shared/src/sprites/units/truck_blue_moved.aseprite \
     ./shared/src/sprites/units/supply_crate_red.aseprite \
     ./shared/src/sprites/units/supply_crate_blue.aseprite \
+    ./shared/src/sprites/units/cavalry_red_moved.aseprite \
+    ./shared/src/sprites/units/cavalry_blue_moved.aseprite \
   --sheet ./shared/src/sprites/unit_moved_sheet.png
 
 # Flags
@@ -210,6 +244,9 @@ cp ./shared/src/sprites/units/tank_blue1.png ./server/src/assets/truck_blue.png
     ./shared/src/sprites/loaded_indicator.aseprite \
     ./shared/src/sprites/low_supplies_indicator.aseprite \
     ./shared/src/sprites/fully_loaded_indicator.aseprite \
+    ./shared/src/sprites/water_tile.aseprite \
+    ./shared/src/sprites/shallows_tile.aseprite \
+    ./shared/src/sprites/bridge_tile.aseprite \
   --sheet ./shared/src/sprites/misc_sheet.png
 
 /Applications/Aseprite.app/Contents/MacOS/aseprite \
@@ -1,4 +1,6 @@
+use shared::unit::definition::Table;
 use std::env;
+use std::fs;
 
 ////////////////////////////////////////////////////////////////////////////////
 // TYPES //
@@ -14,6 +16,7 @@ pub struct MainFlags {
     pub port_number: u64,
     pub dev_mode: bool,
     pub show_elm_output: bool,
+    pub unit_table: Table,
 }
 
 impl Flags {
@@ -39,6 +42,8 @@ impl Flags {
 
         let mut show_elm_output = true;
 
+        let mut unit_table = Table::bundled().clone();
+
         for arg in args {
             let mut dev = || {
                 maybe_ip_address = Ok("127.0.0.1".to_string());
@@ -91,6 +96,25 @@ impl Flags {
                                 return Err(buf);
                             }
                         },
+                        "units" => {
+                            let src = fs::read_to_string(&value).map_err(|error| {
+                                let mut buf = String::new();
+
+                                buf.push_str("could not read units file : ");
+                                buf.push_str(error.to_string().as_str());
+
+                                buf
+                            })?;
+
+                            unit_table = Table::from_src(src.as_str()).map_err(|error| {
+                                let mut buf = String::new();
+
+                                buf.push_str("units file is not valid : ");
+                                buf.push_str(error.as_str());
+
+                                buf
+                            })?;
+                        }
                         unrecognized_key => {
                             let mut buf = String::new();
 
@@ -115,6 +139,7 @@ impl Flags {
             dev_mode,
 
             show_elm_output,
+            unit_table,
         }))
     }
 }
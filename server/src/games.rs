@@ -7,6 +7,7 @@ use shared::name::Name;
 use shared::player::Player;
 use shared::rng::{RandGen, RandSeed};
 use shared::team_color::TeamColor;
+use shared::unit::definition::Table;
 use shared::unit::{Place, Unit, UnitId};
 use shared::{game, unit};
 use std::collections::HashMap;
@@ -20,11 +21,14 @@ use std::str::FromStr;
 pub struct Games {
     games: HashMap<GameId, Game>,
     random_seed: RandSeed,
+    // The unit stats every new game is started with
+    unit_table: Table,
 }
 
 pub struct Flags {
     pub dev: bool,
     pub rand_seed: RandSeed,
+    pub unit_table: Table,
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -38,7 +42,7 @@ impl From<Flags> for Games {
         let games = if flags.dev {
             let dev_seed: RandSeed = RandSeed::next(&mut rng);
 
-            Games::dev_games(dev_seed)
+            Games::dev_games(dev_seed, &flags.unit_table)
         } else {
             HashMap::new()
         };
@@ -48,6 +52,7 @@ impl From<Flags> for Games {
         Games {
             games,
             random_seed: final_seed,
+            unit_table: flags.unit_table,
         }
     }
 }
@@ -80,7 +85,10 @@ impl Games {
     pub fn new_game_from_lobby(&mut self, lobby: Lobby) -> Result<Game, game::FromLobbyError> {
         let mut rand_gen = RandGen::from_seed(self.random_seed.clone());
 
-        let game: Game = Game::try_from(GameInitFlags::new(lobby, &mut rand_gen))?;
+        let mut game_init_flags = GameInitFlags::new(lobby, &mut rand_gen);
+        game_init_flags.with_unit_table(self.unit_table.clone());
+
+        let game: Game = Game::try_from(game_init_flags)?;
 
         let new_seed: RandSeed = RandSeed::next(&mut rand_gen);
 
@@ -89,7 +97,7 @@ impl Games {
         Ok(game)
     }
 
-    fn dev_games(rand_seed: RandSeed) -> HashMap<GameId, Game> {
+    fn dev_games(rand_seed: RandSeed, unit_table: &Table) -> HashMap<GameId, Game> {
         let mut rng = RandGen::from_seed(rand_seed);
 
         let mut games = HashMap::new();
@@ -129,8 +137,12 @@ impl Games {
 
                     let new_seed: RandSeed = RandSeed::next(&mut rng);
 
-                    Game::try_from(GameInitFlags::new(lobby, &mut RandGen::from_seed(new_seed)))
-                        .unwrap()
+                    let mut init_flags_rng = RandGen::from_seed(new_seed);
+
+                    let mut game_init_flags = GameInitFlags::new(lobby, &mut init_flags_rng);
+                    game_init_flags.with_unit_table(unit_table.clone());
+
+                    Game::try_from(game_init_flags).unwrap()
                 }
                 DevGameId::ReplenishTest => {
                     let red_player_id = Id::from_string("red".to_string(), true).unwrap();
@@ -157,6 +169,7 @@ impl Games {
                     let mut init_flags_rng = RandGen::from_seed(new_seed);
 
                     let mut game_init_flags = GameInitFlags::new(lobby, &mut init_flags_rng);
+                    game_init_flags.with_unit_table(unit_table.clone());
 
                     let mut infantry = unit::Model::new(
                         Unit::Infantry,
@@ -165,7 +178,7 @@ impl Games {
                         &TeamColor::Red,
                     );
 
-                    infantry.supplies = Unit::Infantry.max_supplies() / 3;
+                    infantry.supplies = infantry.definition(unit_table).max_supplies / 3;
 
                     let mut depleted_infantry = unit::Model::new(
                         Unit::Infantry,
@@ -174,8 +187,12 @@ impl Games {
                         &TeamColor::Red,
                     );
 
-                    depleted_infantry.supplies =
-                        (Unit::Infantry.active_supply_cost().unwrap() * 1.5).ceil() as i16;
+                    depleted_infantry.supplies = (depleted_infantry
+                        .definition(unit_table)
+                        .active_supply_cost
+                        .unwrap()
+                        * 1.5)
+                        .ceil() as i16;
 
                     let mut truck = unit::Model::new(
                         Unit::Truck,
@@ -184,7 +201,7 @@ impl Games {
                         &TeamColor::Red,
                     );
 
-                    truck.supplies = Unit::Truck.max_supplies() / 2;
+                    truck.supplies = truck.definition(unit_table).max_supplies / 2;
 
                     let truck_id = UnitId::test("truck");
                     game_init_flags.with_extra_units(&mut vec![
@@ -232,6 +249,7 @@ impl Games {
                     let mut init_flags_rng = RandGen::from_seed(new_seed);
 
                     let mut game_init_flags = GameInitFlags::new(lobby, &mut init_flags_rng);
+                    game_init_flags.with_unit_table(unit_table.clone());
 
                     let truck_1 = unit::Model::new(
                         Unit::Truck,
@@ -289,6 +307,7 @@ impl Games {
                     let mut init_flags_rng = RandGen::from_seed(new_seed);
 
                     let mut game_init_flags = GameInitFlags::new(lobby, &mut init_flags_rng);
+                    game_init_flags.with_unit_table(unit_table.clone());
 
                     let red_truck_1 = unit::Model::new(
                         Unit::Truck,
@@ -402,6 +421,20 @@ impl Games {
                         &TeamColor::Blue,
                     );
 
+                    let red_supply_depot = unit::Model::new(
+                        Unit::SupplyDepot,
+                        &red_player_id,
+                        Place::on_map(0, 6, FacingDirection::Right),
+                        &TeamColor::Red,
+                    );
+
+                    let blue_supply_depot = unit::Model::new(
+                        Unit::SupplyDepot,
+                        &blue_player_id,
+                        Place::on_map(15, 6, FacingDirection::Left),
+                        &TeamColor::Blue,
+                    );
+
                     game_init_flags.with_extra_units(&mut vec![
                         (UnitId::test("red truck 1"), red_truck_1),
                         (UnitId::test("red truck 2"), red_truck_2),
@@ -419,6 +452,8 @@ impl Games {
                         (UnitId::test("red supply crate 2"), red_supply_crate_2),
                         (UnitId::test("blue supply crate 1"), blue_supply_crate_1),
                         (UnitId::test("blue supply crate 2"), blue_supply_crate_2),
+                        (UnitId::test("red supply depot"), red_supply_depot),
+                        (UnitId::test("blue supply depot"), blue_supply_depot),
                     ]);
 
                     Game::try_from(game_init_flags).unwrap()
@@ -444,8 +479,12 @@ impl Games {
 
                     let new_seed: RandSeed = RandSeed::next(&mut rng);
 
-                    Game::try_from(GameInitFlags::new(lobby, &mut RandGen::from_seed(new_seed)))
-                        .unwrap()
+                    let mut init_flags_rng = RandGen::from_seed(new_seed);
+
+                    let mut game_init_flags = GameInitFlags::new(lobby, &mut init_flags_rng);
+                    game_init_flags.with_unit_table(unit_table.clone());
+
+                    Game::try_from(game_init_flags).unwrap()
                 }
             };
 
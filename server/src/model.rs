@@ -44,6 +44,7 @@ impl Model {
         let games: Games = games::Flags {
             dev: flags.dev_mode,
             rand_seed: games_seed,
+            unit_table: flags.unit_table,
         }
         .into();
 
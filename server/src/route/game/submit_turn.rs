@@ -1,6 +1,6 @@
 use crate::model::Model;
 use actix_web::{web, HttpResponse};
-use shared::api::game::submit_turn::{Request, Response};
+use shared::api::game::submit_turn::{Rejection, Request, Response};
 use shared::game::{Game, GameId};
 use shared::id::Id;
 use shared::rng::RandSeed;
@@ -47,6 +47,15 @@ pub async fn handle(
         }
     };
 
+    if let Err(invalids) = game.validate_turn(&player_id, &req.moves) {
+        return match Rejection::new(invalids).to_bytes() {
+            Ok(rejection_bytes) => HttpResponse::UnprocessableEntity()
+                .header("Content-Type", "application/octet-stream")
+                .body(rejection_bytes),
+            Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+        };
+    }
+
     game.take_changes(&mut req.changes.clone());
 
     if let Err(err) = game.set_turn(player_id, req.moves) {
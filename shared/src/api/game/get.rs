@@ -1,4 +1,5 @@
 use crate::game::{Game, GameId};
+use crate::id::Id;
 use serde::{Deserialize, Serialize};
 
 ////////////////////////////////////////////////////////////////
@@ -24,6 +25,10 @@ impl Response {
         self.game.clone()
     }
 
+    pub fn get_turn_errors(&self, player_id: &Id) -> Vec<String> {
+        self.game.get_players_turn_errors(player_id)
+    }
+
     pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
         bincode::serialize(self)
     }
@@ -1,5 +1,7 @@
 use crate::game;
+use crate::game::validation::Invalid;
 use crate::game::Game;
+use crate::id::Id;
 use serde::{Deserialize, Serialize};
 
 ////////////////////////////////////////////////////////////////
@@ -39,6 +41,10 @@ impl Response {
         Response { game }
     }
 
+    pub fn get_turn_errors(&self, player_id: &Id) -> Vec<String> {
+        self.game.get_players_turn_errors(player_id)
+    }
+
     pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
         bincode::serialize(self)
     }
@@ -47,3 +53,28 @@ impl Response {
         bincode::deserialize(&byte_data[..])
     }
 }
+
+////////////////////////////////////////////////////////////////
+// Rejection //
+////////////////////////////////////////////////////////////////
+
+// Sent back instead of a Response when the submitted
+// actions do not pass validation
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Rejection {
+    pub invalids: Vec<Invalid>,
+}
+
+impl Rejection {
+    pub fn new(invalids: Vec<Invalid>) -> Rejection {
+        Rejection { invalids }
+    }
+
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    pub fn from_bytes(byte_data: Vec<u8>) -> bincode::Result<Rejection> {
+        bincode::deserialize(&byte_data[..])
+    }
+}
@@ -7,6 +7,14 @@ pub enum FacingDirection {
     Right,
 }
 
+// Which side of a unit an attack comes in on
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Exposure {
+    Front,
+    Flank,
+    Rear,
+}
+
 impl FacingDirection {
     pub fn to_file_name_str(&self) -> &str {
         match self {
@@ -20,6 +28,15 @@ impl FacingDirection {
             FacingDirection::Right => "right",
         }
     }
+    pub fn exposure_to(&self, attacking_from: &Direction) -> Exposure {
+        match (self, attacking_from) {
+            (_, Direction::North) | (_, Direction::South) => Exposure::Flank,
+            (FacingDirection::Left, Direction::West) => Exposure::Front,
+            (FacingDirection::Left, Direction::East) => Exposure::Rear,
+            (FacingDirection::Right, Direction::East) => Exposure::Front,
+            (FacingDirection::Right, Direction::West) => Exposure::Rear,
+        }
+    }
     pub fn from_directions(dirs: Vec<Direction>) -> Option<FacingDirection> {
         let mut ret = None;
 
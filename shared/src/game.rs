@@ -1,15 +1,22 @@
 pub mod action;
+pub mod conditions;
 pub mod day;
 pub mod event;
+pub mod forecast;
 pub mod mobility;
 pub mod replenishment;
+pub mod supply_line;
 pub mod unit_index;
+pub mod validation;
+pub mod weather;
 
 use crate::facing_direction::FacingDirection;
 use crate::game::action::Action;
+use crate::game::conditions::Conditions;
 use crate::game::day::Time;
-use crate::game::event::Event;
+use crate::game::event::{Event, TurnError};
 use crate::game::unit_index::Indexes;
+use crate::game::weather::Weather;
 use crate::id::Id;
 use crate::lobby::{Lobby, LobbyId};
 use crate::located::Located;
@@ -18,9 +25,11 @@ use crate::owner::Owned;
 use crate::player::Player;
 use crate::rng::{RandGen, RandSeed};
 use crate::team_color::TeamColor;
+use crate::unit::definition::Table;
 use crate::unit::{Place, Unit, UnitId};
 use crate::{located, unit};
 use serde::{Deserialize, Serialize};
+use std::cmp;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 
@@ -123,9 +132,15 @@ pub struct Game {
     pub remaining_guests: Vec<(Id, Guest)>,
     pub indexes: Indexes,
     pub map: Map,
+    // The stats every unit in this game was made with
+    pub unit_table: Table,
     pub turn_number: u32,
+    // Changes from turn to turn, drawn from the same
+    // seed as everything else that happens in a turn
+    pub weather: Weather,
     pub turns_changes: Vec<Change>,
     pub prev_turns_events: Vec<Event>,
+    pub prev_turns_errors: Vec<TurnError>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
@@ -200,6 +215,13 @@ impl Game {
     pub fn day(&self) -> Time {
         Time::from_turn(self.turn_number)
     }
+
+    pub fn conditions(&self) -> Conditions {
+        Conditions {
+            time_of_day: self.day().day_part,
+            weather: self.weather.clone(),
+        }
+    }
     pub fn get_rideable_units_by_location(
         &self,
         owner_id: &Id,
@@ -211,7 +233,10 @@ impl Game {
                 let rideable_units = units
                     .iter()
                     .filter_map(|(rideable_unit_id, _, possibly_rideable_unit)| {
-                        if possibly_rideable_unit.unit.can_carry(carrying_unit)
+                        if possibly_rideable_unit
+                            .definition(&self.unit_table)
+                            .can_carry
+                            .contains(carrying_unit)
                             && &possibly_rideable_unit.owner == owner_id
                         {
                             Some((rideable_unit_id.clone(), possibly_rideable_unit.clone()))
@@ -239,7 +264,10 @@ impl Game {
                 let supply_crates = units
                     .iter()
                     .filter_map(|(unit_id, _, possibly_supply_crate)| {
-                        if possibly_supply_crate.unit.is_supply_crate() {
+                        if possibly_supply_crate
+                            .definition(&self.unit_table)
+                            .is_supply_crate
+                        {
                             Some((unit_id.clone(), possibly_supply_crate.clone()))
                         } else {
                             None
@@ -322,38 +350,119 @@ impl Game {
             }
         };
 
+        // The moves were made during the turn that is ending
+        let conditions = self.conditions();
+
         self.turn_number += 1;
         self.process_changes();
         // self.process_outcomes(outcomes.clone())?;
 
         let event_rand_seed: RandSeed = RandSeed::next(&mut rng);
-        let event::ProcessedTurn { events, .. } = event::process_turn(
+        let event::ProcessedTurn { events, errors } = event::process_turn(
             event_rand_seed,
             &mut player_moves,
             &mut self.indexes,
-            &self.map,
+            &mut self.map,
+            &self.unit_table,
+            &conditions,
         );
 
+        self.regrow_forage(&events);
+
         self.prev_turns_events = events;
+        self.prev_turns_errors = errors;
         self.indexes.by_location = unit_index::by_location::make(&self.indexes.by_id);
         self.indexes.by_player = unit_index::by_player::make(&self.indexes.by_id);
         self.indexes.by_transport = unit_index::by_transport::make(&self.indexes.by_id);
-        self.host_visibility =
-            calculate_player_visibility(&self.host_id, &self.map, &self.indexes.by_id);
-        self.first_guest_visibility =
-            calculate_player_visibility(&self.first_guest_id, &self.map, &self.indexes.by_id);
+        self.weather = self.weather.next(&mut rng);
+        self.trace_supply_lines();
+
+        let conditions = self.conditions();
+        self.host_visibility = calculate_player_visibility(
+            &self.host_id,
+            &self.map,
+            &self.indexes.by_id,
+            &conditions,
+            &self.unit_table,
+        );
+        self.first_guest_visibility = calculate_player_visibility(
+            &self.first_guest_id,
+            &self.map,
+            &self.indexes.by_id,
+            &conditions,
+            &self.unit_table,
+        );
         self.hosts_turn = Turn::Waiting;
         self.first_guests_turn = Turn::Waiting;
 
         for (guest_id, guest) in &mut self.remaining_guests {
-            guest.visibility =
-                calculate_player_visibility(guest_id, &self.map, &self.indexes.by_id);
+            guest.visibility = calculate_player_visibility(
+                guest_id,
+                &self.map,
+                &self.indexes.by_id,
+                &conditions,
+                &self.unit_table,
+            );
             guest.turn = Turn::Waiting;
         }
 
         Ok(true)
     }
 
+    // Tiles that were left alone this turn grow back a little
+    fn regrow_forage(&mut self, events: &[Event]) {
+        let foraged_now = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Foraged { loc, .. } => Some(loc.clone()),
+                _ => None,
+            })
+            .collect::<HashSet<Located<()>>>();
+
+        self.map.regrow_forage(&foraged_now);
+    }
+
+    // Flag the units that are cut off from their depots,
+    // so they pay more for their upkeep next turn
+    fn trace_supply_lines(&mut self) {
+        let conditions = self.conditions();
+
+        let mut player_ids = vec![self.host_id.clone(), self.first_guest_id.clone()];
+        for (guest_id, _) in self.remaining_guests.iter() {
+            player_ids.push(guest_id.clone());
+        }
+
+        let mut in_supply: HashSet<UnitId> = HashSet::new();
+        for player_id in player_ids.iter() {
+            in_supply.extend(
+                supply_line::network(
+                    player_id,
+                    &self.indexes,
+                    &self.map,
+                    &conditions,
+                    &self.unit_table,
+                )
+                .in_supply,
+            );
+        }
+
+        let unit_ids: Vec<UnitId> = self
+            .indexes
+            .by_id
+            .iter()
+            .map(|(id, _)| id.clone())
+            .collect();
+        for unit_id in unit_ids {
+            if let Some(unit_model) = self.indexes.by_id.get_mut(&unit_id) {
+                unit_model.in_supply = in_supply.contains(&unit_id);
+            }
+        }
+
+        self.indexes.by_location = unit_index::by_location::make(&self.indexes.by_id);
+        self.indexes.by_player = unit_index::by_player::make(&self.indexes.by_id);
+        self.indexes.by_transport = unit_index::by_transport::make(&self.indexes.by_id);
+    }
+
     fn process_changes(&mut self) {
         for change in &mut self.turns_changes {
             match change {
@@ -384,6 +493,26 @@ impl Game {
         Err("Could not find player when getting turn".to_string())
     }
 
+    pub fn validate_turn(
+        &self,
+        player_id: &Id,
+        moves: &[Action],
+    ) -> Result<(), Vec<validation::Invalid>> {
+        let visibility = self
+            .get_players_visibility(player_id)
+            .map_err(|err_msg| vec![validation::Invalid::CouldNotValidate(err_msg)])?;
+
+        validation::validate_turn(
+            player_id,
+            moves,
+            visibility,
+            &self.indexes,
+            &self.map,
+            &self.conditions(),
+            &self.unit_table,
+        )
+    }
+
     pub fn set_turn(&mut self, player_id: Id, moves: Vec<Action>) -> Result<(), String> {
         if player_id == self.host_id {
             match self.hosts_turn {
@@ -427,6 +556,15 @@ impl Game {
         }
     }
 
+    // What went wrong with the given player's orders last turn
+    pub fn get_players_turn_errors(&self, player_id: &Id) -> Vec<String> {
+        self.prev_turns_errors
+            .iter()
+            .filter(|turn_error| turn_error.is_for_player(player_id))
+            .map(|turn_error| turn_error.message.clone())
+            .collect()
+    }
+
     pub fn get_players_visibility(&self, player_id: &Id) -> Result<&HashSet<Located<()>>, String> {
         if &self.host_id == player_id {
             return Ok(&self.host_visibility);
@@ -448,6 +586,21 @@ impl Game {
         ret_guest_visibility
     }
 
+    pub fn get_players_color(&self, player_id: &Id) -> Option<TeamColor> {
+        if &self.host_id == player_id {
+            return Some(self.host.color.clone());
+        }
+
+        if &self.first_guest_id == player_id {
+            return Some(self.first_guest.color.clone());
+        }
+
+        self.remaining_guests
+            .iter()
+            .find(|(guest_id, _)| guest_id == player_id)
+            .map(|(_, guest)| guest.player.color.clone())
+    }
+
     pub fn waiting_on_player(&self, player_id: &Id) -> bool {
         let mut has_submitted: bool = false;
 
@@ -471,106 +624,44 @@ impl Game {
     }
 }
 
+// How much further a unit standing on high ground can see
+const ELEVATION_SIGHT_BONUS: f32 = 2.0;
+
 pub fn calculate_player_visibility(
     player_id: &Id,
     map: &Map,
     units: &unit_index::by_id::Index,
+    conditions: &Conditions,
+    unit_table: &Table,
 ) -> HashSet<Located<()>> {
     let mut visible_spots = HashSet::new();
 
-    let player_id = player_id.clone();
-
     for unit_model in units.values() {
-        if unit_model.owner.clone() == player_id && !unit_model.unit.is_supply_crate() {
+        if unit_model.owner == *player_id && !unit_model.definition(unit_table).is_supply_crate {
             if let Place::OnMap(loc) = &unit_model.place {
-                let budget = unit_model.unit.visibility_budget();
-
-                let mut search: HashMap<Located<()>, f32> = HashMap::new();
-
-                search.insert(located::unit(loc.x, loc.y), budget);
-
-                while !search.is_empty() {
-                    for (search_loc, spot_budget) in search.clone().into_iter() {
-                        visible_spots.insert(search_loc.clone());
-                        search.remove(&search_loc);
+                let viewer = loc.to_unit();
 
-                        let x = search_loc.x;
-                        let y = search_loc.y;
+                let sight_range = unit_model.visibility_budget(unit_table)
+                    * conditions.visibility_multiplier()
+                    + (map.get_tile(&viewer).elevation() as f32) * ELEVATION_SIGHT_BONUS;
 
-                        if y > 0 {
-                            let north_loc = located::unit(x, y - 1);
-                            let north_tile = map.get_tile(&north_loc);
+                let reach = sight_range.ceil() as u16;
 
-                            let cost = north_tile.visibility_cost();
+                let min_x = viewer.x.saturating_sub(reach);
+                let min_y = viewer.y.saturating_sub(reach);
+                let max_x = cmp::min(viewer.x.saturating_add(reach), map.width.saturating_sub(1));
+                let max_y = cmp::min(viewer.y.saturating_add(reach), map.height.saturating_sub(1));
 
-                            let budget_at_tile = spot_budget - cost;
+                visible_spots.insert(viewer.clone());
 
-                            if budget_at_tile > 0.0 {
-                                search
-                                    .entry(north_loc)
-                                    .and_modify(|existing_budget| {
-                                        if budget_at_tile > *existing_budget {
-                                            *existing_budget = budget_at_tile;
-                                        }
-                                    })
-                                    .or_insert(budget_at_tile);
-                            }
-                        }
-
-                        if x > 0 {
-                            let west_loc = located::unit(x - 1, y);
-                            let west_tile = map.get_tile(&west_loc);
-
-                            let cost = west_tile.visibility_cost();
-
-                            let budget_at_tile = spot_budget - cost;
-
-                            if budget_at_tile > 0.0 {
-                                search
-                                    .entry(west_loc)
-                                    .and_modify(|existing_budget| {
-                                        if budget_at_tile > *existing_budget {
-                                            *existing_budget = budget_at_tile;
-                                        }
-                                    })
-                                    .or_insert(budget_at_tile);
-                            }
-                        }
-
-                        let south_loc = located::unit(x, y + 1);
-                        let south_tile = map.get_tile(&south_loc);
-
-                        let cost = south_tile.visibility_cost();
+                for x in min_x..=max_x {
+                    for y in min_y..=max_y {
+                        let target = located::unit(x, y);
 
-                        let budget_at_tile = spot_budget - cost;
-
-                        if budget_at_tile > 0.0 {
-                            search
-                                .entry(south_loc)
-                                .and_modify(|existing_budget| {
-                                    if budget_at_tile > *existing_budget {
-                                        *existing_budget = budget_at_tile;
-                                    }
-                                })
-                                .or_insert(budget_at_tile);
-                        }
-
-                        let east_loc = located::unit(x + 1, y);
-                        let east_tile = map.get_tile(&east_loc);
-
-                        let cost = east_tile.visibility_cost();
-
-                        let budget_at_tile = spot_budget - cost;
-
-                        if budget_at_tile > 0.0 {
-                            search
-                                .entry(east_loc)
-                                .and_modify(|existing_budget| {
-                                    if budget_at_tile > *existing_budget {
-                                        *existing_budget = budget_at_tile;
-                                    }
-                                })
-                                .or_insert(budget_at_tile);
+                        if (viewer.distance_from(&target) as f32) < sight_range
+                            && has_line_of_sight(map, &viewer, &target)
+                        {
+                            visible_spots.insert(target);
                         }
                     }
                 }
@@ -581,21 +672,45 @@ pub fn calculate_player_visibility(
     visible_spots
 }
 
+// Anything on the way to the target that stands taller than
+// the viewer's own ground blocks the view, and forests hide
+// whatever is inside them from anyone not right next to them
+fn has_line_of_sight(map: &Map, viewer: &Located<()>, target: &Located<()>) -> bool {
+    if viewer.distance_from(target) > 1 && map.get_tile(target).conceals() {
+        return false;
+    }
+
+    let eye_level = map.get_tile(viewer).elevation();
+
+    viewer
+        .tiles_between(target)
+        .iter()
+        .all(|loc| map.get_tile(loc).height() <= eye_level)
+}
+
 pub struct GameInitFlags<'a> {
     pub lobby: Lobby,
     pub rng: &'a mut RandGen,
     pub extra_units: Vec<(UnitId, unit::Model)>,
+    pub unit_table: Table,
 }
 
 impl GameInitFlags<'_> {
-    pub fn new(lobby: Lobby, rng: &mut RandGen) -> GameInitFlags {
+    pub fn new(lobby: Lobby, rng: &mut RandGen) -> GameInitFlags<'_> {
         GameInitFlags {
             lobby,
             rng,
             extra_units: vec![],
+            unit_table: Table::bundled().clone(),
         }
     }
 
+    // Play the game with different unit stats than the
+    // ones that are bundled with the game
+    pub fn with_unit_table(&mut self, unit_table: Table) {
+        self.unit_table = unit_table;
+    }
+
     pub fn with_extra_units(&mut self, more_extra_units: &mut Vec<(UnitId, unit::Model)>) {
         self.extra_units.append(more_extra_units);
     }
@@ -609,6 +724,7 @@ impl TryFrom<GameInitFlags<'_>> for Game {
             lobby,
             rng,
             extra_units,
+            unit_table,
         } = params;
 
         let num_players = lobby.num_players();
@@ -684,10 +800,21 @@ impl TryFrom<GameInitFlags<'_>> for Game {
                     remaining_guests_with_militaries,
                 ]
                 .concat()
-                .to_vec();
+                .into_iter()
+                // Units are made with the bundled stats, so they
+                // are switched over to the ones for this game
+                .map(|(unit_id, unit_model)| (unit_id, unit_model.with_table(&unit_table)))
+                .collect();
 
                 let indexes = Indexes::make(units);
 
+                let weather = Weather::Clear;
+
+                let conditions = Conditions {
+                    time_of_day: Time::from_turn(0).day_part,
+                    weather: weather.clone(),
+                };
+
                 let host_id = lobby.host_id.clone();
 
                 let remaining_guests: Vec<(Id, Guest)> = rest
@@ -695,7 +822,13 @@ impl TryFrom<GameInitFlags<'_>> for Game {
                     .map(|(guest_id, guest_player)| {
                         let guest = Guest {
                             player: guest_player.clone(),
-                            visibility: calculate_player_visibility(guest_id, &map, &indexes.by_id),
+                            visibility: calculate_player_visibility(
+                                guest_id,
+                                &map,
+                                &indexes.by_id,
+                                &conditions,
+                                &unit_table,
+                            ),
                             turn: Turn::Waiting,
                         };
 
@@ -703,12 +836,23 @@ impl TryFrom<GameInitFlags<'_>> for Game {
                     })
                     .collect();
 
-                let host_visibility = calculate_player_visibility(&host_id, &map, &indexes.by_id);
+                let host_visibility = calculate_player_visibility(
+                    &host_id,
+                    &map,
+                    &indexes.by_id,
+                    &conditions,
+                    &unit_table,
+                );
 
-                let first_guest_visibility =
-                    calculate_player_visibility(first_guest_id, &map, &indexes.by_id);
+                let first_guest_visibility = calculate_player_visibility(
+                    first_guest_id,
+                    &map,
+                    &indexes.by_id,
+                    &conditions,
+                    &unit_table,
+                );
 
-                let game = Game {
+                let mut game = Game {
                     host: lobby.host,
                     host_id,
                     host_visibility,
@@ -720,13 +864,294 @@ impl TryFrom<GameInitFlags<'_>> for Game {
                     remaining_guests,
                     indexes,
                     map,
+                    unit_table,
                     turn_number: 0,
+                    weather,
                     turns_changes: Vec::new(),
                     prev_turns_events: vec![],
+                    prev_turns_errors: vec![],
                 };
 
+                game.trace_supply_lines();
+
                 Ok(game)
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test_visibility {
+    use crate::facing_direction::FacingDirection;
+    use crate::game::calculate_player_visibility;
+    use crate::game::conditions::Conditions;
+    use crate::game::day::TimeOfDay;
+    use crate::game::unit_index::Indexes;
+    use crate::game::weather::Weather;
+    use crate::id::Id;
+    use crate::located;
+    use crate::map::{DevFlags, Map};
+    use crate::team_color::TeamColor;
+    use crate::tile::Tile;
+    use crate::unit;
+    use crate::unit::definition::Table;
+    use crate::unit::{Place, Unit, UnitId};
+    use std::convert::TryInto;
+
+    fn infantry_at(x: u16, y: u16, player_id: &Id) -> Indexes {
+        Indexes::make(vec![(
+            UnitId::test("infantry"),
+            unit::Model::new(
+                Unit::Infantry,
+                player_id,
+                Place::on_map(x, y, FacingDirection::Right),
+                &TeamColor::Red,
+            ),
+        )])
+    }
+
+    fn map_from(src: &str) -> Map {
+        DevFlags {
+            base_tile: Tile::GrassPlain,
+            src: src.to_string(),
+        }
+        .try_into()
+        .unwrap()
+    }
+
+    #[test]
+    fn hills_block_sight_behind_them() {
+        let player_id = Id::test("red");
+        let indexes = infantry_at(0, 0, &player_id);
+        let map = map_from("GHGG");
+
+        let visibility = calculate_player_visibility(
+            &player_id,
+            &map,
+            &indexes.by_id,
+            &Conditions::test(),
+            Table::bundled(),
+        );
+
+        assert!(visibility.contains(&located::unit(1, 0)));
+        assert!(!visibility.contains(&located::unit(2, 0)));
+    }
+
+    #[test]
+    fn sight_comes_from_the_games_unit_table() {
+        let player_id = Id::test("red");
+        let indexes = infantry_at(0, 0, &player_id);
+        let map = map_from("GGGGGGGGGG");
+
+        let src = include_str!("unit/units.txt").replacen(
+            "visibility_budget = 3.5",
+            "visibility_budget = 8.0",
+            1,
+        );
+        let far_sighted = Table::from_src(src.as_str()).unwrap();
+
+        let bundled = calculate_player_visibility(
+            &player_id,
+            &map,
+            &indexes.by_id,
+            &Conditions::test(),
+            Table::bundled(),
+        );
+        let rebalanced = calculate_player_visibility(
+            &player_id,
+            &map,
+            &indexes.by_id,
+            &Conditions::test(),
+            &far_sighted,
+        );
+
+        assert!(!bundled.contains(&located::unit(6, 0)));
+        assert!(rebalanced.contains(&located::unit(6, 0)));
+    }
+
+    #[test]
+    fn hills_extend_sight_from_on_top() {
+        let player_id = Id::test("red");
+        let map = map_from("HGGGGGG");
+
+        let from_grass = calculate_player_visibility(
+            &player_id,
+            &map_from("GGGGGGG"),
+            &infantry_at(0, 0, &player_id).by_id,
+            &Conditions::test(),
+            Table::bundled(),
+        );
+        let from_hill = calculate_player_visibility(
+            &player_id,
+            &map,
+            &infantry_at(0, 0, &player_id).by_id,
+            &Conditions::test(),
+            Table::bundled(),
+        );
+
+        assert!(!from_grass.contains(&located::unit(5, 0)));
+        assert!(from_hill.contains(&located::unit(5, 0)));
+    }
+
+    #[test]
+    fn forests_hide_units_unless_adjacent() {
+        let player_id = Id::test("red");
+        let map = map_from("GGF");
+
+        let from_afar = calculate_player_visibility(
+            &player_id,
+            &map,
+            &infantry_at(0, 0, &player_id).by_id,
+            &Conditions::test(),
+            Table::bundled(),
+        );
+        let from_next_to = calculate_player_visibility(
+            &player_id,
+            &map,
+            &infantry_at(1, 0, &player_id).by_id,
+            &Conditions::test(),
+            Table::bundled(),
+        );
+
+        assert!(!from_afar.contains(&located::unit(2, 0)));
+        assert!(from_next_to.contains(&located::unit(2, 0)));
+    }
+
+    #[test]
+    fn night_cuts_sight() {
+        let player_id = Id::test("red");
+        let map = map_from("GGGG");
+        let indexes = infantry_at(0, 0, &player_id);
+        let at_night = Conditions {
+            time_of_day: TimeOfDay::Night,
+            ..Conditions::test()
+        };
+
+        let by_day = calculate_player_visibility(
+            &player_id,
+            &map,
+            &indexes.by_id,
+            &Conditions::test(),
+            Table::bundled(),
+        );
+        let by_night = calculate_player_visibility(
+            &player_id,
+            &map,
+            &indexes.by_id,
+            &at_night,
+            Table::bundled(),
+        );
+
+        assert!(by_day.contains(&located::unit(3, 0)));
+        assert!(!by_night.contains(&located::unit(3, 0)));
+        assert!(by_night.contains(&located::unit(1, 0)));
+    }
+
+    #[test]
+    fn fog_cuts_sight() {
+        let player_id = Id::test("red");
+        let map = map_from("GGGG");
+        let indexes = infantry_at(0, 0, &player_id);
+        let in_fog = Conditions {
+            weather: Weather::Fog,
+            ..Conditions::test()
+        };
+
+        let visibility = calculate_player_visibility(
+            &player_id,
+            &map,
+            &indexes.by_id,
+            &in_fog,
+            Table::bundled(),
+        );
+
+        assert!(!visibility.contains(&located::unit(2, 0)));
+        assert!(visibility.contains(&located::unit(1, 0)));
+    }
+}
+
+#[cfg(test)]
+mod test_turn_errors {
+    use crate::facing_direction::FacingDirection;
+    use crate::game::action::Action;
+    use crate::game::event::TurnError;
+    use crate::game::{Game, GameInitFlags};
+    use crate::id::Id;
+    use crate::lobby::Lobby;
+    use crate::located;
+    use crate::name::Name;
+    use crate::player::Player;
+    use crate::rng::{RandGen, RandSeed};
+    use crate::team_color::TeamColor;
+    use crate::unit;
+    use crate::unit::{Place, Unit, UnitId};
+    use std::convert::TryInto;
+    use std::str::FromStr;
+
+    #[test]
+    fn turn_errors_only_reach_the_players_they_are_for() {
+        let mut rng = RandGen::test();
+
+        let host_id = Id::test("host");
+        let guest_id = Id::test("guest");
+
+        let engineer_id = UnitId::test("host engineer");
+
+        let mut lobby = Lobby::new(
+            host_id.clone(),
+            Player::new(Name::from_str("host").unwrap(), TeamColor::Red),
+        );
+        lobby
+            .add_guest(
+                guest_id.clone(),
+                Player::new(Name::from_str("guest").unwrap(), TeamColor::Blue),
+            )
+            .unwrap();
+
+        let mut game_init_flags = GameInitFlags::new(lobby, &mut rng);
+        game_init_flags.with_extra_units(&mut vec![(
+            engineer_id.clone(),
+            unit::Model::new(
+                Unit::Engineer,
+                &host_id,
+                Place::on_map(8, 8, FacingDirection::Right),
+                &TeamColor::Red,
+            ),
+        )]);
+
+        let mut game: Game = game_init_flags.try_into().unwrap();
+
+        game.set_turn(
+            host_id.clone(),
+            vec![Action::Demolish {
+                unit_id: engineer_id,
+                target: located::unit(9, 8),
+            }],
+        )
+        .unwrap();
+        game.set_turn(guest_id.clone(), vec![]).unwrap();
+
+        assert_eq!(Ok(true), game.advance_turn(RandSeed::test()));
+
+        assert_eq!(
+            vec!["process action error : there is no structure to demolish".to_string()],
+            game.get_players_turn_errors(&host_id)
+        );
+        assert!(game.get_players_turn_errors(&guest_id).is_empty());
+
+        // Errors that could not be traced back to
+        // one player are shown to everyone
+        game.prev_turns_errors.push(TurnError {
+            player_id: None,
+            message: "could not find unit".to_string(),
+        });
+
+        assert!(game
+            .get_players_turn_errors(&host_id)
+            .contains(&"could not find unit".to_string()));
+        assert_eq!(
+            vec!["could not find unit".to_string()],
+            game.get_players_turn_errors(&guest_id)
+        );
+    }
+}
@@ -1,9 +1,14 @@
+use crate::direction::Direction;
+use crate::game::conditions::Conditions;
 use crate::game::unit_index::by_id;
 use crate::id::Id;
 use crate::located::Located;
+use crate::map::Map;
 use crate::path::Path;
 use crate::rng::RandGen;
+use crate::tile::Tile;
 use crate::unit;
+use crate::unit::definition::Table;
 use crate::unit::UnitId;
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
@@ -35,6 +40,43 @@ pub enum Action {
         path: Path,
     },
     Attack(Attack),
+    Entrench {
+        unit_id: UnitId,
+        against: Direction,
+    },
+    Bombard {
+        unit_id: UnitId,
+        target: Located<()>,
+    },
+    Build {
+        unit_id: UnitId,
+        target: Located<()>,
+        structure: Tile,
+    },
+    Demolish {
+        unit_id: UnitId,
+        target: Located<()>,
+    },
+    // A transport hands some of the supplies in the crates
+    // it carries over to the crates a neighbouring
+    // transport carries
+    TransferSupplies {
+        unit_id: UnitId,
+        receiving_unit_id: UnitId,
+        amount: i16,
+    },
+    // Some of a carried crate is left behind as a new
+    // crate where its transport is
+    SplitCrate {
+        crate_id: UnitId,
+        amount: i16,
+    },
+    // A crate empties as much as it can into another
+    // crate on the same tile
+    MergeCrates {
+        crate_id: UnitId,
+        into_crate_id: UnitId,
+    },
     Batch(Vec<Action>),
 }
 
@@ -44,7 +86,7 @@ pub struct Attack {
     pub path: Path,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ClosestCrossingEnemyPath<'a> {
     pub action_index: usize,
     pub action: &'a Action,
@@ -125,6 +167,34 @@ impl Action {
                     None
                 }
             }
+            Action::Entrench { unit_id, .. }
+            | Action::Bombard { unit_id, .. }
+            | Action::Build { unit_id, .. }
+            | Action::Demolish { unit_id, .. }
+            | Action::SplitCrate {
+                crate_id: unit_id, ..
+            } => {
+                if unit_id.clone() == deleted_unit_id {
+                    Some(vec![])
+                } else {
+                    None
+                }
+            }
+            Action::TransferSupplies {
+                unit_id,
+                receiving_unit_id: other_unit_id,
+                ..
+            }
+            | Action::MergeCrates {
+                crate_id: unit_id,
+                into_crate_id: other_unit_id,
+            } => {
+                if unit_id.clone() == deleted_unit_id || other_unit_id.clone() == deleted_unit_id {
+                    Some(vec![])
+                } else {
+                    None
+                }
+            }
             Action::Batch(_) => None,
         }
     }
@@ -136,6 +206,13 @@ impl Action {
             Action::DropOff { .. } => 10,
             Action::Replenish { .. } => 0,
             Action::Attack { .. } => 5,
+            Action::Entrench { .. } => 10,
+            Action::Bombard { .. } => 5,
+            Action::Build { .. } => 10,
+            Action::Demolish { .. } => 10,
+            Action::TransferSupplies { .. } => 10,
+            Action::SplitCrate { .. } => 10,
+            Action::MergeCrates { .. } => 10,
             Action::Batch(_) => 10,
         }
     }
@@ -153,6 +230,13 @@ impl Action {
                 None
             }
             Action::Attack(Attack { path, .. }) => Some(path),
+            Action::Entrench { .. } => None,
+            Action::Bombard { .. } => None,
+            Action::Build { .. } => None,
+            Action::Demolish { .. } => None,
+            Action::TransferSupplies { .. } => None,
+            Action::SplitCrate { .. } => None,
+            Action::MergeCrates { .. } => None,
         }
     }
 
@@ -167,10 +251,43 @@ impl Action {
                 ..
             } => Some(replenishing_unit_id),
             Action::Attack(Attack { unit_id, .. }) => Some(unit_id),
+            Action::Entrench { .. } => None,
+            Action::Bombard { .. } => None,
+            Action::Build { .. } => None,
+            Action::Demolish { .. } => None,
+            Action::TransferSupplies { .. } => None,
+            Action::SplitCrate { .. } => None,
+            Action::MergeCrates { .. } => None,
             Action::Batch(_) => None,
         }
     }
 
+    // Whether carrying out the action changes where
+    // the given unit is
+    pub fn moves_unit(&self, unit_id: &UnitId) -> bool {
+        match self {
+            Action::DropOff { cargo_id } => cargo_id == unit_id,
+            Action::PickUp { cargo_id, .. } if cargo_id == unit_id => true,
+            Action::Batch(actions) => actions.iter().any(|action| action.moves_unit(unit_id)),
+            _ => self.moving_unit() == Some(unit_id),
+        }
+    }
+
+    // The unit carrying out the action, whether or not it moves
+    pub fn unit_id(&self) -> Option<&UnitId> {
+        match self {
+            Action::DropOff { cargo_id } => Some(cargo_id),
+            Action::Entrench { unit_id, .. } => Some(unit_id),
+            Action::Bombard { unit_id, .. } => Some(unit_id),
+            Action::Build { unit_id, .. } => Some(unit_id),
+            Action::Demolish { unit_id, .. } => Some(unit_id),
+            Action::TransferSupplies { unit_id, .. } => Some(unit_id),
+            Action::SplitCrate { crate_id, .. } => Some(crate_id),
+            Action::MergeCrates { crate_id, .. } => Some(crate_id),
+            _ => self.moving_unit(),
+        }
+    }
+
     pub fn closest_crossing_enemy_path<'a>(
         by_id: &by_id::Index,
         path: &Path,
@@ -190,7 +307,8 @@ impl Action {
                 {
                     (unit_id, action_path)
                 } else {
-                    break;
+                    i += 1;
+                    continue;
                 };
 
                 let unit_model = match by_id.get(unit_id) {
@@ -287,6 +405,78 @@ impl Action {
             _ => None,
         }
     }
+
+    // What the given unit pays to carry out this action, if it
+    // goes as planned. Units that are only along for the ride pay
+    // nothing, and attackers pay for the battle on top of the
+    // travel up to the enemy
+    pub fn supply_cost_for(
+        &self,
+        unit_id: &UnitId,
+        unit_model: &unit::Model,
+        map: &Map,
+        conditions: &Conditions,
+        unit_table: &Table,
+    ) -> i16 {
+        let definition = unit_model.definition(unit_table);
+
+        let whole_cost = |cost: Option<f32>| cost.map(|cost| cost.ceil() as i16).unwrap_or(0);
+
+        match self {
+            Action::Travel {
+                unit_id: mover_id,
+                path,
+                ..
+            }
+            | Action::LoadInto {
+                unit_id: mover_id,
+                path,
+                ..
+            }
+            | Action::PickUp {
+                unit_id: mover_id,
+                path,
+                ..
+            }
+            | Action::Replenish {
+                replenishing_unit_id: mover_id,
+                path,
+                ..
+            } if mover_id == unit_id => path.supply_cost(map, definition, conditions),
+            Action::Attack(attack) if &attack.unit_id == unit_id => {
+                let path_to_enemy = match attack.path.last_pos() {
+                    Some(enemy_loc) => attack.path.until(&enemy_loc),
+                    None => attack.path.clone(),
+                };
+
+                path_to_enemy.supply_cost(map, definition, conditions)
+                    + whole_cost(definition.battle_supply_cost)
+            }
+            Action::Entrench {
+                unit_id: entrencher_id,
+                ..
+            } if entrencher_id == unit_id => whole_cost(definition.entrench_supply_cost),
+            Action::Bombard {
+                unit_id: bombarder_id,
+                ..
+            } if bombarder_id == unit_id => whole_cost(definition.bombard_supply_cost),
+            Action::Build {
+                unit_id: builder_id,
+                ..
+            }
+            | Action::Demolish {
+                unit_id: builder_id,
+                ..
+            } if builder_id == unit_id => whole_cost(definition.build_supply_cost),
+            Action::Batch(actions) => actions
+                .iter()
+                .map(|action| {
+                    action.supply_cost_for(unit_id, unit_model, map, conditions, unit_table)
+                })
+                .sum(),
+            _ => 0,
+        }
+    }
 }
 
 impl Ord for Action {
@@ -365,6 +555,13 @@ pub fn order(rng: &mut RandGen, actions: &mut Vec<Action>) {
                             Action::DropOff { .. } => {}
                             Action::Replenish { .. } => {}
                             Action::Attack { .. } => {}
+                            Action::Entrench { .. } => {}
+                            Action::Bombard { .. } => {}
+                            Action::Build { .. } => {}
+                            Action::Demolish { .. } => {}
+                            Action::TransferSupplies { .. } => {}
+                            Action::SplitCrate { .. } => {}
+                            Action::MergeCrates { .. } => {}
                         }
 
                         j += 1;
@@ -402,6 +599,13 @@ pub fn order(rng: &mut RandGen, actions: &mut Vec<Action>) {
                         Action::DropOff { .. } => {}
                         Action::Replenish { .. } => {}
                         Action::Attack { .. } => {}
+                        Action::Entrench { .. } => {}
+                        Action::Bombard { .. } => {}
+                        Action::Build { .. } => {}
+                        Action::Demolish { .. } => {}
+                        Action::TransferSupplies { .. } => {}
+                        Action::SplitCrate { .. } => {}
+                        Action::MergeCrates { .. } => {}
                     }
 
                     j += 1;
@@ -412,6 +616,13 @@ pub fn order(rng: &mut RandGen, actions: &mut Vec<Action>) {
             Action::DropOff { .. } => {}
             Action::Replenish { .. } => {}
             Action::Attack { .. } => {}
+            Action::Entrench { .. } => {}
+            Action::Bombard { .. } => {}
+            Action::Build { .. } => {}
+            Action::Demolish { .. } => {}
+            Action::TransferSupplies { .. } => {}
+            Action::SplitCrate { .. } => {}
+            Action::MergeCrates { .. } => {}
         }
 
         i += 1;
@@ -434,7 +645,7 @@ pub fn order(rng: &mut RandGen, actions: &mut Vec<Action>) {
 mod test_game_actions {
     use crate::direction::Direction;
     use crate::facing_direction::FacingDirection;
-    use crate::game::action::{order, unbatch, Action, Attack};
+    use crate::game::action::{order, unbatch, Action, Attack, ClosestCrossingEnemyPath};
     use crate::game::unit_index::by_id;
     use crate::id::Id;
     use crate::located::Located;
@@ -749,15 +960,15 @@ mod test_game_actions {
         )
         .unwrap();
 
-        let want: Option<(usize, &Action, Located<(UnitId, unit::Model)>)> = Some((
-            1,
-            &blue_infantry_travel_action,
-            Located {
+        let want = Some(ClosestCrossingEnemyPath {
+            action_index: 1,
+            action: &blue_infantry_travel_action,
+            unit_loc: Located {
                 x: 3,
                 y: 2,
                 value: (blue_infantry_id, blue_infantry),
             },
-        ));
+        });
 
         assert_eq!(want, got)
     }
@@ -0,0 +1,23 @@
+use crate::game::day::TimeOfDay;
+use crate::game::weather::Weather;
+
+// Everything outside of the map and the units
+// that changes how a turn plays out
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conditions {
+    pub time_of_day: TimeOfDay,
+    pub weather: Weather,
+}
+
+impl Conditions {
+    pub fn test() -> Conditions {
+        Conditions {
+            time_of_day: TimeOfDay::Midday,
+            weather: Weather::Clear,
+        }
+    }
+
+    pub fn visibility_multiplier(&self) -> f32 {
+        self.time_of_day.visibility_multiplier() * self.weather.visibility_multiplier()
+    }
+}
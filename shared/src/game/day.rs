@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Debug, Clone)]
 pub struct Time {
     pub day_num: u32,
@@ -17,7 +19,7 @@ impl Time {
             2 => TimeOfDay::Morning,
             3 => TimeOfDay::Midday,
             4 => TimeOfDay::Evening,
-            5 => TimeOfDay::Midnight,
+            5 => TimeOfDay::Night,
             n => {
                 unreachable!("{} turn mod 6 is {}", shifted_number, n);
             }
@@ -40,7 +42,7 @@ impl ToString for Time {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TimeOfDay {
     Morning,
     Midday,
@@ -50,15 +52,76 @@ pub enum TimeOfDay {
     EarlyMorning,
 }
 
-impl ToString for TimeOfDay {
-    fn to_string(&self) -> String {
+impl TimeOfDay {
+    fn is_dark(&self) -> bool {
+        matches!(self, TimeOfDay::Night | TimeOfDay::Midnight)
+    }
+
+    // How far units can see compared to the middle
+    // of the day
+    pub fn visibility_multiplier(&self) -> f32 {
+        match self {
+            TimeOfDay::Morning => 1.0,
+            TimeOfDay::Midday => 1.0,
+            TimeOfDay::Evening => 0.75,
+            TimeOfDay::Night => 0.5,
+            TimeOfDay::Midnight => 0.5,
+            TimeOfDay::EarlyMorning => 0.75,
+        }
+    }
+
+    // Moving in the dark is slow going, so it
+    // burns more supplies
+    pub fn travel_cost_multiplier(&self) -> f32 {
+        if self.is_dark() {
+            1.5
+        } else {
+            1.0
+        }
+    }
+
+    // Attacks made in the dark catch defenders
+    // by surprise
+    pub fn surprise_multiplier(&self) -> f32 {
+        if self.is_dark() {
+            1.25
+        } else {
+            1.0
+        }
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            TimeOfDay::Morning => "morning".to_string(),
-            TimeOfDay::Midday => "midday".to_string(),
-            TimeOfDay::Evening => "evening".to_string(),
-            TimeOfDay::Night => "night".to_string(),
-            TimeOfDay::Midnight => "midnight".to_string(),
-            TimeOfDay::EarlyMorning => "early morning".to_string(),
+            TimeOfDay::Morning => write!(f, "morning"),
+            TimeOfDay::Midday => write!(f, "midday"),
+            TimeOfDay::Evening => write!(f, "evening"),
+            TimeOfDay::Night => write!(f, "night"),
+            TimeOfDay::Midnight => write!(f, "midnight"),
+            TimeOfDay::EarlyMorning => write!(f, "early morning"),
         }
     }
 }
+
+#[cfg(test)]
+mod test_day {
+    use crate::game::day::{Time, TimeOfDay};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn every_part_of_the_day_comes_around() {
+        let got: Vec<TimeOfDay> = (0..6).map(|turn| Time::from_turn(turn).day_part).collect();
+
+        let want = vec![
+            TimeOfDay::Morning,
+            TimeOfDay::Midday,
+            TimeOfDay::Evening,
+            TimeOfDay::Night,
+            TimeOfDay::Midnight,
+            TimeOfDay::EarlyMorning,
+        ];
+
+        assert_eq!(want, got);
+    }
+}
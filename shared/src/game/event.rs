@@ -1,17 +1,24 @@
 mod battle;
 
+use crate::direction::Direction;
 use crate::facing_direction::FacingDirection;
 use crate::game::action::{Action, Attack, ClosestCrossingEnemyPath};
+use crate::game::conditions::Conditions;
 use crate::game::replenishment::Replenishment;
 use crate::game::{action, unit_index};
 use crate::id::Id;
 use crate::located::Located;
-use crate::map::Map;
+use crate::map;
+use crate::map::{Map, Town};
 use crate::path::Path;
 use crate::rng::{RandGen, RandSeed};
+use crate::tile::Tile;
 use crate::unit;
+use crate::unit::definition::Table;
 use crate::unit::{Place, UnitId};
 use serde::{Deserialize, Serialize};
+use std::cmp;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub enum Event {
@@ -53,19 +60,170 @@ pub enum Event {
     },
     Perished {
         unit_id: UnitId,
-    }, // Battle {},
+    },
+    Battle {
+        attacker_id: UnitId,
+        defender_id: UnitId,
+        loc: Located<()>,
+    },
+    Damaged {
+        unit_id: UnitId,
+        damage: u8,
+    },
+    Destroyed {
+        unit_id: UnitId,
+    },
+    ConsumedBattleSupplies {
+        unit_id: UnitId,
+        cost: i16,
+    },
+    Recovered {
+        unit_id: UnitId,
+    },
+    Entrenched {
+        unit_id: UnitId,
+        against: Direction,
+        cost: i16,
+    },
+    Retreated {
+        unit_id: UnitId,
+        path: Path,
+    },
+    Routed {
+        unit_id: UnitId,
+    },
+    Captured {
+        unit_id: UnitId,
+        capturer_id: UnitId,
+    },
+    Bombarded {
+        unit_id: UnitId,
+        target: Located<()>,
+        cost: i16,
+    },
+    Built {
+        unit_id: UnitId,
+        loc: Located<()>,
+        structure: Tile,
+        cost: i16,
+    },
+    Demolished {
+        unit_id: UnitId,
+        loc: Located<()>,
+        cost: i16,
+    },
+    TerrainChanged {
+        unit_id: UnitId,
+        loc: Located<()>,
+        tile: Tile,
+    },
+    TownCaptured {
+        unit_id: UnitId,
+        loc: Located<()>,
+    },
+    SuppliedByTown {
+        unit_id: UnitId,
+        town: Located<()>,
+        amount: i16,
+    },
+    Stockpiled {
+        unit_id: UnitId,
+        amount: i16,
+    },
+    ProducedSupplyCrate {
+        unit_id: UnitId,
+        crate_id: UnitId,
+    },
+    TransferredSupplies {
+        unit_id: UnitId,
+        receiving_unit_id: UnitId,
+        amount: i16,
+    },
+    SplitCrate {
+        unit_id: UnitId,
+        new_crate_id: UnitId,
+        amount: i16,
+    },
+    MergedCrates {
+        unit_id: UnitId,
+        into_crate_id: UnitId,
+        amount: i16,
+    },
+    Foraged {
+        unit_id: UnitId,
+        loc: Located<()>,
+        amount: i16,
+    },
+}
+
+impl Event {
+    // The unit whose owner should hear about it if
+    // this event goes wrong
+    fn unit_id(&self) -> &UnitId {
+        match self {
+            Event::ConsumedBaselineSupplies { unit_id, .. } => unit_id,
+            Event::Travelled { unit_id, .. } => unit_id,
+            Event::Loaded { cargo_id, .. } => cargo_id,
+            Event::PickedUp { transport_id, .. } => transport_id,
+            Event::DroppedOff { cargo_id, .. } => cargo_id,
+            Event::ReplenishedUnits { unit_id, .. } => unit_id,
+            Event::WasReplenished { unit_id, .. } => unit_id,
+            Event::DepletedCrate { unit_id, .. } => unit_id,
+            Event::Perished { unit_id } => unit_id,
+            Event::Battle { attacker_id, .. } => attacker_id,
+            Event::Damaged { unit_id, .. } => unit_id,
+            Event::Destroyed { unit_id } => unit_id,
+            Event::ConsumedBattleSupplies { unit_id, .. } => unit_id,
+            Event::Recovered { unit_id } => unit_id,
+            Event::Entrenched { unit_id, .. } => unit_id,
+            Event::Retreated { unit_id, .. } => unit_id,
+            Event::Routed { unit_id } => unit_id,
+            Event::Captured { unit_id, .. } => unit_id,
+            Event::Bombarded { unit_id, .. } => unit_id,
+            Event::Built { unit_id, .. } => unit_id,
+            Event::Demolished { unit_id, .. } => unit_id,
+            Event::TerrainChanged { unit_id, .. } => unit_id,
+            Event::TownCaptured { unit_id, .. } => unit_id,
+            Event::SuppliedByTown { unit_id, .. } => unit_id,
+            Event::Stockpiled { unit_id, .. } => unit_id,
+            Event::ProducedSupplyCrate { unit_id, .. } => unit_id,
+            Event::TransferredSupplies { unit_id, .. } => unit_id,
+            Event::SplitCrate { unit_id, .. } => unit_id,
+            Event::MergedCrates { unit_id, .. } => unit_id,
+            Event::Foraged { unit_id, .. } => unit_id,
+        }
+    }
 }
 
 pub struct ProcessedTurn {
     pub events: Vec<Event>,
-    pub errors: Vec<String>,
+    pub errors: Vec<TurnError>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct TurnError {
+    // None if the error could not be traced back
+    // to any one player
+    pub player_id: Option<Id>,
+    pub message: String,
+}
+
+impl TurnError {
+    pub fn is_for_player(&self, player_id: &Id) -> bool {
+        match &self.player_id {
+            Some(error_player_id) => error_player_id == player_id,
+            None => true,
+        }
+    }
 }
 
 pub fn process_turn(
     rand_seed: RandSeed,
     player_moves: &mut Vec<(Id, Vec<Action>)>,
     indexes: &mut unit_index::Indexes,
-    map: &Map,
+    map: &mut Map,
+    unit_table: &Table,
+    conditions: &Conditions,
 ) -> ProcessedTurn {
     let mut rng = RandGen::from_seed(rand_seed);
 
@@ -104,26 +262,141 @@ pub fn process_turn(
 
     action::unbatch(&mut ordered_actions);
 
-    let mut events = baseline_supply_events(indexes);
-    let mut errors: Vec<String> = vec![];
+    let mut events = baseline_supply_events(indexes, conditions, unit_table);
+    let mut errors: Vec<TurnError> = vec![];
     let mut event_index = 0;
-    // while let Some(event) = events.first() {
-    while event_index < events.len() {
-        let event = events.get(event_index).unwrap();
+    let mut end_of_turn_processed = false;
+    // Orders can still be left to carry out, and the end of
+    // the turn decided, once every event so far is dealt with
+    while event_index < events.len() || !end_of_turn_processed {
+        if let Some(event) = events.get(event_index) {
+            let event_player_id = indexes
+                .by_id
+                .get(event.unit_id())
+                .map(|unit_model| unit_model.owner.clone());
 
-        let mut event_error = |err: String| {
-            let mut err_msg = "event error : ".to_string();
+            let mut event_error = |err: String| {
+                let mut err_msg = "event error : ".to_string();
 
-            err_msg.push_str(err.as_str());
+                err_msg.push_str(err.as_str());
 
-            errors.push(err_msg);
-        };
+                errors.push(TurnError {
+                    player_id: event_player_id.clone(),
+                    message: err_msg,
+                });
+            };
 
-        match event {
-            Event::ConsumedBaselineSupplies { unit_id, cost } => {
-                match indexes.consume_base_supplies(unit_id, *cost) {
-                    Ok(consume_baseline_supplies) => {
-                        if consume_baseline_supplies.perished {
+            match event {
+                Event::ConsumedBaselineSupplies { unit_id, cost } => {
+                    match indexes.consume_base_supplies(unit_id, *cost) {
+                        Ok(consume_baseline_supplies) => {
+                            if consume_baseline_supplies.perished {
+                                delete_actions_for_deleted_unit(
+                                    unit_id.clone(),
+                                    &mut ordered_actions,
+                                );
+
+                                events.push(Event::Perished {
+                                    unit_id: unit_id.clone(),
+                                });
+                            }
+                        }
+                        Err(err) => {
+                            event_error(err);
+                        }
+                    }
+                }
+                Event::Travelled { unit_id, path } => {
+                    if let Err(err) =
+                        indexes.travel_unit(unit_id, path, map, conditions, unit_table)
+                    {
+                        event_error(err);
+                    }
+                }
+                Event::Loaded {
+                    cargo_id,
+                    transport_id,
+                    path,
+                } => {
+                    if let Err(err) = indexes.load_into(
+                        unit_index::CargoAndTransportIds {
+                            cargo_id,
+                            transport_id,
+                        },
+                        path,
+                        map,
+                        conditions,
+                        unit_table,
+                    ) {
+                        event_error(err)
+                    }
+                }
+                Event::PickedUp {
+                    cargo_id,
+                    transport_id,
+                    path,
+                } => {
+                    if let Err(err) = indexes.pick_up(
+                        unit_index::CargoAndTransportIds {
+                            cargo_id,
+                            transport_id,
+                        },
+                        path,
+                        map,
+                        conditions,
+                        unit_table,
+                    ) {
+                        event_error(err);
+                    }
+                }
+                Event::DroppedOff { cargo_id, .. } => {
+                    if let Err(err) = indexes.unload(cargo_id) {
+                        event_error(err);
+                    }
+                }
+                Event::ReplenishedUnits { .. } => {
+                    // This is only used for animation
+                }
+                Event::WasReplenished { unit_id, amount } => {
+                    if let Err(err) = indexes.replenish(unit_id, *amount, unit_table) {
+                        event_error(err)
+                    }
+                }
+                Event::DepletedCrate { unit_id, amount } => {
+                    if let Err(err) = indexes.deplete_supply_crate(unit_id, *amount, unit_table) {
+                        event_error(err)
+                    }
+                }
+                Event::Perished { .. } => {
+                    // This is only used for animation
+                }
+                Event::Battle { .. } => {
+                    // This is only used for animation
+                }
+                Event::Damaged { unit_id, damage } => match indexes.damage(unit_id, *damage) {
+                    Ok(take_damage) => {
+                        if take_damage.destroyed {
+                            delete_actions_for_deleted_unit(unit_id.clone(), &mut ordered_actions);
+
+                            events.push(Event::Destroyed {
+                                unit_id: unit_id.clone(),
+                            });
+                        }
+                    }
+                    Err(err) => {
+                        event_error(err);
+                    }
+                },
+                Event::Destroyed { .. } => {
+                    // This is only used for animation
+                }
+                Event::Entrenched {
+                    unit_id,
+                    against,
+                    cost,
+                } => match indexes.entrench(unit_id, against, *cost) {
+                    Ok(consume_supplies) => {
+                        if consume_supplies.perished {
                             delete_actions_for_deleted_unit(unit_id.clone(), &mut ordered_actions);
 
                             events.push(Event::Perished {
@@ -134,81 +407,294 @@ pub fn process_turn(
                     Err(err) => {
                         event_error(err);
                     }
+                },
+                Event::Retreated { unit_id, path } => {
+                    // The unit gives up on whatever else it was doing
+                    cancel_actions_for_unit(unit_id, &mut ordered_actions);
+
+                    if let Err(err) =
+                        indexes.travel_unit(unit_id, path, map, conditions, unit_table)
+                    {
+                        event_error(err);
+                    }
                 }
-            }
-            Event::Travelled { unit_id, path } => {
-                if let Err(err) = indexes.travel_unit(unit_id, path, map) {
-                    event_error(err);
+                Event::Routed { .. } => {
+                    // This is only used for animation
                 }
-            }
-            Event::Loaded {
-                cargo_id,
-                transport_id,
-                path,
-            } => {
-                if let Err(err) = indexes.load_into(
-                    unit_index::CargoAndTransportIds {
-                        cargo_id,
-                        transport_id,
-                    },
-                    path,
-                    map,
-                ) {
-                    event_error(err)
+                Event::Captured {
+                    unit_id,
+                    capturer_id,
+                } => {
+                    if let Err(err) = indexes.capture(unit_id, capturer_id) {
+                        event_error(err);
+                    }
                 }
-            }
-            Event::PickedUp {
-                cargo_id,
-                transport_id,
-                path,
-            } => {
-                if let Err(err) = indexes.pick_up(
-                    unit_index::CargoAndTransportIds {
-                        cargo_id,
-                        transport_id,
-                    },
-                    path,
-                    map,
-                ) {
-                    event_error(err);
+                Event::Recovered { unit_id } => {
+                    if let Err(err) = indexes.recover(unit_id) {
+                        event_error(err);
+                    }
                 }
-            }
-            Event::DroppedOff { cargo_id, .. } => {
-                if let Err(err) = indexes.unload(cargo_id) {
-                    event_error(err);
+                Event::Bombarded { unit_id, cost, .. } => {
+                    match indexes.consume_base_supplies(unit_id, *cost) {
+                        Ok(consume_supplies) => {
+                            if consume_supplies.perished {
+                                delete_actions_for_deleted_unit(
+                                    unit_id.clone(),
+                                    &mut ordered_actions,
+                                );
+
+                                events.push(Event::Perished {
+                                    unit_id: unit_id.clone(),
+                                });
+                            }
+                        }
+                        Err(err) => {
+                            event_error(err);
+                        }
+                    }
                 }
-            }
-            Event::ReplenishedUnits { .. } => {
-                // This is only used for animation
-            }
-            Event::WasReplenished { unit_id, amount } => {
-                if let Err(err) = indexes.replenish(unit_id, *amount) {
-                    event_error(err)
+                Event::Built {
+                    unit_id,
+                    loc,
+                    structure,
+                    cost,
+                } => {
+                    let unit_id = unit_id.clone();
+                    let loc = loc.clone();
+                    let structure = structure.clone();
+                    let cost = *cost;
+
+                    match indexes.by_id.get(&unit_id).map(|u| u.owner.clone()) {
+                        Some(owner) => match map.work_on(&loc, &structure, &owner) {
+                            Ok(finished) => {
+                                if finished {
+                                    events.push(Event::TerrainChanged {
+                                        unit_id: unit_id.clone(),
+                                        loc,
+                                        tile: structure,
+                                    });
+                                }
+                            }
+                            Err(err) => {
+                                event_error(err);
+                            }
+                        },
+                        None => {
+                            event_error("could not find building unit".to_string());
+                        }
+                    }
+
+                    match indexes.consume_base_supplies(&unit_id, cost) {
+                        Ok(consume_supplies) => {
+                            if consume_supplies.perished {
+                                delete_actions_for_deleted_unit(
+                                    unit_id.clone(),
+                                    &mut ordered_actions,
+                                );
+
+                                events.push(Event::Perished { unit_id });
+                            }
+                        }
+                        Err(err) => {
+                            event_error(err);
+                        }
+                    }
                 }
-            }
-            Event::DepletedCrate { unit_id, amount } => {
-                if let Err(err) = indexes.deplete_supply_crate(unit_id, *amount) {
-                    event_error(err)
+                Event::Demolished { unit_id, loc, cost } => {
+                    let unit_id = unit_id.clone();
+                    let loc = loc.clone();
+                    let cost = *cost;
+
+                    match map.demolish(&loc) {
+                        Ok(tile) => {
+                            events.push(Event::TerrainChanged {
+                                unit_id: unit_id.clone(),
+                                loc,
+                                tile,
+                            });
+                        }
+                        Err(err) => {
+                            event_error(err);
+                        }
+                    }
+
+                    match indexes.consume_base_supplies(&unit_id, cost) {
+                        Ok(consume_supplies) => {
+                            if consume_supplies.perished {
+                                delete_actions_for_deleted_unit(
+                                    unit_id.clone(),
+                                    &mut ordered_actions,
+                                );
+
+                                events.push(Event::Perished { unit_id });
+                            }
+                        }
+                        Err(err) => {
+                            event_error(err);
+                        }
+                    }
+                }
+                Event::TerrainChanged { .. } => {
+                    // This is only used for animation, the map
+                    // is changed when the work is done
+                }
+                Event::TownCaptured { unit_id, loc } => {
+                    match indexes.by_id.get(unit_id).map(|u| u.owner.clone()) {
+                        Some(owner) => {
+                            if let Err(err) = map.capture_town(loc, &owner) {
+                                event_error(err);
+                            }
+                        }
+                        None => {
+                            event_error("could not find capturing unit".to_string());
+                        }
+                    }
+                }
+                Event::SuppliedByTown {
+                    unit_id, amount, ..
+                } => {
+                    if let Err(err) = indexes.add_supplies(unit_id, *amount, unit_table) {
+                        event_error(err);
+                    }
+                }
+                Event::Stockpiled { unit_id, amount } => {
+                    if let Err(err) = indexes.add_supplies(unit_id, *amount, unit_table) {
+                        event_error(err);
+                    }
+                }
+                Event::ProducedSupplyCrate { unit_id, crate_id } => {
+                    if let Err(err) = indexes.produce_supply_crate(unit_id, crate_id, unit_table) {
+                        event_error(err);
+                    }
+                }
+                Event::TransferredSupplies {
+                    unit_id,
+                    receiving_unit_id,
+                    amount,
+                } => {
+                    if let Err(err) =
+                        indexes.transfer_supplies(unit_id, receiving_unit_id, *amount, unit_table)
+                    {
+                        event_error(err);
+                    }
+                }
+                Event::SplitCrate {
+                    unit_id,
+                    new_crate_id,
+                    amount,
+                } => {
+                    if let Err(err) =
+                        indexes.split_crate(unit_id, new_crate_id, *amount, unit_table)
+                    {
+                        event_error(err);
+                    }
+                }
+                Event::MergedCrates {
+                    unit_id,
+                    into_crate_id,
+                    amount,
+                } => {
+                    if let Err(err) = indexes
+                        .add_supplies(into_crate_id, *amount, unit_table)
+                        .and_then(|_| indexes.deplete_supply_crate(unit_id, *amount, unit_table))
+                    {
+                        event_error(err);
+                    }
+                }
+                Event::Foraged {
+                    unit_id,
+                    loc,
+                    amount,
+                } => {
+                    if let Err(err) = indexes.add_supplies(unit_id, *amount, unit_table) {
+                        event_error(err);
+                    }
+
+                    map.forage(loc);
+                }
+                Event::ConsumedBattleSupplies { unit_id, cost } => {
+                    // A unit destroyed in the battle has no supplies
+                    // left to consume
+                    if indexes.by_id.get(unit_id).is_some() {
+                        match indexes.consume_base_supplies(unit_id, *cost) {
+                            Ok(consume_supplies) => {
+                                if consume_supplies.perished {
+                                    delete_actions_for_deleted_unit(
+                                        unit_id.clone(),
+                                        &mut ordered_actions,
+                                    );
+
+                                    events.push(Event::Perished {
+                                        unit_id: unit_id.clone(),
+                                    });
+                                }
+                            }
+                            Err(err) => {
+                                event_error(err);
+                            }
+                        }
+                    }
                 }
             }
-            Event::Perished { .. } => {
-                // This is only used for animation
-            }
-        }
 
-        event_index += 1;
+            event_index += 1;
+        }
 
-        if let Some(action) = ordered_actions.first() {
+        // Actions that fail or come to nothing leave no events
+        // behind, so the next action is taken up straight away
+        // rather than letting the turn run out of events early
+        while let Some(action) = ordered_actions.first() {
             let action = action.clone();
             ordered_actions.remove(0);
 
-            if let Err(err) = process_action(action, &mut ordered_actions, indexes, &mut events) {
+            let action_player_id = action
+                .unit_id()
+                .and_then(|unit_id| indexes.by_id.get(unit_id))
+                .map(|unit_model| unit_model.owner.clone());
+
+            if let Err(err) = process_action(
+                action,
+                &mut ordered_actions,
+                indexes,
+                map,
+                conditions,
+                &mut rng,
+                &mut events,
+                unit_table,
+            ) {
                 let mut err_msg = "process action error : ".to_string();
 
                 err_msg.push_str(err.as_str());
 
-                errors.push(err_msg);
+                errors.push(TurnError {
+                    player_id: action_player_id,
+                    message: err_msg,
+                });
             };
+
+            if event_index < events.len() {
+                break;
+            }
+        }
+
+        // Captures, recovery and town supplies depend on where
+        // units ended up this turn, so they are decided last
+        if event_index == events.len() && ordered_actions.is_empty() && !end_of_turn_processed {
+            let mut capture_events = capture_events(indexes, unit_table);
+            let mut recovery_events = recovery_events(indexes, &events, unit_table);
+            let mut town_capture_events = town_capture_events(indexes, map, unit_table);
+            let mut town_supply_events = town_supply_events(indexes, map, unit_table);
+            let mut supply_depot_events = supply_depot_events(indexes, &mut rng, unit_table);
+            let mut forage_events = forage_events(indexes, map, &events, unit_table);
+
+            events.append(&mut capture_events);
+            events.append(&mut recovery_events);
+            events.append(&mut town_capture_events);
+            events.append(&mut town_supply_events);
+            events.append(&mut supply_depot_events);
+            events.append(&mut forage_events);
+
+            end_of_turn_processed = true;
         }
     }
 
@@ -230,11 +716,18 @@ fn delete_actions_for_deleted_unit(deleted_unit_id: UnitId, actions: &mut Vec<Ac
     }
 }
 
+// Unlike a deleted unit, a unit that is only pushed back
+// is still there, so orders other units have involving it
+// are left alone
+fn cancel_actions_for_unit(unit_id: &UnitId, actions: &mut Vec<Action>) {
+    actions.retain(|action| action.unit_id() != Some(unit_id));
+}
+
 enum AttackCondition {
     NoEnemies,
     StationaryEnemies(Located<Vec<(UnitId, unit::Model)>>),
     MovingEnemy {
-        enemy_loc: Located<(UnitId, unit::Model)>,
+        enemy_loc: Located<UnitId>,
         action_index: usize,
         action: Action,
     },
@@ -247,6 +740,7 @@ impl AttackCondition {
         player_id: Id,
         origin: Located<()>,
         actions: &[Action],
+        unit_table: &Table,
     ) -> Result<AttackCondition, String> {
         let maybe_closest_enemy_path = Action::closest_crossing_enemy_path(
             &indexes.by_id,
@@ -255,9 +749,10 @@ impl AttackCondition {
             actions,
         )?;
 
-        let maybe_closest_stationary_enemy = indexes
-            .by_location
-            .closest_enemy_units_in_path(player_id, &attack.path);
+        let maybe_closest_stationary_enemy =
+            indexes
+                .by_location
+                .closest_enemy_units_in_path(player_id, &attack.path, unit_table);
 
         let attack_conditions: AttackCondition =
             match (maybe_closest_enemy_path, maybe_closest_stationary_enemy) {
@@ -270,7 +765,7 @@ impl AttackCondition {
                     None,
                 ) => AttackCondition::MovingEnemy {
                     action_index,
-                    enemy_loc: moving_enemy_loc,
+                    enemy_loc: moving_enemy_loc.map_value(|(enemy_id, _)| enemy_id),
                     action: enemy_action.clone(),
                 },
                 (None, Some(loc_enemies)) => AttackCondition::StationaryEnemies(loc_enemies),
@@ -288,7 +783,7 @@ impl AttackCondition {
                         AttackCondition::StationaryEnemies(stationary_enemies_loc)
                     } else {
                         AttackCondition::MovingEnemy {
-                            enemy_loc: moving_enemy_loc,
+                            enemy_loc: moving_enemy_loc.map_value(|(enemy_id, _)| enemy_id),
                             action_index,
                             action: enemy_action.clone(),
                         }
@@ -301,14 +796,22 @@ impl AttackCondition {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn process_action(
     action: Action,
     remaining_actions: &mut Vec<Action>,
     indexes: &unit_index::Indexes,
+    map: &Map,
+    conditions: &Conditions,
+    rng: &mut RandGen,
     events: &mut Vec<Event>,
+    unit_table: &Table,
 ) -> Result<(), String> {
     match action {
         Action::Travel { path, unit_id, .. } => {
+            let path =
+                indexes.zone_of_control_path(&unit_id, &path, map, conditions, unit_table)?;
+
             events.push(Event::Travelled { unit_id, path });
         }
         Action::LoadInto {
@@ -316,6 +819,22 @@ fn process_action(
             load_into,
             path,
         } => {
+            check_carry(indexes, &load_into, &unit_id, &unit_id, &path)?;
+
+            let halted_path =
+                indexes.zone_of_control_path(&unit_id, &path, map, conditions, unit_table)?;
+
+            // An enemy stopped the unit before it could get
+            // to its transport
+            if halted_path != path {
+                events.push(Event::Travelled {
+                    unit_id,
+                    path: halted_path,
+                });
+
+                return Ok(());
+            }
+
             events.push(Event::Loaded {
                 cargo_id: unit_id,
                 transport_id: load_into,
@@ -327,6 +846,22 @@ fn process_action(
             cargo_id,
             path,
         } => {
+            check_carry(indexes, &unit_id, &cargo_id, &unit_id, &path)?;
+
+            let halted_path =
+                indexes.zone_of_control_path(&unit_id, &path, map, conditions, unit_table)?;
+
+            // An enemy stopped the transport before it could
+            // get to its cargo
+            if halted_path != path {
+                events.push(Event::Travelled {
+                    unit_id,
+                    path: halted_path,
+                });
+
+                return Ok(());
+            }
+
             events.push(Event::PickedUp {
                 cargo_id,
                 transport_id: unit_id,
@@ -378,6 +913,7 @@ fn process_action(
                 &replenishing_unit_id,
                 replenishment_pos,
                 indexes,
+                unit_table,
             )?;
 
             let replenished_unit_ids = replenishment
@@ -416,35 +952,549 @@ fn process_action(
                 }
             };
 
-            let attack_conditions =
-                AttackCondition::determine(indexes, attack, player_id, origin, remaining_actions)?;
+            let attack_conditions = AttackCondition::determine(
+                indexes,
+                attack.clone(),
+                player_id,
+                origin,
+                remaining_actions,
+                unit_table,
+            )?;
 
             match attack_conditions {
-                AttackCondition::NoEnemies => {}
-                AttackCondition::StationaryEnemies(_) => {}
+                AttackCondition::NoEnemies => {
+                    let path = indexes.zone_of_control_path(
+                        &attack.unit_id,
+                        &attack.path,
+                        map,
+                        conditions,
+                        unit_table,
+                    )?;
+
+                    events.push(Event::Travelled {
+                        unit_id: attack.unit_id,
+                        path,
+                    });
+                }
+                AttackCondition::StationaryEnemies(loc_enemies) => {
+                    let enemy_loc = loc_enemies.to_unit();
+
+                    let attacking_from = match attack.path.direction_into(&enemy_loc) {
+                        Some(dir) => dir.opposite(),
+                        None => {
+                            return Err("attack path does not reach enemies".to_string());
+                        }
+                    };
+
+                    let path_to_enemies = attack.path.until(&enemy_loc);
+
+                    // Some other enemy stopped the attacker before
+                    // it could reach the ones it was attacking
+                    let halted_path = indexes.zone_of_control_path(
+                        &attack.unit_id,
+                        &path_to_enemies,
+                        map,
+                        conditions,
+                        unit_table,
+                    )?;
+
+                    if halted_path != path_to_enemies {
+                        events.push(Event::Travelled {
+                            unit_id: attack.unit_id,
+                            path: halted_path,
+                        });
+
+                        return Ok(());
+                    }
+
+                    if !path_to_enemies.is_empty() {
+                        events.push(Event::Travelled {
+                            unit_id: attack.unit_id.clone(),
+                            path: path_to_enemies.clone(),
+                        });
+                    }
+
+                    let mut attacker = match indexes.by_id.get(&attack.unit_id) {
+                        Some(unit_model) => unit_model.clone(),
+                        None => {
+                            return Err("could not find attacking unit".to_string());
+                        }
+                    };
+
+                    // The attacker has not actually arrived yet, but it
+                    // will have by the time the battle is fought
+                    if let Some(last_pos) = path_to_enemies.last_pos() {
+                        let facing_dir = FacingDirection::from_directions(
+                            path_to_enemies.clone().to_directions(),
+                        )
+                        .unwrap_or(FacingDirection::Right);
+
+                        attacker.place = Place::OnMap(last_pos.with_value(facing_dir));
+                    }
+
+                    // The units in the location index might be out of date
+                    let defenders = loc_enemies
+                        .value
+                        .into_iter()
+                        .filter_map(|(unit_id, _)| {
+                            indexes
+                                .by_id
+                                .get(&unit_id)
+                                .map(|unit_model| (unit_id.clone(), unit_model.clone()))
+                        })
+                        .collect::<Vec<(UnitId, unit::Model)>>();
+
+                    let outcome = battle::stationary_battle(
+                        (&attack.unit_id, &attacker),
+                        attacking_from.clone(),
+                        defenders.clone(),
+                        map,
+                        conditions,
+                        unit_table,
+                    )?;
+
+                    let defender = match defenders
+                        .iter()
+                        .find(|(unit_id, _)| unit_id == &outcome.defender_id)
+                    {
+                        Some((_, defender)) => defender,
+                        None => {
+                            return Err("could not find defending unit".to_string());
+                        }
+                    };
+
+                    let mut retreat_events = outcome.retreat_events(
+                        &attacker,
+                        defender,
+                        &attacking_from,
+                        &indexes.by_location,
+                        map,
+                        conditions,
+                        unit_table,
+                    );
+
+                    events.append(&mut outcome.into_events(enemy_loc));
+                    events.append(&mut retreat_events);
+                }
                 AttackCondition::MovingEnemy {
                     action_index,
-                    action: _,
-                    enemy_loc: _,
+                    action: enemy_action,
+                    enemy_loc,
                 } => {
+                    // The enemy does not get to finish what it was doing
                     remaining_actions.remove(action_index);
-                }
-            }
-        }
-        Action::Batch(_) => {}
-    }
 
-    Ok(())
-}
+                    let cross_loc = enemy_loc.to_unit();
+                    let enemy_id = enemy_loc.value;
 
-fn baseline_supply_events(indexes: &unit_index::Indexes) -> Vec<Event> {
-    let mut events = vec![];
+                    let enemy_path = match enemy_action.path() {
+                        Some(path) => path.clone(),
+                        None => {
+                            return Err("moving enemy has no path".to_string());
+                        }
+                    };
+
+                    // Whichever unit arrived at the crossing first is
+                    // the one being attacked from the other's direction
+                    let attacking_from = match (
+                        attack.path.direction_into(&cross_loc),
+                        enemy_path.direction_into(&cross_loc),
+                    ) {
+                        (Some(dir), _) => dir.opposite(),
+                        (None, Some(enemy_dir)) => enemy_dir,
+                        (None, None) => {
+                            return Err(
+                                "neither unit moves into the meeting engagement".to_string()
+                            );
+                        }
+                    };
 
-    for (unit_id, unit_model) in indexes.by_id.iter() {
-        if let Some(supply_cost) = unit_model.unit.baseline_supply_cost() {
-            events.push(Event::ConsumedBaselineSupplies {
+                    let mut combatants = vec![];
+
+                    for (unit_id, path) in [
+                        (&attack.unit_id, attack.path.through(&cross_loc)),
+                        (&enemy_id, enemy_path.through(&cross_loc)),
+                    ] {
+                        let mut unit_model = match indexes.by_id.get(unit_id) {
+                            Some(unit_model) => unit_model.clone(),
+                            None => {
+                                return Err("could not find unit in meeting engagement".to_string());
+                            }
+                        };
+
+                        if !path.is_empty() {
+                            let facing_dir =
+                                FacingDirection::from_directions(path.clone().to_directions())
+                                    .unwrap_or(FacingDirection::Right);
+
+                            unit_model.place = Place::OnMap(cross_loc.with_value(facing_dir));
+
+                            events.push(Event::Travelled {
+                                unit_id: unit_id.clone(),
+                                path,
+                            });
+                        }
+
+                        combatants.push(unit_model);
+                    }
+
+                    let outcome = battle::meeting_engagement(
+                        (&attack.unit_id, &combatants[0]),
+                        attacking_from.clone(),
+                        (&enemy_id, &combatants[1]),
+                        map,
+                        conditions,
+                        unit_table,
+                    );
+
+                    let mut retreat_events = outcome.retreat_events(
+                        &combatants[0],
+                        &combatants[1],
+                        &attacking_from,
+                        &indexes.by_location,
+                        map,
+                        conditions,
+                        unit_table,
+                    );
+
+                    events.append(&mut outcome.into_events(cross_loc));
+                    events.append(&mut retreat_events);
+                }
+            }
+        }
+        Action::Entrench { unit_id, against } => {
+            let unit_model = match indexes.by_id.get(&unit_id) {
+                Some(u) => u,
+                None => return Err("could not find entrenching unit".to_string()),
+            };
+
+            let cost = match unit_model.definition(unit_table).entrench_supply_cost {
+                Some(cost) => cost.ceil() as i16,
+                None => return Err("unit cannot entrench".to_string()),
+            };
+
+            // Digging in takes the whole turn
+            if moves_this_turn(&unit_id, events, remaining_actions) {
+                return Err("unit cannot entrench on a turn it moves".to_string());
+            }
+
+            events.push(Event::Entrenched {
+                unit_id,
+                against,
+                cost,
+            });
+        }
+        Action::Bombard { unit_id, target } => {
+            let unit_model = match indexes.by_id.get(&unit_id) {
+                Some(u) => u,
+                None => return Err("could not find bombarding unit".to_string()),
+            };
+
+            let (range, cost) = match (
+                unit_model.definition(unit_table).bombard_range,
+                unit_model.definition(unit_table).bombard_supply_cost,
+            ) {
+                (Some(range), Some(cost)) => (range, cost.ceil() as i16),
+                _ => return Err("unit cannot bombard".to_string()),
+            };
+
+            let unit_loc = match unit_model.place.to_map_loc() {
+                Some(loc) => loc,
+                None => return Err("unit cannot bombard while being carried".to_string()),
+            };
+
+            if moves_this_turn(&unit_id, events, remaining_actions) {
+                return Err("unit cannot bombard on a turn it moves".to_string());
+            }
+
+            if unit_loc.distance_from(&target) > range {
+                return Err("bombard target is out of range".to_string());
+            }
+
+            // Whatever is on the tile when the shells land gets
+            // hit, friend or foe
+            let mut hit_unit_ids = indexes
+                .by_id
+                .iter()
+                .filter(|(_, hit_model)| {
+                    hit_model
+                        .place
+                        .to_map_loc()
+                        .map(|loc| loc.is_same_pos_as(&target))
+                        .unwrap_or(false)
+                })
+                .map(|(hit_unit_id, _)| hit_unit_id.clone())
+                .collect::<Vec<UnitId>>();
+
+            hit_unit_ids.sort();
+
+            events.push(Event::Bombarded {
+                unit_id,
+                target,
+                cost,
+            });
+
+            for hit_unit_id in hit_unit_ids {
+                events.push(Event::Damaged {
+                    unit_id: hit_unit_id,
+                    damage: 1,
+                });
+            }
+        }
+        Action::Build {
+            unit_id,
+            target,
+            structure,
+        } => {
+            let unit_model = match indexes.by_id.get(&unit_id) {
+                Some(u) => u,
+                None => return Err("could not find building unit".to_string()),
+            };
+
+            let cost = match unit_model.definition(unit_table).build_supply_cost {
+                Some(cost) => cost.ceil() as i16,
+                None => return Err("unit cannot build".to_string()),
+            };
+
+            let unit_loc = match unit_model.place.to_map_loc() {
+                Some(loc) => loc,
+                None => return Err("unit cannot build while being carried".to_string()),
+            };
+
+            if moves_this_turn(&unit_id, events, remaining_actions) {
+                return Err("unit cannot build on a turn it moves".to_string());
+            }
+
+            if unit_loc.distance_from(&target) > 1 {
+                return Err("build target is out of reach".to_string());
+            }
+
+            if !map.get_tile(&target).can_build(&structure) {
+                return Err("structure cannot be built on this tile".to_string());
+            }
+
+            events.push(Event::Built {
+                unit_id,
+                loc: target,
+                structure,
+                cost,
+            });
+        }
+        Action::Demolish { unit_id, target } => {
+            let unit_model = match indexes.by_id.get(&unit_id) {
+                Some(u) => u,
+                None => return Err("could not find demolishing unit".to_string()),
+            };
+
+            let cost = match unit_model.definition(unit_table).build_supply_cost {
+                Some(cost) => cost.ceil() as i16,
+                None => return Err("unit cannot demolish".to_string()),
+            };
+
+            let unit_loc = match unit_model.place.to_map_loc() {
+                Some(loc) => loc,
+                None => return Err("unit cannot demolish while being carried".to_string()),
+            };
+
+            if moves_this_turn(&unit_id, events, remaining_actions) {
+                return Err("unit cannot demolish on a turn it moves".to_string());
+            }
+
+            if unit_loc.distance_from(&target) > 1 {
+                return Err("demolish target is out of reach".to_string());
+            }
+
+            match map.structures.get(&target) {
+                Some(structure) => {
+                    if structure.owner.as_ref() == Some(&unit_model.owner) {
+                        return Err("units cannot demolish their own structures".to_string());
+                    }
+
+                    if strands_units(&target, &structure.replaced, indexes, remaining_actions) {
+                        return Err(
+                            "cannot demolish a structure units are on or crossing".to_string()
+                        );
+                    }
+                }
+                None => return Err("there is no structure to demolish".to_string()),
+            }
+
+            events.push(Event::Demolished {
+                unit_id,
+                loc: target,
+                cost,
+            });
+        }
+        Action::TransferSupplies {
+            unit_id,
+            receiving_unit_id,
+            amount,
+        } => {
+            let (giver, receiver) = match (
+                indexes.by_id.get(&unit_id),
+                indexes.by_id.get(&receiving_unit_id),
+            ) {
+                (Some(giver), Some(receiver)) => (giver, receiver),
+                _ => return Err("could not find transports to transfer supplies".to_string()),
+            };
+
+            if giver.owner != receiver.owner {
+                return Err("supplies can only be transferred to your own units".to_string());
+            }
+
+            let giver_loc = indexes.position_of_unit_or_transport(&unit_id)?;
+            let receiver_loc = indexes.position_of_unit_or_transport(&receiving_unit_id)?;
+
+            if giver_loc.distance_from(&receiver_loc) > 1 {
+                return Err("transports are too far apart to transfer supplies".to_string());
+            }
+
+            let carried = |transport_id: &UnitId| {
+                indexes
+                    .carried_supply_crates(transport_id, unit_table)
+                    .iter()
+                    .filter_map(|crate_id| indexes.by_id.get(crate_id))
+                    .cloned()
+                    .collect::<Vec<unit::Model>>()
+            };
+
+            let available: i16 = carried(&unit_id)
+                .iter()
+                .map(|crate_model| crate_model.supplies)
+                .sum();
+
+            let room: i16 = carried(&receiving_unit_id)
+                .iter()
+                .map(|crate_model| {
+                    crate_model.definition(unit_table).max_supplies - crate_model.supplies
+                })
+                .sum();
+
+            let amount = cmp::min(amount, cmp::min(available, room));
+
+            if amount <= 0 {
+                return Err("there are no supplies to transfer, or no room for them".to_string());
+            }
+
+            events.push(Event::TransferredSupplies {
+                unit_id,
+                receiving_unit_id,
+                amount,
+            });
+        }
+        Action::SplitCrate { crate_id, amount } => {
+            let crate_model = match indexes.by_id.get(&crate_id) {
+                Some(u) => u,
+                None => return Err("could not find supply crate to split".to_string()),
+            };
+
+            if !crate_model.definition(unit_table).is_supply_crate || crate_model.place.is_on_map()
+            {
+                return Err("only carried supply crates can be split".to_string());
+            }
+
+            if amount <= 0 || crate_model.supplies <= amount {
+                return Err("supply crate does not have enough to split".to_string());
+            }
+
+            events.push(Event::SplitCrate {
+                unit_id: crate_id,
+                new_crate_id: UnitId::new(rng),
+                amount,
+            });
+        }
+        Action::MergeCrates {
+            crate_id,
+            into_crate_id,
+        } => {
+            let (crate_model, into_crate_model) = match (
+                indexes.by_id.get(&crate_id),
+                indexes.by_id.get(&into_crate_id),
+            ) {
+                (Some(crate_model), Some(into_crate_model)) => (crate_model, into_crate_model),
+                _ => return Err("could not find supply crates to merge".to_string()),
+            };
+
+            let same_tile = match (
+                crate_model.place.to_map_loc(),
+                into_crate_model.place.to_map_loc(),
+            ) {
+                (Some(loc), Some(into_loc)) => loc.is_same_pos_as(into_loc),
+                _ => false,
+            };
+
+            if crate_id == into_crate_id
+                || !crate_model.definition(unit_table).is_supply_crate
+                || !into_crate_model.definition(unit_table).is_supply_crate
+                || !same_tile
+            {
+                return Err("only supply crates on the same tile can be merged".to_string());
+            }
+
+            let amount = cmp::min(
+                crate_model.supplies,
+                into_crate_model.definition(unit_table).max_supplies - into_crate_model.supplies,
+            );
+
+            if amount <= 0 {
+                return Err("there is no room left to merge the supply crates".to_string());
+            }
+
+            events.push(Event::MergedCrates {
+                unit_id: crate_id,
+                into_crate_id,
+                amount,
+            });
+        }
+        Action::Batch(_) => {}
+    }
+
+    Ok(())
+}
+
+// Units can only be carried by transports of their own side,
+// and only once the two of them are on the same tile
+fn check_carry(
+    indexes: &unit_index::Indexes,
+    transport_id: &UnitId,
+    cargo_id: &UnitId,
+    moving_unit_id: &UnitId,
+    path: &Path,
+) -> Result<(), String> {
+    let (transport, cargo) = match (indexes.by_id.get(transport_id), indexes.by_id.get(cargo_id)) {
+        (Some(transport), Some(cargo)) => (transport, cargo),
+        _ => return Err("could not find units to load".to_string()),
+    };
+
+    if transport.owner != cargo.owner {
+        return Err("units can only be carried by their own side".to_string());
+    }
+
+    let waiting_unit_id = if moving_unit_id == transport_id {
+        cargo_id
+    } else {
+        transport_id
+    };
+
+    if !indexes.meets_at_path_end(moving_unit_id, waiting_unit_id, path) {
+        return Err("units do not meet at the end of the path".to_string());
+    }
+
+    Ok(())
+}
+
+fn baseline_supply_events(
+    indexes: &unit_index::Indexes,
+    conditions: &Conditions,
+    unit_table: &Table,
+) -> Vec<Event> {
+    let mut events = vec![];
+
+    for (unit_id, unit_model) in indexes.by_id.iter() {
+        if let Some(supply_cost) = unit_model.baseline_supply_cost(unit_table, conditions) {
+            events.push(Event::ConsumedBaselineSupplies {
                 unit_id: unit_id.clone(),
-                cost: supply_cost.ceil() as i16,
+                cost: supply_cost,
             });
         }
     }
@@ -452,570 +1502,2719 @@ fn baseline_supply_events(indexes: &unit_index::Indexes) -> Vec<Event> {
     events
 }
 
-#[cfg(test)]
-mod test_events {
-    use crate::direction::Direction;
-    use crate::facing_direction::FacingDirection;
-    use crate::game::action::Action;
-    use crate::game::event::process_turn;
-    use crate::game::unit_index::Indexes;
-    use crate::id::Id;
-    use crate::located::Located;
-    use crate::map::Map;
-    use crate::path::Path;
-    use crate::rng::RandSeed;
-    use crate::team_color::TeamColor;
-    use crate::unit::{Place, Unit, UnitId};
-    use crate::{located, unit};
-    use pretty_assertions::assert_eq;
+// Enemy combat units that end the turn on an unescorted
+// supply crate or truck take it for themselves
+fn capture_events(indexes: &unit_index::Indexes, unit_table: &Table) -> Vec<Event> {
+    let mut capture_events = vec![];
+
+    let is_escort = |unit_model: &unit::Model, owner: &Id| {
+        unit_model.owner == *owner && unit_model.definition(unit_table).can_capture
+    };
+
+    for (loc, units_at_loc) in indexes.by_location.iter() {
+        // The location index can be out of date in the middle
+        // of a turn, so the units are looked up by id
+        let units = units_at_loc
+            .iter()
+            .filter_map(|(unit_id, _, _)| {
+                indexes.by_id.get(unit_id).and_then(|unit_model| {
+                    match unit_model.place.to_map_loc() {
+                        Some(unit_loc) if unit_loc.is_same_pos_as(loc) => {
+                            Some((unit_id, unit_model))
+                        }
+                        _ => None,
+                    }
+                })
+            })
+            .collect::<Vec<(&UnitId, &unit::Model)>>();
+
+        for (unit_id, unit_model) in units.iter() {
+            if !unit_model.definition(unit_table).capturable {
+                continue;
+            }
+
+            let escorted_on_tile = units
+                .iter()
+                .any(|(_, other)| is_escort(other, &unit_model.owner));
+
+            let escorted_in_cargo = indexes.by_id.values().any(|other| {
+                other.place == Place::InUnit((*unit_id).clone())
+                    && is_escort(other, &unit_model.owner)
+            });
+
+            if escorted_on_tile || escorted_in_cargo {
+                continue;
+            }
+
+            let maybe_capturer_id = units
+                .iter()
+                .filter(|(_, other)| {
+                    other.owner != unit_model.owner && other.definition(unit_table).can_capture
+                })
+                .map(|(other_id, _)| *other_id)
+                .min();
+
+            if let Some(capturer_id) = maybe_capturer_id {
+                capture_events.push(Event::Captured {
+                    unit_id: (*unit_id).clone(),
+                    capturer_id: capturer_id.clone(),
+                });
+            }
+        }
+    }
+
+    capture_events
+}
+
+// Units that can take towns do so by ending the turn in
+// one, unless the town's owner has units there too
+fn town_capture_events(indexes: &unit_index::Indexes, map: &Map, unit_table: &Table) -> Vec<Event> {
+    let mut town_capture_events = vec![];
+
+    for (town_loc, town) in sorted_towns(map) {
+        let units_in_town = indexes
+            .by_id
+            .iter()
+            .filter(|(_, unit_model)| match unit_model.place.to_map_loc() {
+                Some(unit_loc) => unit_loc.is_same_pos_as(town_loc),
+                None => false,
+            })
+            .collect::<Vec<(&UnitId, &unit::Model)>>();
+
+        let defended = units_in_town
+            .iter()
+            .any(|(_, unit_model)| Some(&unit_model.owner) == town.owner.as_ref());
+
+        if defended {
+            continue;
+        }
+
+        let maybe_capturer_id = units_in_town
+            .iter()
+            .filter(|(_, unit_model)| unit_model.definition(unit_table).can_capture_towns)
+            .map(|(unit_id, _)| *unit_id)
+            .min();
+
+        if let Some(capturer_id) = maybe_capturer_id {
+            town_capture_events.push(Event::TownCaptured {
+                unit_id: capturer_id.clone(),
+                loc: town_loc.clone(),
+            });
+        }
+    }
+
+    town_capture_events
+}
+
+// Each town sends its supplies to whichever of its owner's
+// crates or trucks, on or next to the town, has the most room
+fn town_supply_events(indexes: &unit_index::Indexes, map: &Map, unit_table: &Table) -> Vec<Event> {
+    let mut town_supply_events = vec![];
+
+    for (town_loc, town) in sorted_towns(map) {
+        let owner = match &town.owner {
+            Some(owner) => owner,
+            None => continue,
+        };
+
+        let maybe_receiver = indexes
+            .by_id
+            .iter()
+            .filter(|(_, unit_model)| {
+                unit_model.owner == *owner
+                    && (unit_model.definition(unit_table).is_supply_crate
+                        || unit_model.definition(unit_table).can_pick_up_supply_crates)
+            })
+            .filter(|(_, unit_model)| match unit_model.place.to_map_loc() {
+                Some(unit_loc) => unit_loc.distance_from(town_loc) <= 1,
+                None => false,
+            })
+            .map(|(unit_id, unit_model)| {
+                let room = unit_model.definition(unit_table).max_supplies - unit_model.supplies;
+
+                (room, unit_id)
+            })
+            .filter(|(room, _)| *room > 0)
+            .max_by(|(room, unit_id), (other_room, other_unit_id)| {
+                room.cmp(other_room).then(other_unit_id.cmp(unit_id))
+            });
+
+        if let Some((room, unit_id)) = maybe_receiver {
+            town_supply_events.push(Event::SuppliedByTown {
+                unit_id: unit_id.clone(),
+                town: town_loc.clone(),
+                amount: cmp::min(room, map::TOWN_SUPPLIES_PER_TURN),
+            });
+        }
+    }
+
+    town_supply_events
+}
+
+// Towns are gone through in the same order every time,
+// so the events come out the same on every machine
+fn sorted_towns(map: &Map) -> Vec<(&Located<()>, &Town)> {
+    let mut towns = map.towns.iter().collect::<Vec<(&Located<()>, &Town)>>();
+
+    towns.sort_by_key(|(loc, _)| (loc.y, loc.x));
+
+    towns
+}
+
+// Depots add to their stockpile every turn, and send
+// it out as a new supply crate once it is full
+fn supply_depot_events(
+    indexes: &unit_index::Indexes,
+    rng: &mut RandGen,
+    unit_table: &Table,
+) -> Vec<Event> {
+    let mut depots = indexes
+        .by_id
+        .iter()
+        .filter(|(_, unit_model)| unit_model.place.is_on_map())
+        .filter_map(|(unit_id, unit_model)| {
+            unit_model
+                .definition(unit_table)
+                .supply_production
+                .map(|production| (unit_id, unit_model, production))
+        })
+        .collect::<Vec<(&UnitId, &unit::Model, i16)>>();
+
+    // Making crates uses up random ids, so the depots
+    // are gone through in the same order every time
+    depots.sort_by_key(|(unit_id, _, _)| *unit_id);
+
+    let mut supply_depot_events = vec![];
+
+    for (unit_id, unit_model, production) in depots {
+        let room = unit_model.definition(unit_table).max_supplies - unit_model.supplies;
+
+        if room > 0 {
+            supply_depot_events.push(Event::Stockpiled {
+                unit_id: unit_id.clone(),
+                amount: cmp::min(room, production),
+            });
+        }
+
+        if production >= room {
+            supply_depot_events.push(Event::ProducedSupplyCrate {
+                unit_id: unit_id.clone(),
+                crate_id: UnitId::new(rng),
+            });
+
+            // Whatever did not fit before the crate went
+            // out starts off the next one
+            if production > room {
+                supply_depot_events.push(Event::Stockpiled {
+                    unit_id: unit_id.clone(),
+                    amount: production - room,
+                });
+            }
+        }
+    }
+
+    supply_depot_events
+}
+
+// Units that can live off the land gather supplies from
+// the tile they held all turn. Enemies close by cut what
+// they can safely gather in half, and tiles that have been
+// picked over give less each time. Tiles nobody forages
+// grow back as the turn ends
+fn forage_events(
+    indexes: &unit_index::Indexes,
+    map: &Map,
+    events: &[Event],
+    unit_table: &Table,
+) -> Vec<Event> {
+    let mut foragers = indexes
+        .by_id
+        .iter()
+        .filter(|(unit_id, _)| !moved_this_turn(unit_id, events))
+        .filter_map(|(unit_id, unit_model)| {
+            match (
+                unit_model.definition(unit_table).forage_supplies,
+                unit_model.place.to_map_loc(),
+            ) {
+                (Some(forage_supplies), Some(loc)) if map.get_tile(&loc.to_unit()).can_forage() => {
+                    Some((unit_id, unit_model, loc.to_unit(), forage_supplies))
+                }
+                _ => None,
+            }
+        })
+        .collect::<Vec<(&UnitId, &unit::Model, Located<()>, i16)>>();
+
+    // Units foraging the same tile leave less for one
+    // another, so they are gone through in the same order
+    foragers.sort_by_key(|(unit_id, _, _, _)| *unit_id);
+
+    let mut foraged_now: HashMap<Located<()>, u8> = HashMap::new();
+    let mut forage_events = vec![];
+
+    for (unit_id, unit_model, loc, forage_supplies) in foragers {
+        let times_foraged = map
+            .times_foraged(&loc)
+            .saturating_add(foraged_now.get(&loc).cloned().unwrap_or(0));
+
+        // Only enemies that could actually attack get in the way
+        let enemy_nearby = indexes.by_id.values().any(|other| {
+            other.owner != unit_model.owner
+                && other.definition(unit_table).can_attack
+                && other
+                    .place
+                    .to_map_loc()
+                    .map(|other_loc| other_loc.distance_from(&loc) <= 1)
+                    .unwrap_or(false)
+        });
+
+        let mut amount = forage_supplies / (1 + times_foraged as i16);
+
+        if enemy_nearby {
+            amount /= 2;
+        }
+
+        let amount = cmp::min(
+            amount,
+            unit_model.definition(unit_table).max_supplies - unit_model.supplies,
+        );
+
+        if amount > 0 {
+            *foraged_now.entry(loc.clone()).or_insert(0) += 1;
+
+            forage_events.push(Event::Foraged {
+                unit_id: unit_id.clone(),
+                loc,
+                amount,
+            });
+        }
+    }
+
+    forage_events
+}
+
+// Whether the unit changed position this turn, either
+// under its own power or by being carried or pushed back
+fn moved_this_turn(unit_id: &UnitId, events: &[Event]) -> bool {
+    events.iter().any(|event| match event {
+        Event::Travelled {
+            unit_id: moved_id, ..
+        } => moved_id == unit_id,
+        Event::Loaded { cargo_id, .. } => cargo_id == unit_id,
+        Event::DroppedOff { cargo_id, .. } => cargo_id == unit_id,
+        Event::Retreated {
+            unit_id: moved_id, ..
+        } => moved_id == unit_id,
+        _ => false,
+    })
+}
+
+// Orders are carried out in a random order, so a unit that
+// has not moved yet might still be about to
+fn moves_this_turn(unit_id: &UnitId, events: &[Event], remaining_actions: &[Action]) -> bool {
+    moved_this_turn(unit_id, events)
+        || remaining_actions
+            .iter()
+            .any(|action| action.moves_unit(unit_id))
+}
+
+// Tearing down a bridge would leave whoever is on it,
+// or still on their way across, standing in the water
+fn strands_units(
+    target: &Located<()>,
+    replaced: &Tile,
+    indexes: &unit_index::Indexes,
+    remaining_actions: &[Action],
+) -> bool {
+    if replaced.mobility_cost().is_finite() {
+        return false;
+    }
+
+    let units_on_target = indexes
+        .by_location
+        .get(target)
+        .map(|units| !units.is_empty())
+        .unwrap_or(false);
+
+    units_on_target
+        || remaining_actions.iter().any(|action| {
+            action
+                .path()
+                .map(|path| {
+                    path.to_loc_directions()
+                        .iter()
+                        .any(|step| step.is_same_pos_as(target))
+                })
+                .unwrap_or(false)
+        })
+}
+
+// Units that did not move or fight this turn
+// recover some of their health
+fn recovery_events(
+    indexes: &unit_index::Indexes,
+    events: &[Event],
+    unit_table: &Table,
+) -> Vec<Event> {
+    let mut active_units: HashSet<&UnitId> = HashSet::new();
+
+    for event in events {
+        match event {
+            Event::Travelled { unit_id, .. } => {
+                active_units.insert(unit_id);
+            }
+            Event::Loaded { cargo_id, .. } => {
+                active_units.insert(cargo_id);
+            }
+            Event::PickedUp { transport_id, .. } => {
+                active_units.insert(transport_id);
+            }
+            Event::ReplenishedUnits { unit_id, .. } => {
+                active_units.insert(unit_id);
+            }
+            Event::Battle {
+                attacker_id,
+                defender_id,
+                ..
+            } => {
+                active_units.insert(attacker_id);
+                active_units.insert(defender_id);
+            }
+            Event::Bombarded { unit_id, .. } => {
+                active_units.insert(unit_id);
+            }
+            Event::Built { unit_id, .. } => {
+                active_units.insert(unit_id);
+            }
+            Event::Demolished { unit_id, .. } => {
+                active_units.insert(unit_id);
+            }
+            Event::Damaged { unit_id, .. } => {
+                active_units.insert(unit_id);
+            }
+            _ => {}
+        }
+    }
+
+    let mut recovery_events = vec![];
+
+    for (unit_id, unit_model) in indexes.by_id.iter() {
+        if unit_model.can_recover(unit_table) && !active_units.contains(unit_id) {
+            recovery_events.push(Event::Recovered {
+                unit_id: unit_id.clone(),
+            });
+        }
+    }
+
+    recovery_events
+}
+
+#[cfg(test)]
+mod test_events {
+    use crate::direction::Direction;
+    use crate::facing_direction::FacingDirection;
+    use crate::game::action::{Action, Attack};
+    use crate::game::conditions::Conditions;
+    use crate::game::event::{cancel_actions_for_unit, process_turn, Event, TurnError};
+    use crate::game::unit_index::Indexes;
+    use crate::id::Id;
+    use crate::located::Located;
+    use crate::map::{Map, Structure, Town, TOWN_SUPPLIES_PER_TURN};
+    use crate::path::Path;
+    use crate::rng::{RandGen, RandSeed};
+    use crate::team_color::TeamColor;
+    use crate::tile::Tile;
+    use crate::unit::definition::Table;
+    use crate::unit::{Health, Place, Unit, UnitId};
+    use crate::{located, unit};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn process_travel() {
+        let rand_seed = RandSeed::test();
+
+        let player_1 = Id::from_string("player 1".to_string(), true).unwrap();
+        let infantry_id = UnitId::test("infantry");
+
+        let path = Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::South]);
+
+        let player_1_actions = vec![Action::Travel {
+            unit_id: infantry_id.clone(),
+            path: path.clone(),
+            dismounted_from: None,
+        }];
+
+        let mut actions = vec![(player_1.clone(), player_1_actions)];
+
+        let mut indexes = Indexes::make(vec![(
+            infantry_id.clone(),
+            unit::Model::new(
+                Unit::Infantry,
+                &player_1,
+                Place::OnMap(Located {
+                    x: 2,
+                    y: 2,
+                    value: FacingDirection::Right,
+                }),
+                &TeamColor::Red,
+            ),
+        )]);
+
+        let mut map = Map::grass_square();
+
+        let got_errors = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        )
+        .errors;
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, got_errors);
+
+        let got_infantry_loc = indexes
+            .by_id
+            .get(&infantry_id)
+            .unwrap()
+            .place
+            .to_map_loc()
+            .unwrap();
+
+        let want_loc = &Located {
+            x: 2,
+            y: 3,
+            value: FacingDirection::Right,
+        };
+
+        assert_eq!(want_loc, got_infantry_loc);
+
+        let got_units_by_loc = indexes.by_location.get(&want_loc.to_unit()).unwrap();
+
+        let mut infantry_after_turn = unit::Model::new(
+            Unit::Infantry,
+            &player_1,
+            Place::OnMap(Located {
+                x: 2,
+                y: 3,
+                value: FacingDirection::Right,
+            }),
+            &TeamColor::Red,
+        );
+
+        infantry_after_turn.supplies = 982;
+
+        let want_units = &vec![(infantry_id, FacingDirection::Right, infantry_after_turn)];
+
+        assert_eq!(want_units, got_units_by_loc);
+    }
+
+    #[test]
+    fn process_loading() {
+        let rand_seed = RandSeed::test();
+
+        let player_1 = Id::from_string("player 1".to_string(), true).unwrap();
+        let infantry_id = UnitId::test("infantry");
+        let truck_id = UnitId::test("truck");
+
+        let player_1_loads_actions = vec![Action::LoadInto {
+            unit_id: infantry_id.clone(),
+            load_into: truck_id.clone(),
+            path: Path::from_directions_test_only(
+                &located::unit(2, 2),
+                &vec![Direction::South, Direction::South],
+            ),
+        }];
+
+        let mut actions = vec![(player_1.clone(), player_1_loads_actions)];
+
+        let mut indexes = Indexes::make(vec![
+            (
+                infantry_id.clone(),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &player_1,
+                    Place::OnMap(Located {
+                        x: 2,
+                        y: 2,
+                        value: FacingDirection::Right,
+                    }),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                truck_id.clone(),
+                unit::Model::new(
+                    Unit::Truck,
+                    &player_1,
+                    Place::OnMap(Located {
+                        x: 2,
+                        y: 4,
+                        value: FacingDirection::Right,
+                    }),
+                    &TeamColor::Red,
+                ),
+            ),
+        ]);
+
+        let mut map = Map::grass_square();
+
+        let got_errors = process_turn(
+            rand_seed.clone(),
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        )
+        .errors;
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, got_errors);
+
+        // After the turn, the infantry is located in the truck
+        {
+            let got_infantry_loc = indexes
+                .by_id
+                .get(&infantry_id)
+                .unwrap()
+                .place
+                .in_unit_loc()
+                .unwrap();
+
+            let want_loc = truck_id.clone();
+
+            assert_eq!(want_loc, got_infantry_loc.clone());
+        }
+
+        // After the turn, the infantry is no longer located a 2,2, where it was before
+        {
+            let got_units_by_loc = indexes.by_location.get(&located::unit(2, 2)).unwrap();
+
+            let want_units: Vec<(UnitId, FacingDirection, unit::Model)> = vec![];
+
+            assert_eq!(&want_units, got_units_by_loc);
+        }
+
+        // After the turn, we can get the infantry by id as we expect it
+        {
+            let mut infantry_after_turn = unit::Model::new(
+                Unit::Infantry,
+                &player_1,
+                Place::InUnit(truck_id.clone()),
+                &TeamColor::Red,
+            );
+
+            infantry_after_turn.supplies = 972;
+
+            let got_infantry_by_id = indexes.by_id.get(&infantry_id.clone()).unwrap().clone();
+            let want_infantry_by_id = infantry_after_turn;
+
+            assert_eq!(want_infantry_by_id, got_infantry_by_id);
+        }
+
+        let player_1_unload_actions = vec![
+            Action::Travel {
+                unit_id: truck_id.clone(),
+                path: Path::from_directions_test_only(
+                    &located::unit(2, 4),
+                    &vec![
+                        Direction::East,
+                        Direction::East,
+                        Direction::East,
+                        Direction::East,
+                    ],
+                ),
+                dismounted_from: None,
+            },
+            Action::Travel {
+                unit_id: infantry_id.clone(),
+                path: Path::from_directions_test_only(
+                    &located::unit(6, 4),
+                    &vec![Direction::South, Direction::South],
+                ),
+                dismounted_from: Some(truck_id.clone()),
+            },
+        ];
+
+        let mut actions = vec![(player_1.clone(), player_1_unload_actions)];
+
+        let got_errors = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        )
+        .errors;
+
+        let want_errors: Vec<TurnError> = vec![];
+
+        assert_eq!(want_errors, got_errors);
+
+        // After the second turn, the truck has moved
+        {
+            let got_truck_loc = indexes
+                .by_id
+                .get(&truck_id)
+                .unwrap()
+                .place
+                .to_map_loc()
+                .unwrap();
+
+            let want_loc = Located {
+                x: 6,
+                y: 4,
+                value: FacingDirection::Right,
+            };
+
+            assert_eq!(want_loc, got_truck_loc.clone());
+        }
+
+        // After the second turn, the infantry is located south of the truck
+        {
+            let got_infantry_loc = indexes
+                .by_id
+                .get(&infantry_id)
+                .unwrap()
+                .place
+                .to_map_loc()
+                .unwrap();
+
+            let want_loc = Located {
+                x: 6,
+                y: 6,
+                value: FacingDirection::Right,
+            };
+
+            assert_eq!(want_loc, got_infantry_loc.clone());
+        }
+    }
+
+    #[test]
+    fn process_picking_up_crate() {
+        let rand_seed = RandSeed::test();
+
+        let player_1 = Id::from_string("player 1".to_string(), true).unwrap();
+        let truck_id = UnitId::test("truck");
+        let supply_crate_id = UnitId::test("supply crate");
+
+        let player_1_pickup_actions = vec![Action::PickUp {
+            unit_id: truck_id.clone(),
+            cargo_id: supply_crate_id.clone(),
+            path: Path::from_directions_test_only(
+                &located::unit(4, 4),
+                &vec![Direction::West, Direction::West],
+            ),
+        }];
+
+        let mut actions = vec![(player_1.clone(), player_1_pickup_actions)];
+
+        let mut indexes = Indexes::make(vec![
+            (
+                supply_crate_id.clone(),
+                unit::Model::new(
+                    Unit::SupplyCrate,
+                    &player_1,
+                    Place::OnMap(Located {
+                        x: 2,
+                        y: 4,
+                        value: FacingDirection::Right,
+                    }),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                truck_id.clone(),
+                unit::Model::new(
+                    Unit::Truck,
+                    &player_1,
+                    Place::OnMap(Located {
+                        x: 4,
+                        y: 4,
+                        value: FacingDirection::Left,
+                    }),
+                    &TeamColor::Red,
+                ),
+            ),
+        ]);
+
+        let mut map = Map::grass_square();
+
+        let got_errors = process_turn(
+            rand_seed.clone(),
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        )
+        .errors;
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, got_errors);
+
+        // After turn truck is located where the crate was
+        {
+            let got_truck_loc = indexes
+                .by_id
+                .get(&truck_id)
+                .unwrap()
+                .place
+                .to_map_loc()
+                .unwrap()
+                .to_unit();
+
+            let want_truck_loc = located::unit(2, 4);
+
+            assert_eq!(want_truck_loc, got_truck_loc);
+
+            let got_prev_loc = indexes.by_location.get(&located::unit(4, 4));
+            let no_units: Vec<(UnitId, FacingDirection, unit::Model)> = vec![];
+            let want_prev_loc: Option<&Vec<(UnitId, FacingDirection, unit::Model)>> =
+                Some(&no_units);
+
+            assert_eq!(want_prev_loc, got_prev_loc);
+
+            let got_curr_loc = indexes.by_location.get(&located::unit(2, 4));
+
+            let mut truck_after_turn = unit::Model::new(
+                Unit::Truck,
+                &player_1,
+                Place::OnMap(Located {
+                    x: 2,
+                    y: 4,
+                    value: FacingDirection::Left,
+                }),
+                &TeamColor::Red,
+            );
+
+            truck_after_turn.supplies = 1962;
+
+            let one_unit: Vec<(UnitId, FacingDirection, unit::Model)> =
+                vec![(truck_id.clone(), FacingDirection::Left, truck_after_turn)];
+
+            let want_curr_loc: Option<&Vec<(UnitId, FacingDirection, unit::Model)>> =
+                Some(&one_unit);
+
+            assert_eq!(want_curr_loc, got_curr_loc);
+        }
+
+        // After turn crates location is inside truck
+        {
+            let got_crate_loc = indexes
+                .by_id
+                .get(&supply_crate_id)
+                .unwrap()
+                .place
+                .in_unit_loc()
+                .unwrap()
+                .clone();
+
+            let want_crate_loc = truck_id.clone();
+
+            assert_eq!(want_crate_loc, got_crate_loc);
+        }
+
+        let player_1_drop_actions = vec![Action::DropOff {
+            cargo_id: supply_crate_id.clone(),
+        }];
+
+        let mut actions = vec![(player_1.clone(), player_1_drop_actions)];
+
+        let got_errors = process_turn(
+            rand_seed.clone(),
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        )
+        .errors;
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, got_errors);
+
+        // After second turn crates location is on map
+        {
+            let got_crate_loc = indexes
+                .by_id
+                .get(&supply_crate_id)
+                .unwrap()
+                .place
+                .to_map_loc()
+                .unwrap()
+                .clone();
+
+            let want_crate_loc = Located {
+                x: 2,
+                y: 4,
+                value: FacingDirection::Left,
+            };
+
+            assert_eq!(want_crate_loc, got_crate_loc);
+
+            let mut crate_after_turn = unit::Model::new(
+                Unit::SupplyCrate,
+                &player_1,
+                Place::OnMap(Located {
+                    x: 2,
+                    y: 4,
+                    value: FacingDirection::Left,
+                }),
+                &TeamColor::Red,
+            );
+
+            crate_after_turn.supplies = 8192;
+
+            let mut truck_after_turn = unit::Model::new(
+                Unit::Truck,
+                &player_1,
+                Place::OnMap(Located {
+                    x: 2,
+                    y: 4,
+                    value: FacingDirection::Left,
+                }),
+                &TeamColor::Red,
+            );
+
+            truck_after_turn.supplies = 1962;
+
+            let two_units: Vec<(UnitId, FacingDirection, unit::Model)> = vec![
+                (truck_id.clone(), FacingDirection::Left, truck_after_turn),
+                (
+                    supply_crate_id.clone(),
+                    FacingDirection::Left,
+                    crate_after_turn,
+                ),
+            ];
+
+            let want_curr_loc: Option<&Vec<(UnitId, FacingDirection, unit::Model)>> =
+                Some(&two_units);
+
+            let got_curr_loc = indexes.by_location.get(&located::unit(2, 4));
+
+            assert_eq!(want_curr_loc, got_curr_loc);
+        }
+    }
+
+    #[test]
+    fn process_two_player_travel() {
+        let rand_seed = RandSeed::test();
+
+        let red_player_id = Id::from_string("red player".to_string(), true).unwrap();
+        let red_infantry_id = UnitId::test("red infantry");
+
+        let red_player_actions = vec![Action::Travel {
+            unit_id: red_infantry_id.clone(),
+            path: Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::South]),
+            dismounted_from: None,
+        }];
+
+        let blue_player_id = Id::from_string("blue player".to_string(), true).unwrap();
+        let blue_infantry_id = UnitId::test("blue infantry");
+
+        let blue_player_actions = vec![Action::Travel {
+            unit_id: blue_infantry_id.clone(),
+            path: Path::from_directions_test_only(&located::unit(4, 2), &vec![Direction::South]),
+            dismounted_from: None,
+        }];
+
+        let mut actions = vec![
+            (red_player_id.clone(), red_player_actions),
+            (blue_player_id.clone(), blue_player_actions),
+        ];
+
+        let mut indexes = Indexes::make(vec![
+            (
+                red_infantry_id.clone(),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &red_player_id,
+                    Place::OnMap(Located {
+                        x: 2,
+                        y: 2,
+                        value: FacingDirection::Right,
+                    }),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                blue_infantry_id.clone(),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &blue_player_id,
+                    Place::OnMap(Located {
+                        x: 4,
+                        y: 2,
+                        value: FacingDirection::Right,
+                    }),
+                    &TeamColor::Blue,
+                ),
+            ),
+        ]);
+
+        let mut map = Map::grass_square();
+
+        let got_errors = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        )
+        .errors;
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, got_errors);
+
+        let got_red_infantry_loc = indexes
+            .by_id
+            .get(&red_infantry_id)
+            .unwrap()
+            .place
+            .to_map_loc()
+            .unwrap();
+
+        let want_red_infantry_loc = &Located {
+            x: 2,
+            y: 3,
+            value: FacingDirection::Right,
+        };
+
+        assert_eq!(want_red_infantry_loc, got_red_infantry_loc);
+
+        let got_blue_infantry_loc = indexes
+            .by_id
+            .get(&blue_infantry_id)
+            .unwrap()
+            .place
+            .to_map_loc()
+            .unwrap();
+
+        let want_blue_infantry_loc = &Located {
+            x: 4,
+            y: 3,
+            value: FacingDirection::Right,
+        };
+
+        assert_eq!(want_blue_infantry_loc, got_blue_infantry_loc);
+
+        let got_units_by_loc = indexes
+            .by_location
+            .get(&want_red_infantry_loc.to_unit())
+            .unwrap();
+
+        let mut infantry_after_turn = unit::Model::new(
+            Unit::Infantry,
+            &red_player_id,
+            Place::OnMap(Located {
+                x: 2,
+                y: 3,
+                value: FacingDirection::Right,
+            }),
+            &TeamColor::Red,
+        );
+
+        infantry_after_turn.supplies = 982;
+
+        let want_units = &vec![(red_infantry_id, FacingDirection::Right, infantry_after_turn)];
+
+        assert_eq!(want_units, got_units_by_loc);
+    }
+
+    #[test]
+    fn process_stationary_battle() {
+        let rand_seed = RandSeed::test();
+
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let tank_id = UnitId::test("red tank");
+        let infantry_id = UnitId::test("blue infantry");
+
+        let red_actions = vec![Action::Attack(Attack {
+            unit_id: tank_id.clone(),
+            path: Path::from_directions_test_only(
+                &located::unit(2, 2),
+                &vec![Direction::East, Direction::East, Direction::East],
+            ),
+        })];
+
+        let mut actions = vec![(red_player_id.clone(), red_actions)];
+
+        let mut indexes = Indexes::make(vec![
+            (
+                tank_id.clone(),
+                unit::Model::new(
+                    Unit::Tank,
+                    &red_player_id,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                infantry_id.clone(),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &blue_player_id,
+                    Place::on_map(4, 2, FacingDirection::Left),
+                    &TeamColor::Blue,
+                ),
+            ),
+        ]);
+
+        let mut map = Map::grass_square();
+
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+
+        assert!(processed_turn.events.contains(&Event::Battle {
+            attacker_id: tank_id.clone(),
+            defender_id: infantry_id.clone(),
+            loc: located::unit(4, 2),
+        }));
+
+        let got_tank_loc = indexes
+            .by_id
+            .get(&tank_id)
+            .unwrap()
+            .place
+            .to_map_loc()
+            .unwrap()
+            .to_unit();
+
+        assert_eq!(located::unit(3, 2), got_tank_loc);
+
+        let got_infantry_health = indexes.by_id.get(&infantry_id).unwrap().health.clone();
+
+        assert_eq!(unit::Health::High, got_infantry_health);
+    }
+
+    #[test]
+    fn process_meeting_engagement() {
+        let rand_seed = RandSeed::test();
+
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let tank_id = UnitId::test("red tank");
+        let infantry_id = UnitId::test("blue infantry");
+
+        let red_actions = vec![Action::Attack(Attack {
+            unit_id: tank_id.clone(),
+            path: Path::from_directions_test_only(
+                &located::unit(2, 2),
+                &vec![Direction::East, Direction::East, Direction::East],
+            ),
+        })];
+
+        let blue_actions = vec![Action::Travel {
+            unit_id: infantry_id.clone(),
+            path: Path::from_directions_test_only(
+                &located::unit(4, 0),
+                &vec![Direction::South, Direction::South, Direction::South],
+            ),
+            dismounted_from: None,
+        }];
+
+        let mut actions = vec![
+            (red_player_id.clone(), red_actions),
+            (blue_player_id.clone(), blue_actions),
+        ];
+
+        let mut indexes = Indexes::make(vec![
+            (
+                tank_id.clone(),
+                unit::Model::new(
+                    Unit::Tank,
+                    &red_player_id,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                infantry_id.clone(),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &blue_player_id,
+                    Place::on_map(4, 0, FacingDirection::Left),
+                    &TeamColor::Blue,
+                ),
+            ),
+        ]);
+
+        let mut map = Map::grass_square();
+
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+
+        assert!(processed_turn.events.contains(&Event::Battle {
+            attacker_id: tank_id.clone(),
+            defender_id: infantry_id.clone(),
+            loc: located::unit(4, 2),
+        }));
+
+        // The infantry lost, and was pushed back the way
+        // the tank was heading
+        for (unit_id, want_loc) in [
+            (&tank_id, located::unit(4, 2)),
+            (&infantry_id, located::unit(5, 2)),
+        ] {
+            let got_loc = indexes
+                .by_id
+                .get(unit_id)
+                .unwrap()
+                .place
+                .to_map_loc()
+                .unwrap()
+                .to_unit();
+
+            assert_eq!(want_loc, got_loc);
+        }
+    }
+
+    #[test]
+    fn process_recovery() {
+        let rand_seed = RandSeed::test();
+
+        let player_1 = Id::test("player 1");
+        let infantry_id = UnitId::test("infantry");
+
+        let mut infantry = unit::Model::new(
+            Unit::Infantry,
+            &player_1,
+            Place::on_map(2, 2, FacingDirection::Right),
+            &TeamColor::Red,
+        );
+
+        infantry.health = unit::Health::Medium;
+
+        let mut actions = vec![(player_1, vec![])];
+
+        let mut indexes = Indexes::make(vec![(infantry_id.clone(), infantry)]);
+
+        let mut map = Map::grass_square();
+
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+
+        let got_infantry_health = indexes.by_id.get(&infantry_id).unwrap().health.clone();
+
+        assert_eq!(unit::Health::High, got_infantry_health);
+    }
+
+    #[test]
+    fn process_out_of_supply_upkeep() {
+        let rand_seed = RandSeed::test();
+
+        let player_1 = Id::test("player 1");
+        let supplied_id = UnitId::test("supplied");
+        let cut_off_id = UnitId::test("cut off");
+
+        let supplied = unit::Model::new(
+            Unit::Infantry,
+            &player_1,
+            Place::on_map(2, 2, FacingDirection::Right),
+            &TeamColor::Red,
+        );
+
+        let mut cut_off = supplied.clone();
+        cut_off.place = Place::on_map(2, 4, FacingDirection::Right);
+        cut_off.in_supply = false;
+
+        let mut indexes = Indexes::make(vec![
+            (supplied_id.clone(), supplied),
+            (cut_off_id.clone(), cut_off),
+        ]);
+
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut vec![(player_1, vec![])],
+            &mut indexes,
+            &mut Map::grass_square(),
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let cost_of = |want_id: &UnitId| {
+            processed_turn
+                .events
+                .iter()
+                .find_map(|event| match event {
+                    Event::ConsumedBaselineSupplies { unit_id, cost } if unit_id == want_id => {
+                        Some(*cost)
+                    }
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        assert!(cost_of(&cut_off_id) > cost_of(&supplied_id));
+    }
+
+    #[test]
+    fn process_entrenchment() {
+        let rand_seed = RandSeed::test();
+
+        let player_1 = Id::test("player 1");
+        let infantry_id = UnitId::test("infantry");
+
+        let player_1_actions = vec![Action::Entrench {
+            unit_id: infantry_id.clone(),
+            against: Direction::East,
+        }];
+
+        let mut actions = vec![(player_1.clone(), player_1_actions)];
+
+        let mut indexes = Indexes::make(vec![(
+            infantry_id.clone(),
+            unit::Model::new(
+                Unit::Infantry,
+                &player_1,
+                Place::on_map(2, 2, FacingDirection::Right),
+                &TeamColor::Red,
+            ),
+        )]);
+
+        let mut map = Map::grass_square();
+
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+
+        let infantry = indexes.by_id.get(&infantry_id).unwrap();
+
+        assert_eq!(Some(Direction::East), infantry.entrenched_against);
+
+        // The baseline supply cost, plus the cost of entrenching,
+        // less what it foraged while staying put
+        assert_eq!(1024 - 22 - 64 + 16, infantry.supplies);
+    }
+
+    #[test]
+    fn cannot_entrench_and_move_whatever_the_order() {
+        let player_1 = Id::test("player 1");
+        let infantry_id = UnitId::test("infantry");
+
+        let entrench = Action::Entrench {
+            unit_id: infantry_id.clone(),
+            against: Direction::East,
+        };
+
+        let travel = Action::Travel {
+            unit_id: infantry_id.clone(),
+            path: Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::East]),
+            dismounted_from: None,
+        };
+
+        let mut rng = RandGen::test();
+
+        for player_1_actions in [
+            vec![entrench.clone(), travel.clone()],
+            vec![travel, entrench],
+        ] {
+            for _ in 0..4 {
+                let mut indexes = Indexes::make(vec![(
+                    infantry_id.clone(),
+                    red_model(
+                        &player_1,
+                        Unit::Infantry,
+                        Place::on_map(2, 2, FacingDirection::Right),
+                    ),
+                )]);
+
+                let processed_turn = process_turn(
+                    RandSeed::next(&mut rng),
+                    &mut vec![(player_1.clone(), player_1_actions.clone())],
+                    &mut indexes,
+                    &mut Map::grass_square(),
+                    Table::bundled(),
+                    &Conditions::test(),
+                );
+
+                assert!(!processed_turn
+                    .events
+                    .iter()
+                    .any(|event| matches!(event, Event::Entrenched { .. })));
+
+                let infantry = indexes.by_id.get(&infantry_id).unwrap();
+
+                assert_eq!(None, infantry.entrenched_against);
+                assert_eq!(Place::on_map(3, 2, FacingDirection::Right), infantry.place);
+            }
+        }
+    }
+
+    fn red_model(player_id: &Id, unit: Unit, place: Place) -> unit::Model {
+        unit::Model::new(unit, player_id, place, &TeamColor::Red)
+    }
 
     #[test]
-    fn process_travel() {
+    fn process_supply_transfer() {
         let rand_seed = RandSeed::test();
 
-        let player_1 = Id::from_string("player 1".to_string(), true).unwrap();
+        let player_1 = Id::test("player 1");
+        let giver_id = UnitId::test("giver");
+        let receiver_id = UnitId::test("receiver");
+        let full_crate_id = UnitId::test("full crate");
+        let low_crate_id = UnitId::test("low crate");
+
+        let player_1_actions = vec![Action::TransferSupplies {
+            unit_id: giver_id.clone(),
+            receiving_unit_id: receiver_id.clone(),
+            amount: 5000,
+        }];
+
+        let mut actions = vec![(player_1.clone(), player_1_actions)];
+
+        let mut low_crate = red_model(
+            &player_1,
+            Unit::SupplyCrate,
+            Place::InUnit(receiver_id.clone()),
+        );
+        low_crate.supplies = 1000;
+
+        let mut indexes = Indexes::make(vec![
+            (
+                giver_id.clone(),
+                red_model(
+                    &player_1,
+                    Unit::Truck,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                ),
+            ),
+            (
+                receiver_id.clone(),
+                red_model(
+                    &player_1,
+                    Unit::Truck,
+                    Place::on_map(3, 2, FacingDirection::Right),
+                ),
+            ),
+            (
+                full_crate_id.clone(),
+                red_model(
+                    &player_1,
+                    Unit::SupplyCrate,
+                    Place::InUnit(giver_id.clone()),
+                ),
+            ),
+            (low_crate_id.clone(), low_crate),
+        ]);
+
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut Map::grass_square(),
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+
+        assert!(processed_turn.events.contains(&Event::TransferredSupplies {
+            unit_id: giver_id,
+            receiving_unit_id: receiver_id,
+            amount: 5000,
+        }));
+
+        assert_eq!(3192, indexes.by_id.get(&full_crate_id).unwrap().supplies);
+        assert_eq!(6000, indexes.by_id.get(&low_crate_id).unwrap().supplies);
+    }
+
+    #[test]
+    fn process_replenishment_from_part_of_a_crate() {
+        let player_1 = Id::test("player 1");
+        let truck_id = UnitId::test("truck");
+        let crate_id = UnitId::test("crate");
         let infantry_id = UnitId::test("infantry");
 
-        let path = Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::South]);
+        let mut hungry_infantry = red_model(
+            &player_1,
+            Unit::Infantry,
+            Place::on_map(2, 2, FacingDirection::Right),
+        );
+        hungry_infantry.supplies = 24;
 
-        let player_1_actions = vec![Action::Travel {
-            unit_id: infantry_id.clone(),
-            path: path.clone(),
-            dismounted_from: None,
+        let mut indexes = Indexes::make(vec![
+            (
+                truck_id.clone(),
+                red_model(
+                    &player_1,
+                    Unit::Truck,
+                    Place::on_map(1, 2, FacingDirection::Right),
+                ),
+            ),
+            (
+                crate_id.clone(),
+                red_model(
+                    &player_1,
+                    Unit::SupplyCrate,
+                    Place::InUnit(truck_id.clone()),
+                ),
+            ),
+            (infantry_id.clone(), hungry_infantry),
+        ]);
+
+        let player_1_actions = vec![Action::Replenish {
+            replenishing_unit_id: truck_id,
+            units: vec![],
+            depleted_supply_crates: vec![],
+            path: Path::from_directions_test_only(&located::unit(1, 2), &vec![Direction::East]),
         }];
 
+        let processed_turn = process_turn(
+            RandSeed::test(),
+            &mut vec![(player_1, player_1_actions)],
+            &mut indexes,
+            &mut Map::grass_square(),
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+
+        let depleted = processed_turn.events.iter().find_map(|event| match event {
+            Event::DepletedCrate { unit_id, amount } if unit_id == &crate_id => Some(*amount),
+            _ => None,
+        });
+
+        // The crate keeps whatever it did not hand out
+        assert!(indexes.by_id.get(&infantry_id).unwrap().supplies > 24);
+        assert_eq!(
+            Some(indexes.by_id.get(&crate_id).unwrap().supplies),
+            depleted.map(|amount| 8192 - amount)
+        );
+        assert!(depleted.unwrap() < 8192);
+    }
+
+    #[test]
+    fn process_split_and_merge_crates() {
+        let rand_seed = RandSeed::test();
+
+        let player_1 = Id::test("player 1");
+        let truck_id = UnitId::test("truck");
+        let carried_crate_id = UnitId::test("carried crate");
+        let dropped_crate_id = UnitId::test("dropped crate");
+        let stack_crate_id = UnitId::test("stack crate");
+
+        let player_1_actions = vec![
+            Action::SplitCrate {
+                crate_id: carried_crate_id.clone(),
+                amount: 2000,
+            },
+            Action::MergeCrates {
+                crate_id: dropped_crate_id.clone(),
+                into_crate_id: stack_crate_id.clone(),
+            },
+        ];
+
         let mut actions = vec![(player_1.clone(), player_1_actions)];
 
-        let mut indexes = Indexes::make(vec![(
-            infantry_id.clone(),
-            unit::Model::new(
-                Unit::Infantry,
-                &player_1,
-                Place::OnMap(Located {
-                    x: 2,
-                    y: 2,
-                    value: FacingDirection::Right,
-                }),
-                &TeamColor::Red,
-            ),
-        )]);
+        let mut dropped_crate = red_model(
+            &player_1,
+            Unit::SupplyCrate,
+            Place::on_map(5, 5, FacingDirection::Right),
+        );
+        dropped_crate.supplies = 3000;
 
-        let map = Map::grass_square();
+        let mut stack_crate = dropped_crate.clone();
+        stack_crate.supplies = 6000;
+
+        let mut indexes = Indexes::make(vec![
+            (
+                truck_id.clone(),
+                red_model(
+                    &player_1,
+                    Unit::Truck,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                ),
+            ),
+            (
+                carried_crate_id.clone(),
+                red_model(
+                    &player_1,
+                    Unit::SupplyCrate,
+                    Place::InUnit(truck_id.clone()),
+                ),
+            ),
+            (dropped_crate_id.clone(), dropped_crate),
+            (stack_crate_id.clone(), stack_crate),
+        ]);
 
-        let got_errors = process_turn(rand_seed, &mut actions, &mut indexes, &map).errors;
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut Map::grass_square(),
+            Table::bundled(),
+            &Conditions::test(),
+        );
 
-        let want_errors: Vec<String> = vec![];
-        assert_eq!(want_errors, got_errors);
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
 
-        let got_infantry_loc = indexes
-            .by_id
-            .get(&infantry_id)
-            .unwrap()
-            .place
-            .to_map_loc()
+        let new_crate_id = processed_turn
+            .events
+            .iter()
+            .find_map(|event| match event {
+                Event::SplitCrate { new_crate_id, .. } => Some(new_crate_id.clone()),
+                _ => None,
+            })
             .unwrap();
 
-        let want_loc = &Located {
-            x: 2,
-            y: 3,
-            value: FacingDirection::Right,
-        };
+        let new_crate = indexes.by_id.get(&new_crate_id).unwrap();
 
-        assert_eq!(want_loc, got_infantry_loc);
+        assert_eq!(2000, new_crate.supplies);
+        assert_eq!(Place::on_map(2, 2, FacingDirection::Right), new_crate.place);
+        assert_eq!(6192, indexes.by_id.get(&carried_crate_id).unwrap().supplies);
 
-        let got_units_by_loc = indexes.by_location.get(&want_loc.to_unit()).unwrap();
+        // The stack only had room for some of the dropped crate
+        assert_eq!(8192, indexes.by_id.get(&stack_crate_id).unwrap().supplies);
+        assert_eq!(808, indexes.by_id.get(&dropped_crate_id).unwrap().supplies);
+    }
 
-        let mut infantry_after_turn = unit::Model::new(
-            Unit::Infantry,
+    #[test]
+    fn process_foraging() {
+        let player_1 = Id::test("player 1");
+        let player_2 = Id::test("player 2");
+        let forager_id = UnitId::test("forager");
+        let watched_id = UnitId::test("watched");
+        let enemy_id = UnitId::test("enemy");
+        let enemy_truck_id = UnitId::test("enemy truck");
+
+        let mut forager = red_model(
             &player_1,
-            Place::OnMap(Located {
-                x: 2,
-                y: 3,
-                value: FacingDirection::Right,
-            }),
-            &TeamColor::Red,
+            Unit::Infantry,
+            Place::on_map(2, 2, FacingDirection::Right),
         );
+        forager.supplies = 512;
 
-        infantry_after_turn.supplies = 982;
+        let mut watched = forager.clone();
+        watched.place = Place::on_map(8, 8, FacingDirection::Right);
 
-        let want_units = &vec![(infantry_id, FacingDirection::Right, infantry_after_turn)];
+        let mut indexes = Indexes::make(vec![
+            (forager_id.clone(), forager),
+            (watched_id.clone(), watched),
+            (
+                enemy_id,
+                red_model(
+                    &player_2,
+                    Unit::Infantry,
+                    Place::on_map(8, 9, FacingDirection::Right),
+                ),
+            ),
+            (
+                enemy_truck_id,
+                red_model(
+                    &player_2,
+                    Unit::Truck,
+                    Place::on_map(2, 3, FacingDirection::Right),
+                ),
+            ),
+        ]);
+
+        let mut map = Map::grass_square();
+
+        let foraged_by = |events: &Vec<Event>, want_id: &UnitId| {
+            events.iter().find_map(|event| match event {
+                Event::Foraged {
+                    unit_id, amount, ..
+                } if unit_id == want_id => Some(*amount),
+                _ => None,
+            })
+        };
+
+        let first_turn = process_turn(
+            RandSeed::test(),
+            &mut vec![(player_1.clone(), vec![])],
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        // An enemy truck next to it is no threat
+        assert_eq!(Some(16), foraged_by(&first_turn.events, &forager_id));
+        // Having an enemy right next to it halves what it can gather
+        assert_eq!(Some(8), foraged_by(&first_turn.events, &watched_id));
+        assert_eq!(1, map.times_foraged(&located::unit(2, 2)));
+
+        let second_turn = process_turn(
+            RandSeed::test(),
+            &mut vec![(player_1, vec![])],
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        // The tile has already been picked over once
+        assert_eq!(Some(8), foraged_by(&second_turn.events, &forager_id));
+        assert_eq!(2, map.times_foraged(&located::unit(2, 2)));
+    }
+
+    #[test]
+    fn process_rout() {
+        let rand_seed = RandSeed::test();
+
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let tank_id = UnitId::test("red tank");
+        let infantry_id = UnitId::test("blue infantry");
+
+        let red_actions = vec![Action::Attack(Attack {
+            unit_id: tank_id.clone(),
+            path: Path::from_directions_test_only(
+                &located::unit(2, 0),
+                &vec![Direction::South, Direction::South],
+            ),
+        })];
+
+        let mut actions = vec![(red_player_id.clone(), red_actions)];
+
+        let mut indexes = Indexes::make(vec![
+            (
+                tank_id.clone(),
+                unit::Model::new(
+                    Unit::Tank,
+                    &red_player_id,
+                    Place::on_map(2, 0, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                UnitId::test("red infantry"),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &red_player_id,
+                    Place::on_map(1, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                infantry_id.clone(),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &blue_player_id,
+                    Place::on_map(2, 2, FacingDirection::Left),
+                    &TeamColor::Blue,
+                ),
+            ),
+        ]);
+
+        // The blue infantry is cornered, with red infantry
+        // blocking the only way out
+        let mut map = Map::grass_square();
+        map.width = 3;
+        map.height = 3;
+
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+
+        assert!(processed_turn.events.contains(&Event::Routed {
+            unit_id: infantry_id.clone(),
+        }));
+
+        let infantry = indexes.by_id.get(&infantry_id).unwrap();
+
+        // Attacked from the flank, then hurt again for
+        // being unable to escape
+        assert_eq!(unit::Health::Medium, infantry.health);
+    }
+
+    #[test]
+    fn retreating_only_cancels_the_units_own_actions() {
+        let truck_id = UnitId::test("truck");
+        let infantry_id = UnitId::test("infantry");
+
+        let load_into = Action::LoadInto {
+            unit_id: infantry_id,
+            load_into: truck_id.clone(),
+            path: Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::East]),
+        };
+
+        let mut actions = vec![
+            load_into.clone(),
+            Action::Travel {
+                unit_id: truck_id.clone(),
+                path: Path::from_directions_test_only(&located::unit(3, 2), &vec![Direction::East]),
+                dismounted_from: None,
+            },
+        ];
+
+        cancel_actions_for_unit(&truck_id, &mut actions);
+
+        assert_eq!(vec![load_into], actions);
+    }
+
+    #[test]
+    fn process_capture() {
+        let rand_seed = RandSeed::test();
+
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let infantry_id = UnitId::test("red infantry");
+        let crate_id = UnitId::test("blue crate");
+
+        let red_actions = vec![Action::Travel {
+            unit_id: infantry_id.clone(),
+            path: Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::East]),
+            dismounted_from: None,
+        }];
+
+        let mut actions = vec![(red_player_id.clone(), red_actions)];
+
+        let mut indexes = Indexes::make(vec![
+            (
+                infantry_id.clone(),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &red_player_id,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                crate_id.clone(),
+                unit::Model::new(
+                    Unit::SupplyCrate,
+                    &blue_player_id,
+                    Place::on_map(3, 2, FacingDirection::Left),
+                    &TeamColor::Blue,
+                ),
+            ),
+        ]);
+
+        let mut map = Map::grass_square();
+
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+
+        assert!(processed_turn.events.contains(&Event::Captured {
+            unit_id: crate_id.clone(),
+            capturer_id: infantry_id,
+        }));
+
+        let supply_crate = indexes.by_id.get(&crate_id).unwrap();
 
-        assert_eq!(want_units, got_units_by_loc);
+        assert_eq!(red_player_id, supply_crate.owner);
+        assert_eq!(TeamColor::Red, supply_crate.color);
     }
 
     #[test]
-    fn process_loading() {
-        let rand_seed = RandSeed::test();
-
-        let player_1 = Id::from_string("player 1".to_string(), true).unwrap();
-        let infantry_id = UnitId::test("infantry");
-        let truck_id = UnitId::test("truck");
+    fn failed_actions_do_not_end_the_turn_early() {
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let infantry_id = UnitId::test("red infantry");
+        let crate_id = UnitId::test("blue crate");
+
+        // Orders for units that do not exist fail without
+        // leaving any events behind
+        let mut red_actions = (0..3)
+            .map(|n| Action::Bombard {
+                unit_id: UnitId::test(format!("missing {}", n).as_str()),
+                target: located::unit(5, 5),
+            })
+            .collect::<Vec<Action>>();
 
-        let player_1_loads_actions = vec![Action::LoadInto {
+        red_actions.push(Action::Travel {
             unit_id: infantry_id.clone(),
-            load_into: truck_id.clone(),
-            path: Path::from_directions_test_only(
-                &located::unit(2, 2),
-                &vec![Direction::South, Direction::South],
-            ),
-        }];
-
-        let mut actions = vec![(player_1.clone(), player_1_loads_actions)];
+            path: Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::East]),
+            dismounted_from: None,
+        });
 
         let mut indexes = Indexes::make(vec![
             (
                 infantry_id.clone(),
-                unit::Model::new(
+                red_model(
+                    &red_player_id,
                     Unit::Infantry,
-                    &player_1,
-                    Place::OnMap(Located {
-                        x: 2,
-                        y: 2,
-                        value: FacingDirection::Right,
-                    }),
-                    &TeamColor::Red,
+                    Place::on_map(2, 2, FacingDirection::Right),
                 ),
             ),
             (
-                truck_id.clone(),
+                crate_id.clone(),
                 unit::Model::new(
-                    Unit::Truck,
-                    &player_1,
-                    Place::OnMap(Located {
-                        x: 2,
-                        y: 4,
-                        value: FacingDirection::Right,
-                    }),
-                    &TeamColor::Red,
+                    Unit::SupplyCrate,
+                    &blue_player_id,
+                    Place::on_map(3, 2, FacingDirection::Left),
+                    &TeamColor::Blue,
                 ),
             ),
         ]);
 
-        let map = Map::grass_square();
+        let processed_turn = process_turn(
+            RandSeed::test(),
+            &mut vec![(red_player_id, red_actions)],
+            &mut indexes,
+            &mut Map::grass_square(),
+            Table::bundled(),
+            &Conditions::test(),
+        );
 
-        let got_errors = process_turn(rand_seed.clone(), &mut actions, &mut indexes, &map).errors;
+        assert_eq!(3, processed_turn.errors.len());
 
-        let want_errors: Vec<String> = vec![];
-        assert_eq!(want_errors, got_errors);
+        // The crate is only captured once the travel is done
+        let travelled_at = processed_turn
+            .events
+            .iter()
+            .position(|event| matches!(event, Event::Travelled { .. }));
+        let captured_at = processed_turn
+            .events
+            .iter()
+            .position(|event| matches!(event, Event::Captured { .. }));
 
-        // After the turn, the infantry is located in the truck
-        {
-            let got_infantry_loc = indexes
-                .by_id
-                .get(&infantry_id)
-                .unwrap()
-                .place
-                .in_unit_loc()
-                .unwrap();
+        assert!(travelled_at.is_some());
+        assert!(captured_at > travelled_at);
+    }
 
-            let want_loc = truck_id.clone();
+    #[test]
+    fn turns_without_upkeep_are_still_carried_out() {
+        let red_player_id = Id::test("red");
 
-            assert_eq!(want_loc, got_infantry_loc.clone());
-        }
+        let depot_id = UnitId::test("red depot");
+        let crate_id = UnitId::test("red crate");
+        let into_crate_id = UnitId::test("red other crate");
 
-        // After the turn, the infantry is no longer located a 2,2, where it was before
-        {
-            let got_units_by_loc = indexes.by_location.get(&located::unit(2, 2)).unwrap();
+        let red_crate = |supplies: i16| {
+            let mut crate_model = red_model(
+                &red_player_id,
+                Unit::SupplyCrate,
+                Place::on_map(4, 4, FacingDirection::Right),
+            );
+            crate_model.supplies = supplies;
+            crate_model
+        };
 
-            let want_units: Vec<(UnitId, FacingDirection, unit::Model)> = vec![];
+        // None of these units use up supplies,
+        // so the turn starts without any events
+        let mut indexes = Indexes::make(vec![
+            (
+                depot_id.clone(),
+                red_model(
+                    &red_player_id,
+                    Unit::SupplyDepot,
+                    Place::on_map(0, 2, FacingDirection::Right),
+                ),
+            ),
+            (crate_id.clone(), red_crate(100)),
+            (into_crate_id.clone(), red_crate(100)),
+        ]);
+        indexes.by_id.get_mut(&depot_id).unwrap().supplies = 0;
+
+        let processed_turn = process_turn(
+            RandSeed::test(),
+            &mut vec![(
+                red_player_id,
+                vec![Action::MergeCrates {
+                    crate_id: crate_id.clone(),
+                    into_crate_id: into_crate_id.clone(),
+                }],
+            )],
+            &mut indexes,
+            &mut Map::grass_square(),
+            Table::bundled(),
+            &Conditions::test(),
+        );
 
-            assert_eq!(&want_units, got_units_by_loc);
-        }
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+        assert!(processed_turn.events.contains(&Event::MergedCrates {
+            unit_id: crate_id,
+            into_crate_id,
+            amount: 100,
+        }));
+        assert!(processed_turn
+            .events
+            .iter()
+            .any(|event| matches!(event, Event::Stockpiled { .. })));
+    }
 
-        // After the turn, we can get the infantry by id as we expect it
-        {
-            let mut infantry_after_turn = unit::Model::new(
-                Unit::Infantry,
-                &player_1,
-                Place::InUnit(truck_id.clone()),
-                &TeamColor::Red,
-            );
+    #[test]
+    fn process_zone_of_control() {
+        let rand_seed = RandSeed::test();
 
-            infantry_after_turn.supplies = 972;
+        let red_player = Id::from_string("red".to_string(), true).unwrap();
+        let blue_player = Id::from_string("blue".to_string(), true).unwrap();
 
-            let got_infantry_by_id = indexes.by_id.get(&infantry_id.clone()).unwrap().clone();
-            let want_infantry_by_id = infantry_after_turn;
+        let red_infantry_id = UnitId::test("red infantry");
+        let blue_infantry_id = UnitId::test("blue infantry");
 
-            assert_eq!(want_infantry_by_id, got_infantry_by_id);
-        }
+        let path = Path::from_directions_test_only(
+            &located::unit(2, 2),
+            &vec![Direction::East, Direction::East, Direction::East],
+        );
 
-        let player_1_unload_actions = vec![
-            Action::Travel {
-                unit_id: truck_id.clone(),
-                path: Path::from_directions_test_only(
-                    &located::unit(2, 4),
-                    &vec![
-                        Direction::East,
-                        Direction::East,
-                        Direction::East,
-                        Direction::East,
-                    ],
-                ),
+        let mut actions = vec![(
+            red_player.clone(),
+            vec![Action::Travel {
+                unit_id: red_infantry_id.clone(),
+                path,
                 dismounted_from: None,
-            },
-            Action::Travel {
-                unit_id: infantry_id.clone(),
-                path: Path::from_directions_test_only(
-                    &located::unit(6, 4),
-                    &vec![Direction::South, Direction::South],
+            }],
+        )];
+
+        let mut indexes = Indexes::make(vec![
+            (
+                red_infantry_id.clone(),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &red_player,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                    &TeamColor::Red,
                 ),
-                dismounted_from: Some(truck_id.clone()),
-            },
-        ];
+            ),
+            (
+                blue_infantry_id,
+                unit::Model::new(
+                    Unit::Infantry,
+                    &blue_player,
+                    Place::on_map(4, 3, FacingDirection::Left),
+                    &TeamColor::Blue,
+                ),
+            ),
+        ]);
 
-        let mut actions = vec![(player_1.clone(), player_1_unload_actions)];
+        let got_errors = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut Map::grass_square(),
+            Table::bundled(),
+            &Conditions::test(),
+        )
+        .errors;
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, got_errors);
 
-        let got_errors = process_turn(rand_seed, &mut actions, &mut indexes, &map).errors;
+        let got_loc = indexes
+            .by_id
+            .get(&red_infantry_id)
+            .unwrap()
+            .place
+            .to_map_loc()
+            .unwrap()
+            .to_unit();
 
-        let want_errors: Vec<String> = vec![];
+        assert_eq!(located::unit(4, 2), got_loc);
+    }
 
-        assert_eq!(want_errors, got_errors);
+    #[test]
+    fn zone_of_control_stops_a_pick_up_short() {
+        let rand_seed = RandSeed::test();
 
-        // After the second turn, the truck has moved
-        {
-            let got_truck_loc = indexes
-                .by_id
-                .get(&truck_id)
-                .unwrap()
-                .place
-                .to_map_loc()
-                .unwrap();
+        let red_player = Id::test("red");
+        let blue_player = Id::test("blue");
 
-            let want_loc = Located {
-                x: 6,
-                y: 4,
-                value: FacingDirection::Right,
-            };
+        let truck_id = UnitId::test("red truck");
+        let crate_id = UnitId::test("red crate");
 
-            assert_eq!(want_loc, got_truck_loc.clone());
-        }
+        let mut actions = vec![(
+            red_player.clone(),
+            vec![Action::PickUp {
+                unit_id: truck_id.clone(),
+                cargo_id: crate_id.clone(),
+                path: Path::from_directions_test_only(
+                    &located::unit(2, 2),
+                    &vec![Direction::East, Direction::East, Direction::East],
+                ),
+            }],
+        )];
 
-        // After the second turn, the infantry is located south of the truck
-        {
-            let got_infantry_loc = indexes
-                .by_id
-                .get(&infantry_id)
-                .unwrap()
-                .place
-                .to_map_loc()
-                .unwrap();
+        let mut indexes = Indexes::make(vec![
+            (
+                truck_id.clone(),
+                unit::Model::new(
+                    Unit::Truck,
+                    &red_player,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                crate_id.clone(),
+                unit::Model::new(
+                    Unit::SupplyCrate,
+                    &red_player,
+                    Place::on_map(5, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                UnitId::test("blue infantry"),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &blue_player,
+                    Place::on_map(3, 3, FacingDirection::Left),
+                    &TeamColor::Blue,
+                ),
+            ),
+        ]);
 
-            let want_loc = Located {
-                x: 6,
-                y: 6,
-                value: FacingDirection::Right,
-            };
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut Map::grass_square(),
+            Table::bundled(),
+            &Conditions::test(),
+        );
 
-            assert_eq!(want_loc, got_infantry_loc.clone());
-        }
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
+
+        assert!(!processed_turn
+            .events
+            .iter()
+            .any(|event| matches!(event, Event::PickedUp { .. })));
+
+        let truck_loc = indexes.by_id.get(&truck_id).unwrap().place.to_map_loc();
+
+        assert_eq!(
+            Some(located::unit(3, 2)),
+            truck_loc.map(|loc| loc.to_unit())
+        );
+        assert_eq!(
+            Place::on_map(5, 2, FacingDirection::Right),
+            indexes.by_id.get(&crate_id).unwrap().place
+        );
     }
 
     #[test]
-    fn process_picking_up_crate() {
+    fn process_bombard() {
         let rand_seed = RandSeed::test();
 
-        let player_1 = Id::from_string("player 1".to_string(), true).unwrap();
-        let truck_id = UnitId::test("truck");
-        let supply_crate_id = UnitId::test("supply crate");
+        let red_player = Id::from_string("red".to_string(), true).unwrap();
+        let blue_player = Id::from_string("blue".to_string(), true).unwrap();
 
-        let player_1_pickup_actions = vec![Action::PickUp {
-            unit_id: truck_id.clone(),
-            cargo_id: supply_crate_id.clone(),
-            path: Path::from_directions_test_only(
-                &located::unit(4, 4),
-                &vec![Direction::West, Direction::West],
-            ),
-        }];
+        let artillery_id = UnitId::test("red artillery");
+        let infantry_id = UnitId::test("blue infantry");
 
-        let mut actions = vec![(player_1.clone(), player_1_pickup_actions)];
+        let mut actions = vec![(
+            red_player.clone(),
+            vec![Action::Bombard {
+                unit_id: artillery_id.clone(),
+                target: located::unit(5, 2),
+            }],
+        )];
 
         let mut indexes = Indexes::make(vec![
             (
-                supply_crate_id.clone(),
+                artillery_id.clone(),
                 unit::Model::new(
-                    Unit::SupplyCrate,
-                    &player_1,
-                    Place::OnMap(Located {
-                        x: 2,
-                        y: 4,
-                        value: FacingDirection::Right,
-                    }),
+                    Unit::Artillery,
+                    &red_player,
+                    Place::on_map(2, 2, FacingDirection::Right),
                     &TeamColor::Red,
                 ),
             ),
             (
-                truck_id.clone(),
+                infantry_id.clone(),
                 unit::Model::new(
-                    Unit::Truck,
-                    &player_1,
-                    Place::OnMap(Located {
-                        x: 4,
-                        y: 4,
-                        value: FacingDirection::Left,
-                    }),
-                    &TeamColor::Red,
+                    Unit::Infantry,
+                    &blue_player,
+                    Place::on_map(5, 2, FacingDirection::Left),
+                    &TeamColor::Blue,
                 ),
             ),
         ]);
 
-        let map = Map::grass_square();
-
-        let got_errors = process_turn(rand_seed.clone(), &mut actions, &mut indexes, &map).errors;
-
-        let want_errors: Vec<String> = vec![];
-        assert_eq!(want_errors, got_errors);
-
-        // After turn truck is located where the crate was
-        {
-            let got_truck_loc = indexes
-                .by_id
-                .get(&truck_id)
-                .unwrap()
-                .place
-                .to_map_loc()
-                .unwrap()
-                .to_unit();
+        let processed_turn = process_turn(
+            rand_seed,
+            &mut actions,
+            &mut indexes,
+            &mut Map::grass_square(),
+            Table::bundled(),
+            &Conditions::test(),
+        );
 
-            let want_truck_loc = located::unit(2, 4);
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, processed_turn.errors);
 
-            assert_eq!(want_truck_loc, got_truck_loc);
+        assert!(processed_turn.events.contains(&Event::Bombarded {
+            unit_id: artillery_id.clone(),
+            target: located::unit(5, 2),
+            cost: 256,
+        }));
 
-            let got_prev_loc = indexes.by_location.get(&located::unit(4, 4));
-            let no_units: Vec<(UnitId, FacingDirection, unit::Model)> = vec![];
-            let want_prev_loc: Option<&Vec<(UnitId, FacingDirection, unit::Model)>> =
-                Some(&no_units);
+        let infantry = indexes.by_id.get(&infantry_id).unwrap();
 
-            assert_eq!(want_prev_loc, got_prev_loc);
+        assert_eq!(Health::High, infantry.health);
 
-            let got_curr_loc = indexes.by_location.get(&located::unit(2, 4));
+        let artillery = indexes.by_id.get(&artillery_id).unwrap();
 
-            let mut truck_after_turn = unit::Model::new(
-                Unit::Truck,
-                &player_1,
-                Place::OnMap(Located {
-                    x: 2,
-                    y: 4,
-                    value: FacingDirection::Left,
-                }),
-                &TeamColor::Red,
-            );
+        assert_eq!(2048 - 16 - 256, artillery.supplies);
+    }
 
-            truck_after_turn.supplies = 1962;
+    #[test]
+    fn cannot_bombard_and_move_whatever_the_order() {
+        let red_player = Id::test("red");
+        let blue_player = Id::test("blue");
+
+        let artillery_id = UnitId::test("red artillery");
+        let infantry_id = UnitId::test("blue infantry");
+
+        let mut rng = RandGen::test();
+
+        for _ in 0..8 {
+            let mut actions = vec![(
+                red_player.clone(),
+                vec![
+                    Action::Bombard {
+                        unit_id: artillery_id.clone(),
+                        target: located::unit(5, 2),
+                    },
+                    Action::Travel {
+                        unit_id: artillery_id.clone(),
+                        path: Path::from_directions_test_only(
+                            &located::unit(2, 2),
+                            &vec![Direction::East],
+                        ),
+                        dismounted_from: None,
+                    },
+                ],
+            )];
 
-            let one_unit: Vec<(UnitId, FacingDirection, unit::Model)> =
-                vec![(truck_id.clone(), FacingDirection::Left, truck_after_turn)];
+            let mut indexes = Indexes::make(vec![
+                (
+                    artillery_id.clone(),
+                    unit::Model::new(
+                        Unit::Artillery,
+                        &red_player,
+                        Place::on_map(2, 2, FacingDirection::Right),
+                        &TeamColor::Red,
+                    ),
+                ),
+                (
+                    infantry_id.clone(),
+                    unit::Model::new(
+                        Unit::Infantry,
+                        &blue_player,
+                        Place::on_map(5, 2, FacingDirection::Left),
+                        &TeamColor::Blue,
+                    ),
+                ),
+            ]);
+
+            let processed_turn = process_turn(
+                RandSeed::next(&mut rng),
+                &mut actions,
+                &mut indexes,
+                &mut Map::grass_square(),
+                Table::bundled(),
+                &Conditions::test(),
+            );
 
-            let want_curr_loc: Option<&Vec<(UnitId, FacingDirection, unit::Model)>> =
-                Some(&one_unit);
+            assert!(!processed_turn
+                .events
+                .iter()
+                .any(|event| matches!(event, Event::Bombarded { .. })));
 
-            assert_eq!(want_curr_loc, got_curr_loc);
+            assert_eq!(
+                Place::on_map(3, 2, FacingDirection::Right),
+                indexes.by_id.get(&artillery_id).unwrap().place
+            );
+            assert_eq!(
+                Health::Full,
+                indexes.by_id.get(&infantry_id).unwrap().health
+            );
         }
+    }
 
-        // After turn crates location is inside truck
-        {
-            let got_crate_loc = indexes
-                .by_id
-                .get(&supply_crate_id)
-                .unwrap()
-                .place
-                .in_unit_loc()
-                .unwrap()
-                .clone();
+    #[test]
+    fn process_build() {
+        let rand_seed = RandSeed::test();
 
-            let want_crate_loc = truck_id.clone();
+        let red_player = Id::from_string("red".to_string(), true).unwrap();
 
-            assert_eq!(want_crate_loc, got_crate_loc);
-        }
+        let engineer_id = UnitId::test("red engineer");
 
-        let player_1_drop_actions = vec![Action::DropOff {
-            cargo_id: supply_crate_id.clone(),
-        }];
+        let make_actions = || {
+            vec![(
+                red_player.clone(),
+                vec![Action::Build {
+                    unit_id: engineer_id.clone(),
+                    target: located::unit(3, 2),
+                    structure: Tile::Fortification,
+                }],
+            )]
+        };
 
-        let mut actions = vec![(player_1.clone(), player_1_drop_actions)];
+        let mut indexes = Indexes::make(vec![(
+            engineer_id.clone(),
+            unit::Model::new(
+                Unit::Engineer,
+                &red_player,
+                Place::on_map(2, 2, FacingDirection::Right),
+                &TeamColor::Red,
+            ),
+        )]);
 
-        let got_errors = process_turn(rand_seed.clone(), &mut actions, &mut indexes, &map).errors;
+        let mut map = Map::grass_square();
 
-        let want_errors: Vec<String> = vec![];
-        assert_eq!(want_errors, got_errors);
+        let first_turn = process_turn(
+            rand_seed.clone(),
+            &mut make_actions(),
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
 
-        // After second turn crates location is on map
-        {
-            let got_crate_loc = indexes
-                .by_id
-                .get(&supply_crate_id)
-                .unwrap()
-                .place
-                .to_map_loc()
-                .unwrap()
-                .clone();
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, first_turn.errors);
+        assert_eq!(Tile::GrassPlain, map.get_tile(&located::unit(3, 2)));
+
+        let second_turn = process_turn(
+            rand_seed,
+            &mut make_actions(),
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
 
-            let want_crate_loc = Located {
-                x: 2,
-                y: 4,
-                value: FacingDirection::Left,
-            };
+        assert_eq!(want_errors, second_turn.errors);
+        assert!(second_turn.events.contains(&Event::TerrainChanged {
+            unit_id: engineer_id.clone(),
+            loc: located::unit(3, 2),
+            tile: Tile::Fortification,
+        }));
+        assert_eq!(Tile::Fortification, map.get_tile(&located::unit(3, 2)));
+        assert_eq!(Tile::Fortification, map.grid[2][3].value);
+    }
 
-            assert_eq!(want_crate_loc, got_crate_loc);
+    #[test]
+    fn cannot_demolish_a_bridge_from_under_units() {
+        let red_player = Id::test("red");
+        let blue_player = Id::test("blue");
 
-            let mut crate_after_turn = unit::Model::new(
-                Unit::SupplyCrate,
-                &player_1,
-                Place::OnMap(Located {
-                    x: 2,
-                    y: 4,
-                    value: FacingDirection::Left,
-                }),
-                &TeamColor::Red,
-            );
+        let engineer_id = UnitId::test("blue engineer");
+        let infantry_id = UnitId::test("red infantry");
 
-            crate_after_turn.supplies = 8192;
+        let bridge_loc = located::unit(3, 2);
 
-            let mut truck_after_turn = unit::Model::new(
-                Unit::Truck,
-                &player_1,
-                Place::OnMap(Located {
-                    x: 2,
-                    y: 4,
-                    value: FacingDirection::Left,
-                }),
-                &TeamColor::Red,
+        let make_map = || {
+            let mut map = Map::grass_square();
+
+            map.set_tile(&bridge_loc, Tile::Bridge);
+            map.structures.insert(
+                bridge_loc.clone(),
+                Structure {
+                    owner: Some(red_player.clone()),
+                    replaced: Tile::Water,
+                },
             );
 
-            truck_after_turn.supplies = 1962;
+            map
+        };
 
-            let two_units: Vec<(UnitId, FacingDirection, unit::Model)> = vec![
-                (truck_id.clone(), FacingDirection::Left, truck_after_turn),
+        let make_indexes = |infantry_place: Place| {
+            Indexes::make(vec![
                 (
-                    supply_crate_id.clone(),
-                    FacingDirection::Left,
-                    crate_after_turn,
+                    engineer_id.clone(),
+                    unit::Model::new(
+                        Unit::Engineer,
+                        &blue_player,
+                        Place::on_map(2, 2, FacingDirection::Right),
+                        &TeamColor::Blue,
+                    ),
                 ),
-            ];
+                (
+                    infantry_id.clone(),
+                    unit::Model::new(Unit::Infantry, &red_player, infantry_place, &TeamColor::Red),
+                ),
+            ])
+        };
 
-            let want_curr_loc: Option<&Vec<(UnitId, FacingDirection, unit::Model)>> =
-                Some(&two_units);
+        let demolish = || {
+            (
+                blue_player.clone(),
+                vec![Action::Demolish {
+                    unit_id: engineer_id.clone(),
+                    target: bridge_loc.clone(),
+                }],
+            )
+        };
 
-            let got_curr_loc = indexes.by_location.get(&located::unit(2, 4));
+        // A unit standing on the bridge
+        let mut map = make_map();
+        let mut indexes = make_indexes(Place::on_map(3, 2, FacingDirection::Right));
+
+        let processed_turn = process_turn(
+            RandSeed::test(),
+            &mut vec![demolish(), (red_player.clone(), vec![])],
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
 
-            assert_eq!(want_curr_loc, got_curr_loc);
+        assert_eq!(
+            vec![TurnError {
+                player_id: Some(blue_player.clone()),
+                message:
+                    "process action error : cannot demolish a structure units are on or crossing"
+                        .to_string(),
+            }],
+            processed_turn.errors
+        );
+        assert_eq!(Tile::Bridge, map.get_tile(&bridge_loc));
+
+        // A unit crossing the bridge, whichever order
+        // the crossing and the demolishing happen in
+        let mut rng = RandGen::test();
+
+        for _ in 0..8 {
+            let mut map = make_map();
+            let mut indexes = make_indexes(Place::on_map(3, 1, FacingDirection::Right));
+
+            let cross = (
+                red_player.clone(),
+                vec![Action::Travel {
+                    unit_id: infantry_id.clone(),
+                    path: Path::from_directions_test_only(
+                        &located::unit(3, 1),
+                        &vec![Direction::South, Direction::South],
+                    ),
+                    dismounted_from: None,
+                }],
+            );
+
+            let processed_turn = process_turn(
+                RandSeed::next(&mut rng),
+                &mut vec![demolish(), cross],
+                &mut indexes,
+                &mut map,
+                Table::bundled(),
+                &Conditions::test(),
+            );
+
+            let travelled = processed_turn
+                .events
+                .iter()
+                .position(|event| matches!(event, Event::Travelled { .. }))
+                .unwrap();
+            let demolished = processed_turn
+                .events
+                .iter()
+                .position(|event| matches!(event, Event::Demolished { .. }));
+
+            if let Some(demolished) = demolished {
+                assert!(travelled < demolished);
+                assert_eq!(Tile::Water, map.get_tile(&bridge_loc));
+            } else {
+                assert_eq!(Tile::Bridge, map.get_tile(&bridge_loc));
+            }
         }
     }
 
     #[test]
-    fn process_two_player_travel() {
+    fn process_town_capture_and_supply() {
         let rand_seed = RandSeed::test();
 
-        let red_player_id = Id::from_string("red player".to_string(), true).unwrap();
-        let red_infantry_id = UnitId::test("red infantry");
+        let red_player_id = Id::test("red");
 
-        let red_player_actions = vec![Action::Travel {
-            unit_id: red_infantry_id.clone(),
-            path: Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::South]),
-            dismounted_from: None,
-        }];
+        let infantry_id = UnitId::test("red infantry");
+        let crate_id = UnitId::test("red crate");
 
-        let blue_player_id = Id::from_string("blue player".to_string(), true).unwrap();
-        let blue_infantry_id = UnitId::test("blue infantry");
+        let town_loc = located::unit(3, 2);
 
-        let blue_player_actions = vec![Action::Travel {
-            unit_id: blue_infantry_id.clone(),
-            path: Path::from_directions_test_only(&located::unit(4, 2), &vec![Direction::South]),
+        let red_actions = vec![Action::Travel {
+            unit_id: infantry_id.clone(),
+            path: Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::East]),
             dismounted_from: None,
         }];
 
-        let mut actions = vec![
-            (red_player_id.clone(), red_player_actions),
-            (blue_player_id.clone(), blue_player_actions),
-        ];
+        let mut actions = vec![(red_player_id.clone(), red_actions)];
 
         let mut indexes = Indexes::make(vec![
             (
-                red_infantry_id.clone(),
+                infantry_id.clone(),
                 unit::Model::new(
                     Unit::Infantry,
                     &red_player_id,
-                    Place::OnMap(Located {
-                        x: 2,
-                        y: 2,
-                        value: FacingDirection::Right,
-                    }),
+                    Place::on_map(2, 2, FacingDirection::Right),
                     &TeamColor::Red,
                 ),
             ),
             (
-                blue_infantry_id.clone(),
+                crate_id.clone(),
                 unit::Model::new(
-                    Unit::Infantry,
-                    &blue_player_id,
-                    Place::OnMap(Located {
-                        x: 4,
-                        y: 2,
-                        value: FacingDirection::Right,
-                    }),
-                    &TeamColor::Blue,
+                    Unit::SupplyCrate,
+                    &red_player_id,
+                    Place::on_map(3, 3, FacingDirection::Right),
+                    &TeamColor::Red,
                 ),
             ),
         ]);
 
-        let map = Map::grass_square();
+        indexes.by_id.get_mut(&crate_id).unwrap().supplies = 1000;
 
-        let got_errors = process_turn(rand_seed, &mut actions, &mut indexes, &map).errors;
+        let mut map = Map::grass_square();
 
-        let want_errors: Vec<String> = vec![];
-        assert_eq!(want_errors, got_errors);
+        map.set_tile(&town_loc, Tile::Town);
+        map.towns.insert(town_loc.clone(), Town { owner: None });
 
-        let got_red_infantry_loc = indexes
-            .by_id
-            .get(&red_infantry_id)
-            .unwrap()
-            .place
-            .to_map_loc()
+        let first_turn = process_turn(
+            rand_seed.clone(),
+            &mut actions,
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, first_turn.errors);
+        assert!(first_turn.events.contains(&Event::TownCaptured {
+            unit_id: infantry_id,
+            loc: town_loc.clone(),
+        }));
+        assert_eq!(Some(red_player_id.clone()), map.towns[&town_loc].owner);
+
+        let second_turn = process_turn(
+            rand_seed,
+            &mut vec![(red_player_id, vec![])],
+            &mut indexes,
+            &mut map,
+            Table::bundled(),
+            &Conditions::test(),
+        );
+
+        assert_eq!(want_errors, second_turn.errors);
+        assert!(second_turn.events.contains(&Event::SuppliedByTown {
+            unit_id: crate_id.clone(),
+            town: town_loc,
+            amount: TOWN_SUPPLIES_PER_TURN,
+        }));
+        assert_eq!(
+            1000 + TOWN_SUPPLIES_PER_TURN,
+            indexes.by_id.get(&crate_id).unwrap().supplies
+        );
+    }
+
+    #[test]
+    fn process_supply_depot_production() {
+        let rand_seed = RandSeed::test();
+
+        let red_player_id = Id::test("red");
+
+        let depot_id = UnitId::test("red depot");
+
+        let mut indexes = Indexes::make(vec![
+            (
+                depot_id.clone(),
+                unit::Model::new(
+                    Unit::SupplyDepot,
+                    &red_player_id,
+                    Place::on_map(0, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                UnitId::test("red infantry"),
+                unit::Model::new(
+                    Unit::Infantry,
+                    &red_player_id,
+                    Place::on_map(4, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+        ]);
+
+        let depot = indexes.by_id.get_mut(&depot_id).unwrap();
+        let production = depot
+            .definition(Table::bundled())
+            .supply_production
             .unwrap();
+        depot.supplies = depot.definition(Table::bundled()).max_supplies - (production * 2);
 
-        let want_red_infantry_loc = &Located {
-            x: 2,
-            y: 3,
-            value: FacingDirection::Right,
+        let mut map = Map::grass_square();
+
+        let mut take_turn = |indexes: &mut Indexes| {
+            process_turn(
+                rand_seed.clone(),
+                &mut vec![(red_player_id.clone(), vec![])],
+                indexes,
+                &mut map,
+                Table::bundled(),
+                &Conditions::test(),
+            )
         };
 
-        assert_eq!(want_red_infantry_loc, got_red_infantry_loc);
+        let first_turn = take_turn(&mut indexes);
 
-        let got_blue_infantry_loc = indexes
-            .by_id
-            .get(&blue_infantry_id)
-            .unwrap()
-            .place
-            .to_map_loc()
-            .unwrap();
+        let want_errors: Vec<TurnError> = vec![];
+        assert_eq!(want_errors, first_turn.errors);
+        assert!(first_turn.events.contains(&Event::Stockpiled {
+            unit_id: depot_id.clone(),
+            amount: production,
+        }));
 
-        let want_blue_infantry_loc = &Located {
-            x: 4,
-            y: 3,
-            value: FacingDirection::Right,
+        let crates = |indexes: &Indexes| {
+            indexes
+                .by_id
+                .values()
+                .filter(|unit_model| unit_model.unit == Unit::SupplyCrate)
+                .cloned()
+                .collect::<Vec<unit::Model>>()
         };
 
-        assert_eq!(want_blue_infantry_loc, got_blue_infantry_loc);
+        assert!(crates(&indexes).is_empty());
 
-        let got_units_by_loc = indexes
-            .by_location
-            .get(&want_red_infantry_loc.to_unit())
-            .unwrap();
+        let second_turn = take_turn(&mut indexes);
 
-        let mut infantry_after_turn = unit::Model::new(
-            Unit::Infantry,
-            &red_player_id,
-            Place::OnMap(Located {
-                x: 2,
-                y: 3,
-                value: FacingDirection::Right,
-            }),
-            &TeamColor::Red,
+        assert_eq!(want_errors, second_turn.errors);
+
+        let crates = crates(&indexes);
+
+        assert_eq!(1, crates.len());
+        assert_eq!(
+            crates[0].definition(Table::bundled()).max_supplies,
+            crates[0].supplies
         );
+        assert_eq!(red_player_id, crates[0].owner);
+        assert_eq!(0, indexes.by_id.get(&depot_id).unwrap().supplies);
+    }
 
-        infantry_after_turn.supplies = 982;
+    #[test]
+    fn supply_depots_fill_a_crate_every_four_turns() {
+        let red_player_id = Id::test("red");
 
-        let want_units = &vec![(red_infantry_id, FacingDirection::Right, infantry_after_turn)];
+        let depot_id = UnitId::test("red depot");
 
-        assert_eq!(want_units, got_units_by_loc);
+        let make_indexes = || {
+            let mut indexes = Indexes::make(vec![(
+                depot_id.clone(),
+                unit::Model::new(
+                    Unit::SupplyDepot,
+                    &red_player_id,
+                    Place::on_map(0, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            )]);
+
+            indexes.by_id.get_mut(&depot_id).unwrap().supplies = 0;
+
+            indexes
+        };
+
+        let crates_after = |turns: usize, indexes: &mut Indexes, unit_table: &Table| {
+            let mut map = Map::grass_square();
+            let mut rng = RandGen::test();
+            let mut crates_per_turn = vec![];
+
+            for _ in 0..turns {
+                process_turn(
+                    RandSeed::next(&mut rng),
+                    &mut vec![(red_player_id.clone(), vec![])],
+                    indexes,
+                    &mut map,
+                    unit_table,
+                    &Conditions::test(),
+                );
+
+                crates_per_turn.push(
+                    indexes
+                        .by_id
+                        .values()
+                        .filter(|unit_model| unit_model.unit == Unit::SupplyCrate)
+                        .count(),
+                );
+            }
+
+            crates_per_turn
+        };
+
+        let mut indexes = make_indexes();
+
+        assert_eq!(
+            vec![0, 0, 0, 1, 1, 1, 1, 2],
+            crates_after(8, &mut indexes, Table::bundled())
+        );
+        assert_eq!(0, indexes.by_id.get(&depot_id).unwrap().supplies);
+
+        // Production that does not divide evenly into a crate
+        // carries over instead of being thrown away
+        let src = include_str!("../unit/units.txt").replacen(
+            "supply_production = 2048",
+            "supply_production = 3000",
+            1,
+        );
+        let uneven = Table::from_src(src.as_str()).unwrap();
+
+        let mut indexes = make_indexes();
+
+        assert_eq!(vec![0, 0, 1], crates_after(3, &mut indexes, &uneven));
+        assert_eq!(
+            3000 * 3 - 8192,
+            indexes.by_id.get(&depot_id).unwrap().supplies
+        );
     }
 }
@@ -1,33 +1,652 @@
 use crate::direction::Direction;
+use crate::facing_direction::{Exposure, FacingDirection};
+use crate::game::conditions::Conditions;
+use crate::game::event::Event;
+use crate::game::unit_index::by_location;
+use crate::located;
+use crate::located::Located;
+use crate::map::Map;
+use crate::path::Path;
 use crate::unit;
+use crate::unit::definition::Table;
 use crate::unit::UnitId;
+use std::cmp::Ordering;
 
-enum StationaryBattleOutcome {
-    Todo,
+#[derive(Debug, PartialEq)]
+pub struct Outcome {
+    pub attacker_id: UnitId,
+    pub defender_id: UnitId,
+    pub attacker_damage: u8,
+    pub defender_damage: u8,
+    pub attacker_supply_cost: i16,
+    pub defender_supply_cost: i16,
 }
 
-fn stationary_battle(
-    _attacker: unit::Model,
+impl Outcome {
+    pub fn into_events(self, loc: Located<()>) -> Vec<Event> {
+        let mut events = vec![Event::Battle {
+            attacker_id: self.attacker_id.clone(),
+            defender_id: self.defender_id.clone(),
+            loc,
+        }];
+
+        for (unit_id, damage) in [
+            (&self.attacker_id, self.attacker_damage),
+            (&self.defender_id, self.defender_damage),
+        ] {
+            if damage > 0 {
+                events.push(Event::Damaged {
+                    unit_id: unit_id.clone(),
+                    damage,
+                });
+            }
+        }
+
+        for (unit_id, cost) in [
+            (&self.attacker_id, self.attacker_supply_cost),
+            (&self.defender_id, self.defender_supply_cost),
+        ] {
+            if cost > 0 {
+                events.push(Event::ConsumedBattleSupplies {
+                    unit_id: unit_id.clone(),
+                    cost,
+                });
+            }
+        }
+
+        events
+    }
+}
+
+impl Outcome {
+    // The loser of a one sided battle is pushed back away from
+    // the winner, or routed if there is nowhere to retreat to
+    #[allow(clippy::too_many_arguments)]
+    pub fn retreat_events(
+        &self,
+        attacker: &unit::Model,
+        defender: &unit::Model,
+        attacking_from: &Direction,
+        by_location: &by_location::Index,
+        map: &Map,
+        conditions: &Conditions,
+        unit_table: &Table,
+    ) -> Vec<Event> {
+        let (loser_id, loser, damage, away) =
+            if self.defender_damage > 0 && self.attacker_damage == 0 {
+                (
+                    &self.defender_id,
+                    defender,
+                    self.defender_damage,
+                    attacking_from.opposite(),
+                )
+            } else if self.attacker_damage > 0 && self.defender_damage == 0 {
+                (
+                    &self.attacker_id,
+                    attacker,
+                    self.attacker_damage,
+                    attacking_from.clone(),
+                )
+            } else {
+                return vec![];
+            };
+
+        // Destroyed units have nothing left to retreat
+        if loser.health.damaged(damage).is_none() {
+            return vec![];
+        }
+
+        let loc = match loser.place.to_map_loc() {
+            Some(loc) => loc.to_unit(),
+            None => return vec![],
+        };
+
+        for dir in retreat_directions(&away) {
+            let mut x = loc.x;
+            let mut y = loc.y;
+
+            dir.adjust_coord(&mut x, &mut y);
+
+            let in_bounds = (x, y) != (loc.x, loc.y) && x < map.width && y < map.height;
+
+            if !in_bounds {
+                continue;
+            }
+
+            // Units cannot retreat onto tiles they could not move
+            // onto, like water or a demolished bridge
+            let passable = loser
+                .definition(unit_table)
+                .mobility_cost_at(map, &located::unit(x, y), conditions)
+                .is_finite();
+
+            let occupied_by_enemy = by_location
+                .get(&located::unit(x, y))
+                .map(|units| {
+                    units
+                        .iter()
+                        .any(|(_, _, unit_model)| unit_model.owner != loser.owner)
+                })
+                .unwrap_or(false);
+
+            if passable && !occupied_by_enemy {
+                return vec![Event::Retreated {
+                    unit_id: loser_id.clone(),
+                    path: Path::from_directions(&loc, &vec![dir]),
+                }];
+            }
+        }
+
+        vec![
+            Event::Routed {
+                unit_id: loser_id.clone(),
+            },
+            Event::Damaged {
+                unit_id: loser_id.clone(),
+                damage: 1,
+            },
+        ]
+    }
+}
+
+// Straight back if possible, otherwise off to either side
+fn retreat_directions(away: &Direction) -> Vec<Direction> {
+    if away.is_x_axis() {
+        vec![away.clone(), Direction::North, Direction::South]
+    } else {
+        vec![away.clone(), Direction::East, Direction::West]
+    }
+}
+
+pub fn stationary_battle(
+    attacker: (&UnitId, &unit::Model),
     attacking_from: Direction,
     possible_defenders: Vec<(UnitId, unit::Model)>,
-) -> Result<StationaryBattleOutcome, String> {
-    let defender = choose_defender(attacking_from, possible_defenders)?;
+    map: &Map,
+    conditions: &Conditions,
+    unit_table: &Table,
+) -> Result<Outcome, String> {
+    let (attacker_id, attacker_model) = attacker;
+
+    let (defender_id, defender_model) =
+        choose_defender(&attacking_from, possible_defenders, map, unit_table)?;
+
+    Ok(fight(
+        (attacker_id, attacker_model),
+        (&defender_id, &defender_model),
+        &attacking_from,
+        map,
+        conditions,
+        unit_table,
+    ))
+}
 
-    Ok(StationaryBattleOutcome::Todo)
+// Two units moving into each other fight where their paths
+// cross, under the same rules as a stationary battle
+pub fn meeting_engagement(
+    attacker: (&UnitId, &unit::Model),
+    attacking_from: Direction,
+    enemy: (&UnitId, &unit::Model),
+    map: &Map,
+    conditions: &Conditions,
+    unit_table: &Table,
+) -> Outcome {
+    fight(
+        attacker,
+        enemy,
+        &attacking_from,
+        map,
+        conditions,
+        unit_table,
+    )
+}
+
+fn fight(
+    attacker: (&UnitId, &unit::Model),
+    defender: (&UnitId, &unit::Model),
+    attacking_from: &Direction,
+    map: &Map,
+    conditions: &Conditions,
+    unit_table: &Table,
+) -> Outcome {
+    let (attacker_id, attacker_model) = attacker;
+    let (defender_id, defender_model) = defender;
+
+    let attack = attack_strength(attacker_model, conditions, unit_table);
+    let defense = defense_strength(defender_model, attacking_from, map, unit_table);
+
+    let (attacker_damage, defender_damage) = damage_from_ratio(attack, defense);
+
+    Outcome {
+        attacker_id: attacker_id.clone(),
+        defender_id: defender_id.clone(),
+        attacker_damage,
+        defender_damage,
+        attacker_supply_cost: battle_supply_cost(attacker_model, unit_table),
+        defender_supply_cost: battle_supply_cost(defender_model, unit_table),
+    }
 }
 
+// The stack is defended by whichever unit is best
+// positioned to meet the attack, accounting for facing,
+// entrenchment and terrain as well as raw strength
 fn choose_defender(
-    _attacking_from: Direction,
+    attacking_from: &Direction,
     possible_defenders: Vec<(UnitId, unit::Model)>,
+    map: &Map,
+    unit_table: &Table,
 ) -> Result<(UnitId, unit::Model), String> {
     let (first_defender, remaining_defenders) = match possible_defenders.split_first() {
         None => return Err("no possible defenders".to_string()),
         Some(d) => d,
     };
 
-    let ret: (UnitId, unit::Model) = first_defender.clone();
+    let mut ret: (UnitId, unit::Model) = first_defender.clone();
+    let mut ret_strength = defense_strength(&ret.1, attacking_from, map, unit_table);
 
-    for (_defender_id, _defender) in remaining_defenders {}
+    for (defender_id, defender) in remaining_defenders {
+        let strength = defense_strength(defender, attacking_from, map, unit_table);
+
+        let is_better = match strength.partial_cmp(&ret_strength) {
+            Some(Ordering::Greater) => true,
+            Some(Ordering::Equal) => defender_id < &ret.0,
+            _ => false,
+        };
+
+        if is_better {
+            ret = (defender_id.clone(), defender.clone());
+            ret_strength = strength;
+        }
+    }
 
     Ok(ret)
 }
+
+fn attack_strength(attacker: &unit::Model, conditions: &Conditions, unit_table: &Table) -> f32 {
+    attacker.attack_strength(unit_table)
+        * supply_multiplier(attacker, unit_table)
+        * conditions.time_of_day.surprise_multiplier()
+}
+
+fn defense_strength(
+    defender: &unit::Model,
+    attacking_from: &Direction,
+    map: &Map,
+    unit_table: &Table,
+) -> f32 {
+    let (terrain_bonus, facing_multiplier) = match defender.place.to_map_loc() {
+        Some(loc) => (
+            map.get_tile(&loc.to_unit()).defense_bonus(),
+            facing_multiplier(&loc.value, attacking_from),
+        ),
+        None => (1.0, 1.0),
+    };
+
+    defender.defense_strength(unit_table)
+        * supply_multiplier(defender, unit_table)
+        * terrain_bonus
+        * facing_multiplier
+        * entrenchment_multiplier(&defender.entrenched_against, attacking_from)
+}
+
+// Units running low on supplies fight at as little as
+// half strength
+fn supply_multiplier(unit_model: &unit::Model, unit_table: &Table) -> f32 {
+    0.5 + unit_model.supplies_percent(unit_table).clamp(0.0, 0.5)
+}
+
+// Units are weaker when attacked from the side, and weaker
+// still when attacked from behind
+fn facing_multiplier(facing: &FacingDirection, attacking_from: &Direction) -> f32 {
+    match facing.exposure_to(attacking_from) {
+        Exposure::Front => 1.0,
+        Exposure::Flank => 0.85,
+        Exposure::Rear => 0.7,
+    }
+}
+
+// Entrenchments only help against attacks from the
+// direction the unit dug in against
+fn entrenchment_multiplier(
+    entrenched_against: &Option<Direction>,
+    attacking_from: &Direction,
+) -> f32 {
+    match entrenched_against {
+        Some(dir) if dir == attacking_from => 1.5,
+        _ => 1.0,
+    }
+}
+
+fn battle_supply_cost(unit_model: &unit::Model, unit_table: &Table) -> i16 {
+    unit_model
+        .definition(unit_table)
+        .battle_supply_cost
+        .map(|cost| cost.ceil() as i16)
+        .unwrap_or(0)
+}
+
+// Returns how many steps of health the attacker and the
+// defender lose, in that order
+fn damage_from_ratio(attack: f32, defense: f32) -> (u8, u8) {
+    let ratio = if defense > 0.0 {
+        attack / defense
+    } else {
+        f32::MAX
+    };
+
+    if ratio >= 4.0 {
+        (0, 3)
+    } else if ratio >= 2.5 {
+        (0, 2)
+    } else if ratio >= 1.5 {
+        (0, 1)
+    } else if ratio > 1.0 / 1.5 {
+        (1, 1)
+    } else if ratio > 1.0 / 2.5 {
+        (1, 0)
+    } else {
+        (2, 0)
+    }
+}
+
+#[cfg(test)]
+mod test_battle {
+    use crate::direction::Direction;
+    use crate::facing_direction::FacingDirection;
+    use crate::game::conditions::Conditions;
+    use crate::game::event::battle::{stationary_battle, Outcome};
+    use crate::game::event::Event;
+    use crate::game::unit_index::Indexes;
+    use crate::id::Id;
+    use crate::located;
+    use crate::map::Map;
+    use crate::path::Path;
+    use crate::team_color::TeamColor;
+    use crate::tile::Tile;
+    use crate::unit;
+    use crate::unit::definition::Table;
+    use crate::unit::{Place, Unit, UnitId};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn tank_beats_infantry_on_grass() {
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let tank_id = UnitId::test("red tank");
+        let tank = unit::Model::new(
+            Unit::Tank,
+            &red_player_id,
+            Place::on_map(2, 2, FacingDirection::Right),
+            &TeamColor::Red,
+        );
+
+        let infantry_id = UnitId::test("blue infantry");
+        let infantry = unit::Model::new(
+            Unit::Infantry,
+            &blue_player_id,
+            Place::on_map(3, 2, FacingDirection::Left),
+            &TeamColor::Blue,
+        );
+
+        let got = stationary_battle(
+            (&tank_id, &tank),
+            Direction::West,
+            vec![(infantry_id.clone(), infantry)],
+            &Map::grass_square(),
+            &Conditions::test(),
+            Table::bundled(),
+        )
+        .unwrap();
+
+        let want = Outcome {
+            attacker_id: tank_id,
+            defender_id: infantry_id,
+            attacker_damage: 0,
+            defender_damage: 1,
+            attacker_supply_cost: 128,
+            defender_supply_cost: 32,
+        };
+
+        assert_eq!(want, got);
+    }
+
+    #[test]
+    fn strongest_unit_in_stack_defends() {
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let infantry_id = UnitId::test("red infantry");
+        let infantry = unit::Model::new(
+            Unit::Infantry,
+            &red_player_id,
+            Place::on_map(2, 2, FacingDirection::Right),
+            &TeamColor::Red,
+        );
+
+        let truck_id = UnitId::test("blue truck");
+        let truck = unit::Model::new(
+            Unit::Truck,
+            &blue_player_id,
+            Place::on_map(3, 2, FacingDirection::Left),
+            &TeamColor::Blue,
+        );
+
+        // Facing away from the attack, but still stronger
+        // than the truck
+        let tank_id = UnitId::test("blue tank");
+        let tank = unit::Model::new(
+            Unit::Tank,
+            &blue_player_id,
+            Place::on_map(3, 2, FacingDirection::Right),
+            &TeamColor::Blue,
+        );
+
+        let got = stationary_battle(
+            (&infantry_id, &infantry),
+            Direction::West,
+            vec![(truck_id, truck), (tank_id.clone(), tank)],
+            &Map::grass_square(),
+            &Conditions::test(),
+            Table::bundled(),
+        )
+        .unwrap();
+
+        let want = Outcome {
+            attacker_id: infantry_id,
+            defender_id: tank_id,
+            attacker_damage: 1,
+            defender_damage: 0,
+            attacker_supply_cost: 32,
+            defender_supply_cost: 128,
+        };
+
+        assert_eq!(want, got);
+    }
+
+    #[test]
+    fn entrenchment_only_helps_from_the_front() {
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let tank_id = UnitId::test("red tank");
+        let tank = unit::Model::new(
+            Unit::Tank,
+            &red_player_id,
+            Place::on_map(2, 2, FacingDirection::Right),
+            &TeamColor::Red,
+        );
+
+        let infantry_id = UnitId::test("blue infantry");
+        let mut infantry = unit::Model::new(
+            Unit::Infantry,
+            &blue_player_id,
+            Place::on_map(3, 2, FacingDirection::Left),
+            &TeamColor::Blue,
+        );
+
+        infantry.entrenched_against = Some(Direction::West);
+
+        let from_the_front = stationary_battle(
+            (&tank_id, &tank),
+            Direction::West,
+            vec![(infantry_id.clone(), infantry.clone())],
+            &Map::grass_square(),
+            &Conditions::test(),
+            Table::bundled(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            (1, 1),
+            (
+                from_the_front.attacker_damage,
+                from_the_front.defender_damage
+            )
+        );
+
+        let from_the_flank = stationary_battle(
+            (&tank_id, &tank),
+            Direction::North,
+            vec![(infantry_id, infantry)],
+            &Map::grass_square(),
+            &Conditions::test(),
+            Table::bundled(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            (0, 1),
+            (
+                from_the_flank.attacker_damage,
+                from_the_flank.defender_damage
+            )
+        );
+    }
+
+    #[test]
+    fn best_positioned_unit_in_stack_defends() {
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let tank_id = UnitId::test("red tank");
+        let tank = unit::Model::new(
+            Unit::Tank,
+            &red_player_id,
+            Place::on_map(2, 2, FacingDirection::Right),
+            &TeamColor::Red,
+        );
+
+        let facing_away_id = UnitId::test("blue infantry facing away");
+        let facing_away = unit::Model::new(
+            Unit::Infantry,
+            &blue_player_id,
+            Place::on_map(3, 2, FacingDirection::Right),
+            &TeamColor::Blue,
+        );
+
+        let facing_attack_id = UnitId::test("blue infantry facing attack");
+        let facing_attack = unit::Model::new(
+            Unit::Infantry,
+            &blue_player_id,
+            Place::on_map(3, 2, FacingDirection::Left),
+            &TeamColor::Blue,
+        );
+
+        let got = stationary_battle(
+            (&tank_id, &tank),
+            Direction::West,
+            vec![
+                (facing_away_id, facing_away),
+                (facing_attack_id.clone(), facing_attack),
+            ],
+            &Map::grass_square(),
+            &Conditions::test(),
+            Table::bundled(),
+        )
+        .unwrap();
+
+        assert_eq!(facing_attack_id, got.defender_id);
+    }
+
+    #[test]
+    fn cannot_retreat_into_a_river() {
+        let red_player_id = Id::test("red");
+        let blue_player_id = Id::test("blue");
+
+        let tank_id = UnitId::test("red tank");
+        let tank = unit::Model::new(
+            Unit::Tank,
+            &red_player_id,
+            Place::on_map(2, 2, FacingDirection::Right),
+            &TeamColor::Red,
+        );
+
+        let infantry_id = UnitId::test("blue infantry");
+        let infantry = unit::Model::new(
+            Unit::Infantry,
+            &blue_player_id,
+            Place::on_map(3, 2, FacingDirection::Left),
+            &TeamColor::Blue,
+        );
+
+        let indexes = Indexes::make(vec![
+            (tank_id.clone(), tank.clone()),
+            (infantry_id.clone(), infantry.clone()),
+        ]);
+
+        let outcome = Outcome {
+            attacker_id: tank_id,
+            defender_id: infantry_id.clone(),
+            attacker_damage: 0,
+            defender_damage: 1,
+            attacker_supply_cost: 128,
+            defender_supply_cost: 32,
+        };
+
+        // The infantry has its back to a river
+        let mut map = Map::grass_square();
+        for y in 0..map.height {
+            map.set_tile(&located::unit(4, y), Tile::Water);
+        }
+
+        let along_the_bank = outcome.retreat_events(
+            &tank,
+            &infantry,
+            &Direction::West,
+            &indexes.by_location,
+            &map,
+            &Conditions::test(),
+            Table::bundled(),
+        );
+
+        assert_eq!(
+            vec![Event::Retreated {
+                unit_id: infantry_id.clone(),
+                path: Path::from_directions_test_only(
+                    &located::unit(3, 2),
+                    &vec![Direction::North]
+                ),
+            }],
+            along_the_bank
+        );
+
+        // With water on every side there is nowhere to go
+        map.set_tile(&located::unit(3, 1), Tile::Water);
+        map.set_tile(&located::unit(3, 3), Tile::Water);
+
+        let surrounded = outcome.retreat_events(
+            &tank,
+            &infantry,
+            &Direction::West,
+            &indexes.by_location,
+            &map,
+            &Conditions::test(),
+            Table::bundled(),
+        );
+
+        assert!(surrounded.contains(&Event::Routed {
+            unit_id: infantry_id
+        }));
+    }
+}
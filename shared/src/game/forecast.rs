@@ -0,0 +1,174 @@
+use crate::game::conditions::Conditions;
+use crate::unit;
+use crate::unit::definition::Table;
+use std::fmt;
+
+////////////////////////////////////////////////////////////////
+// Types //
+////////////////////////////////////////////////////////////////
+
+// How long a unit can hold out on the supplies it has,
+// if nothing comes along to resupply it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Forecast {
+    // The unit uses nothing up just by waiting around
+    Indefinite,
+    // How many turns until the unit perishes, counting
+    // the coming turn as the first
+    PerishesIn(u16),
+}
+
+////////////////////////////////////////////////////////////////
+// Api //
+////////////////////////////////////////////////////////////////
+
+impl Forecast {
+    // Upkeep comes out at the start of a turn, before any
+    // orders are carried out, so whatever the unit is ordered
+    // to do this turn only comes out of what is left over.
+    // The conditions are assumed to stay as they are
+    pub fn for_unit(
+        unit_model: &unit::Model,
+        queued_supply_cost: i16,
+        conditions: &Conditions,
+        unit_table: &Table,
+    ) -> Forecast {
+        let upkeep = match unit_model.baseline_supply_cost(unit_table, conditions) {
+            Some(cost) if cost > 0 => cost as i32,
+            _ => return Forecast::Indefinite,
+        };
+
+        let after_upkeep = unit_model.supplies as i32 - upkeep;
+
+        if after_upkeep <= 0 {
+            return Forecast::PerishesIn(1);
+        }
+
+        let after_orders = after_upkeep - queued_supply_cost as i32;
+
+        // Orders that leave too little for the next turn's
+        // upkeep starve the unit just the same
+        if after_orders <= upkeep {
+            return Forecast::PerishesIn(1);
+        }
+
+        let more_turns = (after_orders + upkeep - 1) / upkeep;
+
+        Forecast::PerishesIn((1 + more_turns) as u16)
+    }
+
+    pub fn starves_next_turn(&self) -> bool {
+        matches!(self, Forecast::PerishesIn(1))
+    }
+}
+
+impl fmt::Display for Forecast {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Forecast::Indefinite => write!(f, "never"),
+            Forecast::PerishesIn(1) => write!(f, "next turn"),
+            Forecast::PerishesIn(turns) => write!(f, "in {} turns", turns),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_forecast {
+    use crate::direction::Direction;
+    use crate::facing_direction::FacingDirection;
+    use crate::game::action::Action;
+    use crate::game::conditions::Conditions;
+    use crate::game::forecast::Forecast;
+    use crate::id::Id;
+    use crate::map::Map;
+    use crate::team_color::TeamColor;
+    use crate::unit;
+    use crate::unit::definition::Table;
+    use crate::unit::{Place, Unit, UnitId};
+    use pretty_assertions::assert_eq;
+
+    fn red(unit: Unit) -> unit::Model {
+        unit::Model::new(
+            unit,
+            &Id::test("red"),
+            Place::on_map(2, 2, FacingDirection::Right),
+            &TeamColor::Red,
+        )
+    }
+
+    #[test]
+    fn supply_crates_never_run_out() {
+        let supply_crate = red(Unit::SupplyCrate);
+
+        assert_eq!(
+            Forecast::Indefinite,
+            Forecast::for_unit(&supply_crate, 0, &Conditions::test(), Table::bundled())
+        );
+    }
+
+    #[test]
+    fn queued_orders_bring_the_end_closer() {
+        let mut infantry = red(Unit::Infantry);
+        let upkeep = infantry
+            .baseline_supply_cost(Table::bundled(), &Conditions::test())
+            .unwrap();
+
+        infantry.supplies = upkeep * 4;
+
+        assert_eq!(
+            Forecast::PerishesIn(4),
+            Forecast::for_unit(&infantry, 0, &Conditions::test(), Table::bundled())
+        );
+        assert_eq!(
+            Forecast::PerishesIn(3),
+            Forecast::for_unit(&infantry, upkeep, &Conditions::test(), Table::bundled())
+        );
+    }
+
+    #[test]
+    fn draining_orders_warn_of_starvation() {
+        let infantry_id = UnitId::test("infantry");
+        let mut infantry = red(Unit::Infantry);
+        let upkeep = infantry
+            .baseline_supply_cost(Table::bundled(), &Conditions::test())
+            .unwrap();
+
+        infantry.supplies = upkeep * 3;
+
+        let entrench = Action::Entrench {
+            unit_id: infantry_id.clone(),
+            against: Direction::East,
+        };
+
+        let entrench_cost = entrench.supply_cost_for(
+            &infantry_id,
+            &infantry,
+            &Map::grass_square(),
+            &Conditions::test(),
+            Table::bundled(),
+        );
+
+        assert!(
+            !Forecast::for_unit(&infantry, 0, &Conditions::test(), Table::bundled())
+                .starves_next_turn()
+        );
+        assert!(Forecast::for_unit(
+            &infantry,
+            entrench_cost,
+            &Conditions::test(),
+            Table::bundled()
+        )
+        .starves_next_turn());
+    }
+
+    #[test]
+    fn units_without_enough_for_upkeep_starve_next_turn() {
+        let mut infantry = red(Unit::Infantry);
+        infantry.supplies = 1;
+
+        assert!(
+            Forecast::for_unit(&infantry, 0, &Conditions::test(), Table::bundled())
+                .starves_next_turn()
+        );
+    }
+}
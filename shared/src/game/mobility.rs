@@ -1,7 +1,9 @@
+use crate::game::conditions::Conditions;
 use crate::game::unit_index;
 use crate::located;
 use crate::located::Located;
 use crate::map::Map;
+use crate::unit::definition::Table;
 use crate::unit::UnitId;
 use std::collections::{HashMap, HashSet};
 
@@ -15,21 +17,40 @@ pub fn get_units_mobility(
     map: &Map,
     unit_id: &UnitId,
     unit_indexes: &unit_index::Indexes,
+    visibility: &HashSet<Located<()>>,
+    conditions: &Conditions,
+    unit_table: &Table,
 ) -> Result<HashSet<Located<()>>, String> {
     match unit_indexes.by_id.get(unit_id) {
         None => Err("unit not found when getting units mobility".to_string()),
         Some(unit_model) => {
+            let definition = unit_model.definition(unit_table);
+
             let mut mobility = HashSet::new();
 
             let loc = unit_indexes.position_of_unit_or_transport(unit_id)?;
 
+            let origin = located::unit(loc.x, loc.y);
+
+            let zone_of_control =
+                unit_indexes.zone_of_control(&unit_model.owner, visibility, unit_table);
+
+            let enemy_locs = unit_indexes
+                .by_id
+                .values()
+                .filter(|other_model| other_model.owner != unit_model.owner)
+                .filter_map(|other_model| other_model.place.to_map_loc())
+                .map(|loc| loc.to_unit())
+                .filter(|loc| visibility.contains(loc))
+                .collect::<HashSet<Located<()>>>();
+
             let mut search: HashMap<Located<()>, Budget> = HashMap::new();
 
-            if !unit_model.unit.is_supply_crate() {
+            if !definition.is_supply_crate {
                 search.insert(
                     located::unit(loc.x, loc.y),
                     Budget {
-                        mobility: unit_model.unit.mobility_budget(),
+                        mobility: unit_model.mobility_budget(unit_table),
                         supply: unit_model.supplies,
                     },
                 );
@@ -40,6 +61,11 @@ pub fn get_units_mobility(
                     mobility.insert(search_loc.clone());
                     search.remove(&search_loc);
 
+                    // Units stop once they move next to a known
+                    // enemy, though they can still attack it. A unit
+                    // that starts next to an enemy can move away
+                    let halted = search_loc != origin && zone_of_control.contains(&search_loc);
+
                     let mobility_budget = budget.mobility;
                     let supply_budget = budget.supply;
 
@@ -49,15 +75,18 @@ pub fn get_units_mobility(
                     // north
                     if y > 0 {
                         let north_loc = located::unit(x, y - 1);
-                        let north_tile = map.get_tile(&north_loc);
-
-                        let mobility_cost = north_tile.mobility_cost(&unit_model.unit);
-                        let supply_budget_cost = north_tile.travel_supply_cost(&unit_model.unit);
+                        let mobility_cost =
+                            definition.mobility_cost_at(map, &north_loc, conditions);
+                        let supply_budget_cost =
+                            definition.travel_supply_cost_at(map, &north_loc, conditions);
 
                         let mobility_budget_at_tile = mobility_budget - mobility_cost;
                         let supply_budget_at_tile = supply_budget - supply_budget_cost;
 
-                        if mobility_budget_at_tile > 0.0 && supply_budget_at_tile > 0 {
+                        if mobility_budget_at_tile > 0.0
+                            && supply_budget_at_tile > 0
+                            && (!halted || enemy_locs.contains(&north_loc))
+                        {
                             search
                                 .entry(north_loc)
                                 .and_modify(|existing_budget| {
@@ -79,15 +108,17 @@ pub fn get_units_mobility(
                     // west
                     if x > 0 {
                         let west_loc = located::unit(x - 1, y);
-                        let west_tile = map.get_tile(&west_loc);
-
-                        let mobility_cost = west_tile.mobility_cost(&unit_model.unit);
-                        let supply_budget_cost = west_tile.travel_supply_cost(&unit_model.unit);
+                        let mobility_cost = definition.mobility_cost_at(map, &west_loc, conditions);
+                        let supply_budget_cost =
+                            definition.travel_supply_cost_at(map, &west_loc, conditions);
 
                         let mobility_budget_at_tile = mobility_budget - mobility_cost;
                         let supply_budget_at_tile = supply_budget - supply_budget_cost;
 
-                        if mobility_budget_at_tile > 0.0 && supply_budget_at_tile > 0 {
+                        if mobility_budget_at_tile > 0.0
+                            && supply_budget_at_tile > 0
+                            && (!halted || enemy_locs.contains(&west_loc))
+                        {
                             search
                                 .entry(west_loc)
                                 .and_modify(|existing_budget| {
@@ -109,15 +140,18 @@ pub fn get_units_mobility(
                     // south
                     {
                         let south_loc = located::unit(x, y + 1);
-                        let south_tile = map.get_tile(&south_loc);
-
-                        let mobility_cost = south_tile.mobility_cost(&unit_model.unit);
-                        let supply_budget_cost = south_tile.travel_supply_cost(&unit_model.unit);
+                        let mobility_cost =
+                            definition.mobility_cost_at(map, &south_loc, conditions);
+                        let supply_budget_cost =
+                            definition.travel_supply_cost_at(map, &south_loc, conditions);
 
                         let mobility_budget_at_tile = mobility_budget - mobility_cost;
                         let supply_budget_at_tile = supply_budget - supply_budget_cost;
 
-                        if mobility_budget_at_tile > 0.0 && supply_budget_at_tile > 0 {
+                        if mobility_budget_at_tile > 0.0
+                            && supply_budget_at_tile > 0
+                            && (!halted || enemy_locs.contains(&south_loc))
+                        {
                             search
                                 .entry(south_loc)
                                 .and_modify(|existing_budget| {
@@ -139,15 +173,17 @@ pub fn get_units_mobility(
                     // east
                     {
                         let east_loc = located::unit(x + 1, y);
-                        let east_tile = map.get_tile(&east_loc);
-
-                        let mobility_cost = east_tile.mobility_cost(&unit_model.unit);
-                        let supply_budget_cost = east_tile.travel_supply_cost(&unit_model.unit);
+                        let mobility_cost = definition.mobility_cost_at(map, &east_loc, conditions);
+                        let supply_budget_cost =
+                            definition.travel_supply_cost_at(map, &east_loc, conditions);
 
                         let mobility_budget_at_tile = mobility_budget - mobility_cost;
                         let supply_budget_at_tile = supply_budget - supply_budget_cost;
 
-                        if mobility_budget_at_tile > 0.0 && supply_budget_at_tile > 0 {
+                        if mobility_budget_at_tile > 0.0
+                            && supply_budget_at_tile > 0
+                            && (!halted || enemy_locs.contains(&east_loc))
+                        {
                             search
                                 .entry(east_loc)
                                 .and_modify(|existing_budget| {
@@ -176,6 +212,7 @@ pub fn get_units_mobility(
 #[cfg(test)]
 mod test_replenishment {
     use crate::facing_direction::FacingDirection;
+    use crate::game::conditions::Conditions;
     use crate::game::mobility::get_units_mobility;
     use crate::game::unit_index::Indexes;
     use crate::id::Id;
@@ -183,6 +220,7 @@ mod test_replenishment {
     use crate::map::Map;
     use crate::team_color::TeamColor;
     use crate::unit;
+    use crate::unit::definition::Table;
     use crate::unit::{Place, Unit, UnitId};
     use pretty_assertions::assert_eq;
     use std::collections::HashSet;
@@ -206,7 +244,15 @@ mod test_replenishment {
 
         let indexes = Indexes::make(vec![(unit_id.clone(), infantry)]);
 
-        let got = get_units_mobility(&Map::grass_square(), &unit_id, &indexes).unwrap();
+        let got = get_units_mobility(
+            &Map::grass_square(),
+            &unit_id,
+            &indexes,
+            &HashSet::new(),
+            &Conditions::test(),
+            Table::bundled(),
+        )
+        .unwrap();
 
         let wanted_pos: Vec<(u16, u16)> = vec![
             (6, 8),
@@ -257,7 +303,15 @@ mod test_replenishment {
 
         let indexes = Indexes::make(vec![(unit_id.clone(), infantry)]);
 
-        let got = get_units_mobility(&Map::grass_square(), &unit_id, &indexes).unwrap();
+        let got = get_units_mobility(
+            &Map::grass_square(),
+            &unit_id,
+            &indexes,
+            &HashSet::new(),
+            &Conditions::test(),
+            Table::bundled(),
+        )
+        .unwrap();
 
         let wanted_pos: Vec<(u16, u16)> = vec![(7, 8), (8, 7), (8, 8), (8, 9), (9, 8)];
 
@@ -268,4 +322,66 @@ mod test_replenishment {
 
         assert_eq!(got, want);
     }
+
+    #[test]
+    fn zone_of_control() {
+        let red_player_id = Id::from_string("red".to_string(), true).unwrap();
+        let blue_player_id = Id::from_string("blue".to_string(), true).unwrap();
+
+        let tank_id = UnitId::test("red tank");
+        let infantry_id = UnitId::test("blue infantry");
+
+        let tank = unit::Model::new(
+            Unit::Tank,
+            &red_player_id,
+            Place::OnMap(Located {
+                x: 8,
+                y: 8,
+                value: FacingDirection::Right,
+            }),
+            &TeamColor::Red,
+        );
+
+        let infantry = unit::Model::new(
+            Unit::Infantry,
+            &blue_player_id,
+            Place::OnMap(Located {
+                x: 10,
+                y: 8,
+                value: FacingDirection::Left,
+            }),
+            &TeamColor::Blue,
+        );
+
+        let indexes = Indexes::make(vec![(tank_id.clone(), tank), (infantry_id, infantry)]);
+
+        let unseen = get_units_mobility(
+            &Map::grass_square(),
+            &tank_id,
+            &indexes,
+            &HashSet::new(),
+            &Conditions::test(),
+            Table::bundled(),
+        )
+        .unwrap();
+
+        assert!(unseen.contains(&(12, 8).into()));
+
+        let visibility: HashSet<Located<()>> = vec![(10, 8).into()].into_iter().collect();
+
+        let seen = get_units_mobility(
+            &Map::grass_square(),
+            &tank_id,
+            &indexes,
+            &visibility,
+            &Conditions::test(),
+            Table::bundled(),
+        )
+        .unwrap();
+
+        assert!(seen.contains(&(9, 8).into()));
+        assert!(seen.contains(&(10, 8).into()));
+        assert!(!seen.contains(&(11, 8).into()));
+        assert!(!seen.contains(&(12, 8).into()));
+    }
 }
@@ -2,6 +2,7 @@ use crate::game::unit_index::Indexes;
 use crate::id::Id;
 use crate::located::Located;
 use crate::unit;
+use crate::unit::definition::Table;
 use crate::unit::UnitId;
 use std::cmp;
 use std::cmp::Ordering;
@@ -19,10 +20,11 @@ impl Replenishment {
         replenishing_unit_id: &UnitId,
         loc_of_replenishment: Located<()>,
         unit_indexes: &Indexes,
+        unit_table: &Table,
     ) -> Result<Replenishment, String> {
         let unit_ids_to_replenish: Vec<UnitId> = match unit_indexes
             .by_location
-            .get_replenishable_units(viewer_id, &loc_of_replenishment)
+            .get_replenishable_units(viewer_id, &loc_of_replenishment, unit_table)
         {
             Some(mut u) => {
                 u.push(replenishing_unit_id.clone());
@@ -40,7 +42,7 @@ impl Replenishment {
                     let mut supply_crates = cargo
                         .iter()
                         .filter_map(|(unit_id, unit_model)| {
-                            if unit_model.unit.is_supply_crate() {
+                            if unit_model.definition(unit_table).is_supply_crate {
                                 Some((unit_id.clone(), unit_model.supplies))
                             } else {
                                 None
@@ -90,7 +92,8 @@ impl Replenishment {
             for (unit_id, unit_model) in units_to_replenish.clone() {
                 let adjustment = unit_adjustments.entry(unit_id).or_insert(0);
 
-                let capacity = unit_model.unit.max_supplies() - (unit_model.supplies + *adjustment);
+                let capacity = unit_model.definition(unit_table).max_supplies
+                    - (unit_model.supplies + *adjustment);
 
                 if capacity == 0 {
                     units_in_need_of_supplies -= 1;
@@ -138,6 +141,7 @@ mod test_replenishment {
     use crate::id::Id;
     use crate::located::Located;
     use crate::team_color::TeamColor;
+    use crate::unit::definition::Table;
     use crate::unit::{Place, Unit, UnitId};
     use crate::{located, unit};
     use pretty_assertions::assert_eq;
@@ -194,9 +198,14 @@ mod test_replenishment {
 
         let indexes = Indexes::make(units);
 
-        let got =
-            Replenishment::calculate(&red_player_id, &red_truck_id, located::unit(1, 1), &indexes)
-                .unwrap();
+        let got = Replenishment::calculate(
+            &red_player_id,
+            &red_truck_id,
+            located::unit(1, 1),
+            &indexes,
+            Table::bundled(),
+        )
+        .unwrap();
 
         let want = Replenishment {
             replenished_units: vec![(red_infantry_id, 1023), (red_truck_id, 0)],
@@ -258,9 +267,14 @@ mod test_replenishment {
 
         let indexes = Indexes::make(units);
 
-        let got =
-            Replenishment::calculate(&red_player_id, &red_truck_id, located::unit(1, 1), &indexes)
-                .unwrap();
+        let got = Replenishment::calculate(
+            &red_player_id,
+            &red_truck_id,
+            located::unit(1, 1),
+            &indexes,
+            Table::bundled(),
+        )
+        .unwrap();
 
         let want = Replenishment {
             replenished_units: vec![(red_infantry_id, 500), (red_truck_id, 0)],
@@ -324,9 +338,14 @@ mod test_replenishment {
 
         let indexes = Indexes::make(units);
 
-        let got =
-            Replenishment::calculate(&red_player_id, &red_truck_id, located::unit(1, 1), &indexes)
-                .unwrap();
+        let got = Replenishment::calculate(
+            &red_player_id,
+            &red_truck_id,
+            located::unit(1, 1),
+            &indexes,
+            Table::bundled(),
+        )
+        .unwrap();
 
         let want = Replenishment {
             replenished_units: vec![
@@ -394,9 +413,14 @@ mod test_replenishment {
 
         let indexes = Indexes::make(units);
 
-        let got =
-            Replenishment::calculate(&red_player_id, &red_truck_id, located::unit(1, 1), &indexes)
-                .unwrap();
+        let got = Replenishment::calculate(
+            &red_player_id,
+            &red_truck_id,
+            located::unit(1, 1),
+            &indexes,
+            Table::bundled(),
+        )
+        .unwrap();
 
         let want = Replenishment {
             replenished_units: vec![
@@ -464,9 +488,14 @@ mod test_replenishment {
 
         let indexes = Indexes::make(units);
 
-        let got =
-            Replenishment::calculate(&red_player_id, &red_truck_id, located::unit(1, 1), &indexes)
-                .unwrap();
+        let got = Replenishment::calculate(
+            &red_player_id,
+            &red_truck_id,
+            located::unit(1, 1),
+            &indexes,
+            Table::bundled(),
+        )
+        .unwrap();
 
         let want = Replenishment {
             replenished_units: vec![
@@ -549,9 +578,14 @@ mod test_replenishment {
 
         let indexes = Indexes::make(units);
 
-        let got =
-            Replenishment::calculate(&red_player_id, &red_truck_id, located::unit(1, 1), &indexes)
-                .unwrap();
+        let got = Replenishment::calculate(
+            &red_player_id,
+            &red_truck_id,
+            located::unit(1, 1),
+            &indexes,
+            Table::bundled(),
+        )
+        .unwrap();
 
         let want = Replenishment {
             replenished_units: vec![
@@ -643,9 +677,14 @@ mod test_replenishment {
 
         let indexes = Indexes::make(units);
 
-        let got =
-            Replenishment::calculate(&red_player_id, &red_truck_id, located::unit(1, 1), &indexes)
-                .unwrap();
+        let got = Replenishment::calculate(
+            &red_player_id,
+            &red_truck_id,
+            located::unit(1, 1),
+            &indexes,
+            Table::bundled(),
+        )
+        .unwrap();
 
         let want = Replenishment {
             replenished_units: vec![
@@ -739,9 +778,14 @@ mod test_replenishment {
 
         let indexes = Indexes::make(units);
 
-        let got =
-            Replenishment::calculate(&red_player_id, &red_truck_id, located::unit(1, 1), &indexes)
-                .unwrap();
+        let got = Replenishment::calculate(
+            &red_player_id,
+            &red_truck_id,
+            located::unit(1, 1),
+            &indexes,
+            Table::bundled(),
+        )
+        .unwrap();
 
         let want = Replenishment {
             replenished_units: vec![
@@ -837,9 +881,14 @@ mod test_replenishment {
 
         let indexes = Indexes::make(units);
 
-        let got =
-            Replenishment::calculate(&red_player_id, &red_truck_id, located::unit(1, 1), &indexes)
-                .unwrap();
+        let got = Replenishment::calculate(
+            &red_player_id,
+            &red_truck_id,
+            located::unit(1, 1),
+            &indexes,
+            Table::bundled(),
+        )
+        .unwrap();
 
         let want = Replenishment {
             replenished_units: vec![
@@ -0,0 +1,233 @@
+use crate::game::calculate_player_visibility;
+use crate::game::conditions::Conditions;
+use crate::game::mobility::get_units_mobility;
+use crate::game::unit_index::Indexes;
+use crate::id::Id;
+use crate::located;
+use crate::located::Located;
+use crate::map::Map;
+use crate::unit::definition::Table;
+use crate::unit::UnitId;
+use std::collections::HashSet;
+
+////////////////////////////////////////////////////////////////
+// Types //
+////////////////////////////////////////////////////////////////
+
+// Units that are cut off from their depots have to live
+// off what they carry, which costs them more each turn
+pub const OUT_OF_SUPPLY_BASELINE_MULTIPLIER: f32 = 1.5;
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Network {
+    // The units that can draw supplies from a depot
+    pub in_supply: HashSet<UnitId>,
+    // Every tile the network can bring supplies to
+    pub reach: HashSet<Located<()>>,
+}
+
+// A depot, crate or truck, and the tiles it can
+// hand supplies out on
+struct Link {
+    loc: Located<()>,
+    reach: HashSet<Located<()>>,
+    is_depot: bool,
+}
+
+////////////////////////////////////////////////////////////////
+// Api //
+////////////////////////////////////////////////////////////////
+
+// Supplies start out at a player's depots, and are passed
+// along by crates and by trucks within driving range of
+// each other. Depots and crates hand supplies out to the
+// tiles right around them, trucks to anywhere they can drive
+pub fn network(
+    player_id: &Id,
+    indexes: &Indexes,
+    map: &Map,
+    conditions: &Conditions,
+    unit_table: &Table,
+) -> Network {
+    let visibility =
+        calculate_player_visibility(player_id, map, &indexes.by_id, conditions, unit_table);
+
+    let mut links: Vec<Link> = vec![];
+
+    for (loc, units_at_loc) in indexes.by_location.iter() {
+        for (unit_id, _, unit_model) in units_at_loc {
+            if &unit_model.owner != player_id {
+                continue;
+            }
+
+            let definition = unit_model.definition(unit_table);
+            let is_depot = definition.supply_production.is_some();
+
+            let mut reach = around(loc, map);
+
+            if definition.can_pick_up_supply_crates {
+                if let Ok(mobility) =
+                    get_units_mobility(map, unit_id, indexes, &visibility, conditions, unit_table)
+                {
+                    reach.extend(mobility);
+                }
+            } else if !is_depot && !definition.is_supply_crate {
+                continue;
+            }
+
+            links.push(Link {
+                loc: loc.clone(),
+                reach,
+                is_depot,
+            });
+        }
+    }
+
+    let mut connected: Vec<bool> = links.iter().map(|link| link.is_depot).collect();
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+
+        for (index, link) in links.iter().enumerate() {
+            if connected[index] {
+                continue;
+            }
+
+            let joins = links
+                .iter()
+                .zip(connected.iter())
+                .any(|(other, other_is_connected)| {
+                    *other_is_connected
+                        && (other.reach.contains(&link.loc) || link.reach.contains(&other.loc))
+                });
+
+            if joins {
+                connected[index] = true;
+                changed = true;
+            }
+        }
+    }
+
+    let reach: HashSet<Located<()>> = links
+        .into_iter()
+        .zip(connected)
+        .filter(|(_, is_connected)| *is_connected)
+        .flat_map(|(link, _)| link.reach)
+        .collect();
+
+    let mut in_supply: HashSet<UnitId> = HashSet::new();
+
+    for loc in reach.iter() {
+        if let Some(units_at_loc) = indexes.get_units_by_location(loc) {
+            for (unit_id, _, unit_model) in units_at_loc {
+                if &unit_model.owner != player_id {
+                    continue;
+                }
+
+                in_supply.insert(unit_id.clone());
+
+                // Passengers get their supplies through
+                // whatever is carrying them
+                if let Some(passengers) = indexes.by_transport.get(unit_id) {
+                    for (passenger_id, _) in passengers {
+                        in_supply.insert(passenger_id.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    Network { in_supply, reach }
+}
+
+fn around(loc: &Located<()>, map: &Map) -> HashSet<Located<()>> {
+    let mut tiles = HashSet::new();
+
+    tiles.insert(loc.clone());
+
+    if loc.x > 0 {
+        tiles.insert(located::unit(loc.x - 1, loc.y));
+    }
+    if loc.x + 1 < map.width {
+        tiles.insert(located::unit(loc.x + 1, loc.y));
+    }
+    if loc.y > 0 {
+        tiles.insert(located::unit(loc.x, loc.y - 1));
+    }
+    if loc.y + 1 < map.height {
+        tiles.insert(located::unit(loc.x, loc.y + 1));
+    }
+
+    tiles
+}
+
+#[cfg(test)]
+mod test_supply_line {
+    use crate::facing_direction::FacingDirection;
+    use crate::game::conditions::Conditions;
+    use crate::game::supply_line::network;
+    use crate::game::unit_index::Indexes;
+    use crate::id::Id;
+    use crate::map::Map;
+    use crate::team_color::TeamColor;
+    use crate::unit;
+    use crate::unit::definition::Table;
+    use crate::unit::{Place, Unit, UnitId};
+
+    fn red_unit(name: &str, unit: Unit, x: u16, y: u16) -> (UnitId, unit::Model) {
+        (
+            UnitId::test(name),
+            unit::Model::new(
+                unit,
+                &Id::test("red"),
+                Place::on_map(x, y, FacingDirection::Right),
+                &TeamColor::Red,
+            ),
+        )
+    }
+
+    #[test]
+    fn units_next_to_a_depot_are_in_supply() {
+        let indexes = Indexes::make(vec![
+            red_unit("depot", Unit::SupplyDepot, 0, 2),
+            red_unit("near", Unit::Infantry, 1, 2),
+            red_unit("far", Unit::Infantry, 5, 2),
+        ]);
+
+        let network = network(
+            &Id::test("red"),
+            &indexes,
+            &Map::grass_square(),
+            &Conditions::test(),
+            Table::bundled(),
+        );
+
+        assert!(network.in_supply.contains(&UnitId::test("near")));
+        assert!(!network.in_supply.contains(&UnitId::test("far")));
+    }
+
+    #[test]
+    fn trucks_carry_the_supply_line_further() {
+        let indexes = Indexes::make(vec![
+            red_unit("depot", Unit::SupplyDepot, 0, 2),
+            red_unit("truck", Unit::Truck, 3, 2),
+            red_unit("crate", Unit::SupplyCrate, 10, 2),
+            red_unit("near crate", Unit::Infantry, 11, 2),
+            red_unit("cut off", Unit::Infantry, 15, 15),
+        ]);
+
+        let network = network(
+            &Id::test("red"),
+            &indexes,
+            &Map::grass_square(),
+            &Conditions::test(),
+            Table::bundled(),
+        );
+
+        assert!(network.in_supply.contains(&UnitId::test("truck")));
+        assert!(network.in_supply.contains(&UnitId::test("crate")));
+        assert!(network.in_supply.contains(&UnitId::test("near crate")));
+        assert!(!network.in_supply.contains(&UnitId::test("cut off")));
+    }
+}
@@ -1,13 +1,18 @@
+use crate::direction::Direction;
 use crate::facing_direction::FacingDirection;
+use crate::game::calculate_player_visibility;
+use crate::game::conditions::Conditions;
+use crate::id::Id;
+use crate::located;
 use crate::located::Located;
 use crate::map::Map;
 use crate::path::Path;
 use crate::unit;
-use crate::unit::Place;
-use crate::unit::UnitId;
+use crate::unit::definition::Table;
+use crate::unit::{Place, Unit, UnitId};
 use serde::{Deserialize, Serialize};
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub mod by_id;
 pub mod by_location;
@@ -26,6 +31,10 @@ pub struct ConsumeBaselineSupplies {
     pub perished: bool,
 }
 
+pub struct TakeDamage {
+    pub destroyed: bool,
+}
+
 pub struct CargoAndTransportIds<'a> {
     pub cargo_id: &'a UnitId,
     pub transport_id: &'a UnitId,
@@ -54,20 +63,117 @@ impl Indexes {
     }
 
     pub fn perish(&mut self, unit_id: &UnitId) -> Result<(), String> {
-        self.by_id.delete(unit_id);
+        let facing_dir_loc = self.position_of_unit_or_transport(unit_id)?;
 
-        if self.by_transport.contains(unit_id) {
-            let facing_dir_loc = match self.position_of_unit_or_transport(unit_id) {
-                Ok(l) => l,
-                Err(msg) => {
-                    return Err(msg);
+        let place = match self.by_id.get(unit_id) {
+            Some(unit_model) => unit_model.place.clone(),
+            None => {
+                return Err("could not find unit that perished".to_string());
+            }
+        };
+
+        self.by_id.delete(unit_id);
+        self.delete_by_place(unit_id, &place);
+
+        // Any cargo the unit was carrying is left behind
+        // where the unit was
+        if let Some(cargo) = self.by_transport.get_mut(unit_id) {
+            let cargo_ids = cargo
+                .drain(..)
+                .map(|(cargo_id, _)| cargo_id)
+                .collect::<Vec<UnitId>>();
+
+            for cargo_id in cargo_ids {
+                if let Some(cargo_model) = self.by_id.get_mut(&cargo_id) {
+                    cargo_model.place = Place::OnMap(facing_dir_loc.clone());
+
+                    self.by_location.insert(
+                        &facing_dir_loc.to_unit(),
+                        cargo_id,
+                        facing_dir_loc.value.clone(),
+                        cargo_model.clone(),
+                    );
                 }
-            };
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn damage(&mut self, unit_id: &UnitId, damage: u8) -> Result<TakeDamage, String> {
+        let unit_model = match self.by_id.get_mut(unit_id) {
+            None => return Err("could not find unit to damage".to_string()),
+            Some(u) => u,
+        };
+
+        match unit_model.health.damaged(damage) {
+            Some(new_health) => {
+                unit_model.health = new_health;
+
+                Ok(TakeDamage { destroyed: false })
+            }
+            None => {
+                self.perish(unit_id)?;
 
-            for (_, cargo_model) in self.by_transport.get_mut(unit_id).unwrap() {
-                cargo_model.place = Place::OnMap(facing_dir_loc.clone());
+                Ok(TakeDamage { destroyed: true })
             }
         }
+    }
+
+    pub fn entrench(
+        &mut self,
+        unit_id: &UnitId,
+        against: &Direction,
+        cost: i16,
+    ) -> Result<ConsumeBaselineSupplies, String> {
+        match self.by_id.get_mut(unit_id) {
+            None => Err("could not find unit to entrench".to_string()),
+            Some(unit_model) => {
+                unit_model.entrenched_against = Some(against.clone());
+
+                self.consume_base_supplies(unit_id, cost)
+            }
+        }
+    }
+
+    pub fn capture(&mut self, unit_id: &UnitId, capturer_id: &UnitId) -> Result<(), String> {
+        let (owner, color) = match self.by_id.get(capturer_id) {
+            Some(capturer) => (capturer.owner.clone(), capturer.color.clone()),
+            None => return Err("could not find capturing unit".to_string()),
+        };
+
+        let cargo_ids = self
+            .by_id
+            .iter()
+            .filter_map(|(cargo_id, cargo_model)| match &cargo_model.place {
+                Place::InUnit(transport_id) if transport_id == unit_id => Some(cargo_id.clone()),
+                _ => None,
+            })
+            .collect::<Vec<UnitId>>();
+
+        if self.by_id.get(unit_id).is_none() {
+            return Err("could not find unit to capture".to_string());
+        }
+
+        // Anything the captured unit was carrying is
+        // captured along with it
+        for id in cargo_ids.iter().chain(std::iter::once(unit_id)) {
+            if let Some(unit_model) = self.by_id.get_mut(id) {
+                unit_model.owner = owner.clone();
+                unit_model.color = color.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn recover(&mut self, unit_id: &UnitId) -> Result<(), String> {
+        let unit_model = match self.by_id.get_mut(unit_id) {
+            None => return Err("could not find unit to recover".to_string()),
+            Some(u) => u,
+        };
+
+        unit_model.health = unit_model.health.recovered();
 
         Ok(())
     }
@@ -100,43 +206,120 @@ impl Indexes {
         units: CargoAndTransportIds<'_>,
         path: &Path,
         map: &Map,
+        conditions: &Conditions,
+        unit_table: &Table,
     ) -> Result<(), String> {
-        let cargo_unit_model = match self.by_id.get_mut(units.cargo_id) {
-            Some(c) => c,
+        let cargo_last_pos = match self.by_id.get(units.cargo_id) {
+            Some(cargo_unit_model) => match cargo_unit_model.place.clone() {
+                Place::OnMap(loc) => loc.to_unit(),
+                Place::InUnit(_) => {
+                    return Err("cargo not on map during pick up".to_string());
+                }
+            },
             None => {
                 return Err("could not find cargo unit during pick up".to_string());
             }
         };
 
-        let cargo_last_pos = match cargo_unit_model.place.clone() {
-            Place::OnMap(loc) => loc.to_unit(),
-            Place::InUnit(_) => {
-                return Err("cargo not on map during pick up".to_string());
-            }
-        };
+        self.travel_unit(units.transport_id, path, map, conditions, unit_table)?;
 
-        cargo_unit_model.place = Place::InUnit(units.transport_id.clone());
+        if let Some(cargo_unit_model) = self.by_id.get_mut(units.cargo_id) {
+            cargo_unit_model.place = Place::InUnit(units.transport_id.clone());
+        }
 
         self.by_location
             .delete_unit(&cargo_last_pos, units.cargo_id);
 
-        self.travel_unit(units.transport_id, path, map)?;
-
         Ok(())
     }
 
-    pub fn replenish(&mut self, unit_id: &UnitId, amount: i16) -> Result<(), String> {
+    pub fn replenish(
+        &mut self,
+        unit_id: &UnitId,
+        amount: i16,
+        unit_table: &Table,
+    ) -> Result<(), String> {
         let unit_model = match self.by_id.get_mut(unit_id) {
             None => return Err("could not get unit to replenish".to_string()),
             Some(u) => u,
         };
 
-        unit_model.supplies = cmp::min(amount, unit_model.unit.max_supplies());
+        unit_model.supplies = cmp::min(amount, unit_model.definition(unit_table).max_supplies);
+
+        Ok(())
+    }
+
+    // Unlike replenishing, this adds to whatever
+    // supplies the unit already has
+    pub fn add_supplies(
+        &mut self,
+        unit_id: &UnitId,
+        amount: i16,
+        unit_table: &Table,
+    ) -> Result<(), String> {
+        let unit_model = match self.by_id.get_mut(unit_id) {
+            None => return Err("could not get unit to add supplies to".to_string()),
+            Some(u) => u,
+        };
+
+        unit_model.supplies = cmp::min(
+            unit_model.supplies.saturating_add(amount),
+            unit_model.definition(unit_table).max_supplies,
+        );
+
+        Ok(())
+    }
+
+    // Sends a depot's stockpile out as a new supply
+    // crate on the depot's tile
+    pub fn produce_supply_crate(
+        &mut self,
+        depot_id: &UnitId,
+        crate_id: &UnitId,
+        unit_table: &Table,
+    ) -> Result<(), String> {
+        let depot_model = match self.by_id.get_mut(depot_id) {
+            None => return Err("could not get supply depot".to_string()),
+            Some(u) => u,
+        };
+
+        let loc = match &depot_model.place {
+            Place::OnMap(loc) => loc.clone(),
+            Place::InUnit(_) => return Err("supply depot was not on the map".to_string()),
+        };
+
+        let mut crate_model = unit::Model::new(
+            Unit::SupplyCrate,
+            &depot_model.owner,
+            Place::OnMap(loc.clone()),
+            &depot_model.color,
+        )
+        .with_table(unit_table);
+
+        crate_model.supplies = cmp::min(
+            depot_model.supplies,
+            crate_model.definition(unit_table).max_supplies,
+        );
+        depot_model.supplies -= crate_model.supplies;
+
+        self.by_location.insert(
+            &loc.to_unit(),
+            crate_id.clone(),
+            loc.value,
+            crate_model.clone(),
+        );
+        self.by_id.units.insert(crate_id.clone(), crate_model);
+        self.by_player = by_player::make(&self.by_id);
 
         Ok(())
     }
 
-    pub fn deplete_supply_crate(&mut self, unit_id: &UnitId, amount: i16) -> Result<(), String> {
+    pub fn deplete_supply_crate(
+        &mut self,
+        unit_id: &UnitId,
+        amount: i16,
+        unit_table: &Table,
+    ) -> Result<(), String> {
         let unit_model = match self.by_id.get_mut(unit_id) {
             None => {
                 return Err("could not get unit to deplete supplies".to_string());
@@ -146,7 +329,7 @@ impl Indexes {
 
         let place = unit_model.place.clone();
 
-        if !unit_model.unit.is_supply_crate() {
+        if !unit_model.definition(unit_table).is_supply_crate {
             return Err("trying to deplete supplies of non-supply crate unit".to_string());
         }
 
@@ -156,11 +339,127 @@ impl Indexes {
             self.by_id.delete(unit_id);
 
             self.delete_by_place(unit_id, &place);
+        } else {
+            unit_model.supplies = new_supply_level;
         }
 
         Ok(())
     }
 
+    // The supply crates loaded in the given transport
+    pub fn carried_supply_crates(&self, transport_id: &UnitId, unit_table: &Table) -> Vec<UnitId> {
+        let mut crate_ids = self
+            .by_id
+            .iter()
+            .filter(|(_, unit_model)| {
+                unit_model.definition(unit_table).is_supply_crate
+                    && matches!(&unit_model.place, Place::InUnit(id) if id == transport_id)
+            })
+            .map(|(crate_id, _)| crate_id.clone())
+            .collect::<Vec<UnitId>>();
+
+        crate_ids.sort();
+
+        crate_ids
+    }
+
+    // Moves supplies out of the crates one transport carries
+    // into the crates another one carries, as far as the
+    // receiving crates have room
+    pub fn transfer_supplies(
+        &mut self,
+        from_transport_id: &UnitId,
+        to_transport_id: &UnitId,
+        amount: i16,
+        unit_table: &Table,
+    ) -> Result<(), String> {
+        let giving_crates = self.carried_supply_crates(from_transport_id, unit_table);
+        let receiving_crates = self.carried_supply_crates(to_transport_id, unit_table);
+
+        if giving_crates.is_empty() || receiving_crates.is_empty() {
+            return Err("both transports need a supply crate to transfer supplies".to_string());
+        }
+
+        let mut remaining = amount;
+
+        for receiving_id in receiving_crates.iter() {
+            for giving_id in giving_crates.iter() {
+                let room = match self.by_id.get(receiving_id) {
+                    Some(crate_model) => {
+                        crate_model.definition(unit_table).max_supplies - crate_model.supplies
+                    }
+                    None => 0,
+                };
+
+                // Emptied crates are gone by now
+                let available = match self.by_id.get(giving_id) {
+                    Some(crate_model) => crate_model.supplies,
+                    None => 0,
+                };
+
+                let moved = cmp::min(remaining, cmp::min(room, available));
+
+                if moved > 0 {
+                    self.add_supplies(receiving_id, moved, unit_table)?;
+                    self.deplete_supply_crate(giving_id, moved, unit_table)?;
+
+                    remaining -= moved;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Leaves part of a carried crate behind as a new
+    // crate on its transport's tile
+    pub fn split_crate(
+        &mut self,
+        crate_id: &UnitId,
+        new_crate_id: &UnitId,
+        amount: i16,
+        unit_table: &Table,
+    ) -> Result<(), String> {
+        let loc = self.position_of_unit_or_transport(crate_id)?;
+
+        let crate_model = match self.by_id.get_mut(crate_id) {
+            None => return Err("could not get supply crate to split".to_string()),
+            Some(u) => u,
+        };
+
+        if !crate_model.definition(unit_table).is_supply_crate || crate_model.place.is_on_map() {
+            return Err("only carried supply crates can be split".to_string());
+        }
+
+        if amount <= 0 || crate_model.supplies <= amount {
+            return Err("supply crate does not have enough to split".to_string());
+        }
+
+        let mut new_crate_model = unit::Model::new(
+            Unit::SupplyCrate,
+            &crate_model.owner,
+            Place::OnMap(loc.clone()),
+            &crate_model.color,
+        )
+        .with_table(unit_table);
+
+        new_crate_model.supplies = amount;
+        crate_model.supplies -= amount;
+
+        self.by_location.insert(
+            &loc.to_unit(),
+            new_crate_id.clone(),
+            loc.value,
+            new_crate_model.clone(),
+        );
+        self.by_id
+            .units
+            .insert(new_crate_id.clone(), new_crate_model);
+        self.by_player = by_player::make(&self.by_id);
+
+        Ok(())
+    }
+
     pub fn delete_by_place(&mut self, unit_id: &UnitId, place: &Place) {
         match place {
             Place::OnMap(loc) => {
@@ -199,6 +498,8 @@ impl Indexes {
         units: CargoAndTransportIds<'_>,
         path: &Path,
         map: &Map,
+        conditions: &Conditions,
+        unit_table: &Table,
     ) -> Result<(), String> {
         match self.by_id.get_mut(units.cargo_id) {
             None => Err("could not find cargo unit when loading into".to_string()),
@@ -208,7 +509,9 @@ impl Indexes {
                     Place::InUnit(_) => return Err("cargo unit was not on the map".to_string()),
                 };
 
-                cargo_model.supplies -= path.supply_cost(map, &cargo_model.unit);
+                cargo_model.supplies -=
+                    path.supply_cost(map, cargo_model.definition(unit_table), conditions);
+                cargo_model.entrenched_against = None;
                 cargo_model.place = Place::InUnit(units.transport_id.clone());
 
                 self.by_location
@@ -219,7 +522,83 @@ impl Indexes {
         }
     }
 
-    pub fn travel_unit(&mut self, unit_id: &UnitId, path: &Path, map: &Map) -> Result<(), String> {
+    // Tiles next to, or occupied by, enemies of the given player
+    // that the player can see. Moving into one of these tiles
+    // ends a unit's movement
+    pub fn zone_of_control(
+        &self,
+        player_id: &Id,
+        visibility: &HashSet<Located<()>>,
+        unit_table: &Table,
+    ) -> HashSet<Located<()>> {
+        let mut zone = HashSet::new();
+
+        for unit_model in self.by_id.values() {
+            if &unit_model.owner == player_id
+                || !unit_model.definition(unit_table).exerts_zone_of_control
+            {
+                continue;
+            }
+
+            if let Place::OnMap(loc) = &unit_model.place {
+                let enemy_loc = loc.to_unit();
+
+                if !visibility.contains(&enemy_loc) {
+                    continue;
+                }
+
+                for dir in [
+                    Direction::North,
+                    Direction::South,
+                    Direction::East,
+                    Direction::West,
+                ] {
+                    let mut x = enemy_loc.x;
+                    let mut y = enemy_loc.y;
+
+                    dir.adjust_coord(&mut x, &mut y);
+
+                    zone.insert(located::unit(x, y));
+                }
+
+                zone.insert(enemy_loc);
+            }
+        }
+
+        zone
+    }
+
+    // The path a unit can actually travel, stopping at the first
+    // tile inside an enemy zone of control
+    pub fn zone_of_control_path(
+        &self,
+        unit_id: &UnitId,
+        path: &Path,
+        map: &Map,
+        conditions: &Conditions,
+        unit_table: &Table,
+    ) -> Result<Path, String> {
+        let owner = match self.by_id.get(unit_id) {
+            Some(unit_model) => unit_model.owner.clone(),
+            None => {
+                return Err("could not get unit when checking zone of control".to_string());
+            }
+        };
+
+        let visibility =
+            calculate_player_visibility(&owner, map, &self.by_id, conditions, unit_table);
+
+        Ok(path.halted_by(&self.zone_of_control(&owner, &visibility, unit_table)))
+    }
+
+    pub fn travel_unit(
+        &mut self,
+        unit_id: &UnitId,
+        path: &Path,
+        map: &Map,
+        conditions: &Conditions,
+        unit_table: &Table,
+    ) -> Result<(), String> {
         let loc = match path.last_pos() {
             None => {
                 return Ok(());
@@ -236,7 +615,9 @@ impl Indexes {
 
         match self.by_id.get_mut(unit_id) {
             Some(unit_model) => {
-                unit_model.supplies -= path.supply_cost(map, &unit_model.unit);
+                unit_model.supplies -=
+                    path.supply_cost(map, unit_model.definition(unit_table), conditions);
+                unit_model.entrenched_against = None;
 
                 let prev_place = unit_model.place.clone();
 
@@ -273,6 +654,28 @@ impl Indexes {
         }
     }
 
+    // Whether the waiting unit is on the tile the moving unit's
+    // path ends at, or the tile it is already on if it stays put
+    pub fn meets_at_path_end(
+        &self,
+        moving_unit_id: &UnitId,
+        waiting_unit_id: &UnitId,
+        path: &Path,
+    ) -> bool {
+        let meeting_loc = match path.last_pos() {
+            Some(loc) => loc,
+            None => match self.position_of_unit_or_transport(moving_unit_id) {
+                Ok(loc) => loc.to_unit(),
+                Err(_) => return false,
+            },
+        };
+
+        match self.position_of_unit_or_transport(waiting_unit_id) {
+            Ok(waiting_loc) => waiting_loc.is_same_pos_as(&meeting_loc),
+            Err(_) => false,
+        }
+    }
+
     pub fn get_units_by_location(
         &self,
         key: &Located<()>,
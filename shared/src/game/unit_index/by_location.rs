@@ -4,6 +4,7 @@ use crate::id::Id;
 use crate::located::Located;
 use crate::path::Path;
 use crate::unit;
+use crate::unit::definition::Table;
 use crate::unit::{Place, UnitId};
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::Iter;
@@ -42,6 +43,7 @@ impl Index {
         &self,
         player_id: Id,
         path: &Path,
+        unit_table: &Table,
     ) -> Option<Located<Vec<(UnitId, unit::Model)>>> {
         let origin = match path.first_pos() {
             None => {
@@ -57,7 +59,9 @@ impl Index {
                 let non_supply_crate_units: Vec<(UnitId, unit::Model)> = units_at_loc
                     .iter()
                     .filter_map(|(unit_id, _, unit_model)| {
-                        if unit_model.unit.is_supply_crate() || unit_model.owner == player_id {
+                        if unit_model.definition(unit_table).is_supply_crate
+                            || unit_model.owner == player_id
+                        {
                             None
                         } else {
                             Some((unit_id.clone(), unit_model.clone()))
@@ -106,16 +110,17 @@ impl Index {
         &'a self,
         viewer_id: &'a Id,
         loc: &Located<()>,
+        unit_table: &Table,
     ) -> Option<Vec<UnitId>> {
         self.0.get(loc).and_then(|units| {
             let filtered_units = units
                 .iter()
                 .filter_map(|(unit_id, _, unit_model)| {
                     let has_less_than_max_supplies =
-                        unit_model.supplies < unit_model.unit.max_supplies();
+                        unit_model.supplies < unit_model.definition(unit_table).max_supplies;
 
                     if unit_model.owner == *viewer_id
-                        && unit_model.unit.replenishable()
+                        && unit_model.definition(unit_table).replenishable
                         && has_less_than_max_supplies
                     {
                         Some(unit_id.clone())
@@ -167,6 +172,7 @@ mod test_by_location_index {
     use crate::path::Path;
     use crate::team_color::TeamColor;
     use crate::unit;
+    use crate::unit::definition::Table;
     use crate::unit::{Place, Unit, UnitId};
     use pretty_assertions::assert_eq;
     use std::collections::HashMap;
@@ -225,6 +231,7 @@ mod test_by_location_index {
                     Direction::East,
                 ],
             ),
+            Table::bundled(),
         );
 
         let want = Some(Located {
@@ -0,0 +1,895 @@
+use crate::game::action::Action;
+use crate::game::conditions::Conditions;
+use crate::game::replenishment::Replenishment;
+use crate::game::unit_index::Indexes;
+use crate::game::{action, mobility};
+use crate::id::Id;
+use crate::located::Located;
+use crate::map::Map;
+use crate::path::Path;
+use crate::unit::definition::Table;
+use crate::unit::{Place, UnitId};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub enum Invalid {
+    UnitNotFound(UnitId),
+    NotOwnedByPlayer(UnitId),
+    PathDoesNotStartAtUnit(UnitId),
+    PathNotContiguous(UnitId),
+    PathOutOfReach(UnitId),
+    CannotCarry {
+        transport_id: UnitId,
+        cargo_id: UnitId,
+    },
+    OverCapacity(UnitId),
+    CarryOutOfReach {
+        transport_id: UnitId,
+        cargo_id: UnitId,
+    },
+    ReplenishmentMismatch(UnitId),
+    // Each unit can only be given one order a turn
+    MoreThanOneAction(UnitId),
+    CannotAttack(UnitId),
+    CannotEntrench(UnitId),
+    CannotBombard(UnitId),
+    CannotBuild(UnitId),
+    CannotDemolish(UnitId),
+    CannotTransferSupplies(UnitId),
+    CannotSplitCrate(UnitId),
+    CannotMergeCrates(UnitId),
+    CouldNotValidate(String),
+}
+
+impl fmt::Display for Invalid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Invalid::UnitNotFound(unit_id) => {
+                write!(f, "unit {} does not exist", unit_id.to_string())
+            }
+            Invalid::NotOwnedByPlayer(unit_id) => {
+                write!(f, "unit {} does not belong to player", unit_id.to_string())
+            }
+            Invalid::PathDoesNotStartAtUnit(unit_id) => {
+                write!(
+                    f,
+                    "path of unit {} does not start where it is",
+                    unit_id.to_string()
+                )
+            }
+            Invalid::PathNotContiguous(unit_id) => {
+                write!(f, "path of unit {} skips over tiles", unit_id.to_string())
+            }
+            Invalid::PathOutOfReach(unit_id) => {
+                write!(
+                    f,
+                    "path of unit {} goes beyond its mobility",
+                    unit_id.to_string()
+                )
+            }
+            Invalid::CannotCarry {
+                transport_id,
+                cargo_id,
+            } => write!(
+                f,
+                "unit {} cannot carry unit {}",
+                transport_id.to_string(),
+                cargo_id.to_string()
+            ),
+            Invalid::CarryOutOfReach {
+                transport_id,
+                cargo_id,
+            } => write!(
+                f,
+                "unit {} and unit {} do not meet at the end of the path",
+                transport_id.to_string(),
+                cargo_id.to_string()
+            ),
+            Invalid::OverCapacity(transport_id) => {
+                write!(f, "unit {} would carry too much", transport_id.to_string())
+            }
+            Invalid::ReplenishmentMismatch(unit_id) => write!(
+                f,
+                "replenishment by unit {} does not match the supplies available",
+                unit_id.to_string()
+            ),
+            Invalid::MoreThanOneAction(unit_id) => {
+                write!(
+                    f,
+                    "unit {} was given more than one order",
+                    unit_id.to_string()
+                )
+            }
+            Invalid::CannotAttack(unit_id) => {
+                write!(f, "unit {} cannot attack", unit_id.to_string())
+            }
+            Invalid::CannotEntrench(unit_id) => {
+                write!(f, "unit {} cannot entrench", unit_id.to_string())
+            }
+            Invalid::CannotBombard(unit_id) => {
+                write!(f, "unit {} cannot bombard that tile", unit_id.to_string())
+            }
+            Invalid::CannotBuild(unit_id) => {
+                write!(f, "unit {} cannot build there", unit_id.to_string())
+            }
+            Invalid::CannotDemolish(unit_id) => {
+                write!(f, "unit {} cannot demolish that tile", unit_id.to_string())
+            }
+            Invalid::CannotTransferSupplies(unit_id) => write!(
+                f,
+                "unit {} cannot transfer supplies to that unit",
+                unit_id.to_string()
+            ),
+            Invalid::CannotSplitCrate(unit_id) => {
+                write!(f, "supply crate {} cannot be split", unit_id.to_string())
+            }
+            Invalid::CannotMergeCrates(unit_id) => {
+                write!(
+                    f,
+                    "supply crate {} cannot be merged into that crate",
+                    unit_id.to_string()
+                )
+            }
+            Invalid::CouldNotValidate(err_msg) => write!(f, "{}", err_msg),
+        }
+    }
+}
+
+impl Invalid {
+    // The unit the problem is with, if it is with any one unit
+    pub fn unit_id(&self) -> Option<&UnitId> {
+        match self {
+            Invalid::UnitNotFound(unit_id) => Some(unit_id),
+            Invalid::NotOwnedByPlayer(unit_id) => Some(unit_id),
+            Invalid::PathDoesNotStartAtUnit(unit_id) => Some(unit_id),
+            Invalid::PathNotContiguous(unit_id) => Some(unit_id),
+            Invalid::PathOutOfReach(unit_id) => Some(unit_id),
+            Invalid::CannotCarry { cargo_id, .. } => Some(cargo_id),
+            Invalid::CarryOutOfReach { cargo_id, .. } => Some(cargo_id),
+            Invalid::OverCapacity(transport_id) => Some(transport_id),
+            Invalid::ReplenishmentMismatch(unit_id) => Some(unit_id),
+            Invalid::MoreThanOneAction(unit_id) => Some(unit_id),
+            Invalid::CannotAttack(unit_id) => Some(unit_id),
+            Invalid::CannotEntrench(unit_id) => Some(unit_id),
+            Invalid::CannotBombard(unit_id) => Some(unit_id),
+            Invalid::CannotBuild(unit_id) => Some(unit_id),
+            Invalid::CannotDemolish(unit_id) => Some(unit_id),
+            Invalid::CannotTransferSupplies(unit_id) => Some(unit_id),
+            Invalid::CannotSplitCrate(unit_id) => Some(unit_id),
+            Invalid::CannotMergeCrates(unit_id) => Some(unit_id),
+            Invalid::CouldNotValidate(_) => None,
+        }
+    }
+}
+
+// Check that the actions a player submitted are ones they
+// could have legitimately made from the client
+pub fn validate_turn(
+    player_id: &Id,
+    actions: &[Action],
+    visibility: &HashSet<Located<()>>,
+    indexes: &Indexes,
+    map: &Map,
+    conditions: &Conditions,
+    unit_table: &Table,
+) -> Result<(), Vec<Invalid>> {
+    let mut actions = actions.to_vec();
+    action::unbatch(&mut actions);
+
+    let mut invalids = Vec::new();
+
+    // Orders are carried out in a random order, so a unit
+    // with more than one would end up doing whichever
+    // happened to come first
+    let mut units_with_actions: HashSet<&UnitId> = HashSet::new();
+
+    for action in actions.iter() {
+        if let Some(unit_id) = action.unit_id() {
+            if !units_with_actions.insert(unit_id) {
+                invalids.push(Invalid::MoreThanOneAction(unit_id.clone()));
+            }
+        }
+    }
+
+    for action in actions.iter() {
+        if let Err(mut action_invalids) = validate_action(
+            player_id, action, visibility, indexes, map, conditions, unit_table,
+        ) {
+            invalids.append(&mut action_invalids);
+        }
+    }
+
+    if let Err(mut capacity_invalids) = validate_capacity(&actions, indexes, unit_table) {
+        invalids.append(&mut capacity_invalids);
+    }
+
+    if invalids.is_empty() {
+        Ok(())
+    } else {
+        Err(invalids)
+    }
+}
+
+fn validate_action(
+    player_id: &Id,
+    action: &Action,
+    visibility: &HashSet<Located<()>>,
+    indexes: &Indexes,
+    map: &Map,
+    conditions: &Conditions,
+    unit_table: &Table,
+) -> Result<(), Vec<Invalid>> {
+    let unit_id = match action.unit_id() {
+        Some(unit_id) => unit_id,
+        None => return Ok(()),
+    };
+
+    let unit_model = match indexes.by_id.get(unit_id) {
+        Some(unit_model) => unit_model,
+        None => return Err(vec![Invalid::UnitNotFound(unit_id.clone())]),
+    };
+
+    if &unit_model.owner != player_id {
+        return Err(vec![Invalid::NotOwnedByPlayer(unit_id.clone())]);
+    }
+
+    let mut invalids = Vec::new();
+
+    if let Some(path) = action.path() {
+        if let Err(invalid) = validate_path(
+            unit_id, path, visibility, indexes, map, conditions, unit_table,
+        ) {
+            invalids.push(invalid);
+        }
+    }
+
+    match action {
+        Action::LoadInto {
+            load_into, path, ..
+        } => {
+            if let Err(invalid) = validate_carry(
+                player_id, load_into, unit_id, unit_id, path, indexes, unit_table,
+            ) {
+                invalids.push(invalid);
+            }
+        }
+        Action::PickUp { cargo_id, path, .. } => {
+            if let Err(invalid) = validate_carry(
+                player_id, unit_id, cargo_id, unit_id, path, indexes, unit_table,
+            ) {
+                invalids.push(invalid);
+            }
+        }
+        Action::Replenish {
+            units,
+            depleted_supply_crates,
+            path,
+            ..
+        } => {
+            if let Err(invalid) = validate_replenishment(
+                player_id,
+                unit_id,
+                path,
+                units,
+                depleted_supply_crates,
+                indexes,
+                unit_table,
+            ) {
+                invalids.push(invalid);
+            }
+        }
+        Action::Travel { .. } => {}
+        Action::DropOff { .. } => {}
+        Action::Attack(_) => {
+            if !unit_model.definition(unit_table).can_attack {
+                invalids.push(Invalid::CannotAttack(unit_id.clone()));
+            }
+        }
+        Action::Bombard { target, .. } => {
+            let in_range = match (
+                unit_model.definition(unit_table).bombard_range,
+                &unit_model.place,
+            ) {
+                (Some(range), Place::OnMap(loc)) => loc.distance_from(target) <= range,
+                _ => false,
+            };
+
+            if !in_range {
+                invalids.push(Invalid::CannotBombard(unit_id.clone()));
+            }
+        }
+        Action::Build {
+            target, structure, ..
+        } => {
+            let can_build = match (
+                unit_model.definition(unit_table).build_supply_cost,
+                &unit_model.place,
+            ) {
+                (Some(_), Place::OnMap(loc)) => {
+                    loc.distance_from(target) <= 1 && map.get_tile(target).can_build(structure)
+                }
+                _ => false,
+            };
+
+            if !can_build {
+                invalids.push(Invalid::CannotBuild(unit_id.clone()));
+            }
+        }
+        Action::Demolish { target, .. } => {
+            let can_demolish = match (
+                unit_model.definition(unit_table).build_supply_cost,
+                &unit_model.place,
+                map.structures.get(target),
+            ) {
+                (Some(_), Place::OnMap(loc), Some(structure)) => {
+                    // Units on a bridge would be left standing in the water
+                    let strands_units = structure.replaced.mobility_cost().is_infinite()
+                        && indexes
+                            .by_location
+                            .get(target)
+                            .map(|units| !units.is_empty())
+                            .unwrap_or(false);
+
+                    loc.distance_from(target) <= 1
+                        && structure.owner.as_ref() != Some(player_id)
+                        && !strands_units
+                }
+                _ => false,
+            };
+
+            if !can_demolish {
+                invalids.push(Invalid::CannotDemolish(unit_id.clone()));
+            }
+        }
+        Action::TransferSupplies {
+            receiving_unit_id,
+            amount,
+            ..
+        } => {
+            let can_transfer = match (
+                indexes.by_id.get(receiving_unit_id),
+                indexes.position_of_unit_or_transport(unit_id),
+                indexes.position_of_unit_or_transport(receiving_unit_id),
+            ) {
+                (Some(receiver), Ok(loc), Ok(receiver_loc)) => {
+                    *amount > 0
+                        && unit_id != receiving_unit_id
+                        && &receiver.owner == player_id
+                        && unit_model.definition(unit_table).can_pick_up_supply_crates
+                        && receiver.definition(unit_table).can_pick_up_supply_crates
+                        && loc.distance_from(&receiver_loc) <= 1
+                }
+                _ => false,
+            };
+
+            if !can_transfer {
+                invalids.push(Invalid::CannotTransferSupplies(unit_id.clone()));
+            }
+        }
+        Action::SplitCrate { amount, .. } => {
+            let can_split = unit_model.definition(unit_table).is_supply_crate
+                && !unit_model.place.is_on_map()
+                && *amount > 0
+                && *amount < unit_model.supplies;
+
+            if !can_split {
+                invalids.push(Invalid::CannotSplitCrate(unit_id.clone()));
+            }
+        }
+        Action::MergeCrates { into_crate_id, .. } => {
+            let can_merge = match (indexes.by_id.get(into_crate_id), &unit_model.place) {
+                (Some(into_crate), Place::OnMap(loc)) => {
+                    unit_id != into_crate_id
+                        && &into_crate.owner == player_id
+                        && unit_model.definition(unit_table).is_supply_crate
+                        && into_crate.definition(unit_table).is_supply_crate
+                        && into_crate
+                            .place
+                            .to_map_loc()
+                            .map(|into_loc| into_loc.is_same_pos_as(loc))
+                            .unwrap_or(false)
+                }
+                _ => false,
+            };
+
+            if !can_merge {
+                invalids.push(Invalid::CannotMergeCrates(unit_id.clone()));
+            }
+        }
+        Action::Entrench { .. } => {
+            if unit_model
+                .definition(unit_table)
+                .entrench_supply_cost
+                .is_none()
+            {
+                invalids.push(Invalid::CannotEntrench(unit_id.clone()));
+            }
+        }
+        Action::Batch(_) => {}
+    }
+
+    if invalids.is_empty() {
+        Ok(())
+    } else {
+        Err(invalids)
+    }
+}
+
+fn validate_path(
+    unit_id: &UnitId,
+    path: &Path,
+    visibility: &HashSet<Located<()>>,
+    indexes: &Indexes,
+    map: &Map,
+    conditions: &Conditions,
+    unit_table: &Table,
+) -> Result<(), Invalid> {
+    let steps = path.to_loc_directions();
+
+    let origin = match steps.first() {
+        Some(origin) => origin,
+        None => return Ok(()),
+    };
+
+    let unit_loc = indexes
+        .position_of_unit_or_transport(unit_id)
+        .map_err(Invalid::CouldNotValidate)?;
+
+    if !origin.is_same_pos_as(&unit_loc) {
+        return Err(Invalid::PathDoesNotStartAtUnit(unit_id.clone()));
+    }
+
+    for pair in steps.windows(2) {
+        if pair[0].distance_from(&pair[1]) != 1 {
+            return Err(Invalid::PathNotContiguous(unit_id.clone()));
+        }
+    }
+
+    let unit_mobility =
+        mobility::get_units_mobility(map, unit_id, indexes, visibility, conditions, unit_table)
+            .map_err(Invalid::CouldNotValidate)?;
+
+    let unit_model = indexes
+        .by_id
+        .get(unit_id)
+        .ok_or_else(|| Invalid::UnitNotFound(unit_id.clone()))?;
+
+    let mut mobility_cost = 0.0;
+
+    for step in steps.iter().skip(1) {
+        let step_loc = step.to_unit();
+
+        if !unit_mobility.contains(&step_loc) {
+            return Err(Invalid::PathOutOfReach(unit_id.clone()));
+        }
+
+        mobility_cost += unit_model
+            .definition(unit_table)
+            .mobility_cost_at(map, &step_loc, conditions);
+    }
+
+    // Paths can double back on themselves, so every tile being
+    // in range is not enough on its own
+    if mobility_cost > unit_model.mobility_budget(unit_table) {
+        return Err(Invalid::PathOutOfReach(unit_id.clone()));
+    }
+
+    Ok(())
+}
+
+// Both units have to belong to the player, and the one that
+// is not moving has to be waiting where the other one's
+// path ends
+fn validate_carry(
+    player_id: &Id,
+    transport_id: &UnitId,
+    cargo_id: &UnitId,
+    moving_unit_id: &UnitId,
+    path: &Path,
+    indexes: &Indexes,
+    unit_table: &Table,
+) -> Result<(), Invalid> {
+    let transport = indexes
+        .by_id
+        .get(transport_id)
+        .ok_or_else(|| Invalid::UnitNotFound(transport_id.clone()))?;
+
+    let cargo = indexes
+        .by_id
+        .get(cargo_id)
+        .ok_or_else(|| Invalid::UnitNotFound(cargo_id.clone()))?;
+
+    if &transport.owner != player_id {
+        return Err(Invalid::NotOwnedByPlayer(transport_id.clone()));
+    }
+
+    if &cargo.owner != player_id {
+        return Err(Invalid::NotOwnedByPlayer(cargo_id.clone()));
+    }
+
+    if !transport
+        .definition(unit_table)
+        .can_carry
+        .contains(&cargo.unit)
+    {
+        return Err(Invalid::CannotCarry {
+            transport_id: transport_id.clone(),
+            cargo_id: cargo_id.clone(),
+        });
+    }
+
+    let waiting_unit_id = if moving_unit_id == transport_id {
+        cargo_id
+    } else {
+        transport_id
+    };
+
+    if !indexes.meets_at_path_end(moving_unit_id, waiting_unit_id, path) {
+        return Err(Invalid::CarryOutOfReach {
+            transport_id: transport_id.clone(),
+            cargo_id: cargo_id.clone(),
+        });
+    }
+
+    Ok(())
+}
+
+// Transports should not end the turn carrying more units
+// than they have room for
+fn validate_capacity(
+    actions: &[Action],
+    indexes: &Indexes,
+    unit_table: &Table,
+) -> Result<(), Vec<Invalid>> {
+    let mut loads: HashMap<UnitId, i32> = HashMap::new();
+
+    for unit_model in indexes.by_id.values() {
+        if let Place::InUnit(transport_id) = &unit_model.place {
+            *loads.entry(transport_id.clone()).or_insert(0) += 1;
+        }
+    }
+
+    for action in actions {
+        match action {
+            Action::LoadInto { load_into, .. } => {
+                *loads.entry(load_into.clone()).or_insert(0) += 1;
+            }
+            Action::PickUp { unit_id, .. } => {
+                *loads.entry(unit_id.clone()).or_insert(0) += 1;
+            }
+            Action::DropOff { cargo_id } => {
+                if let Some(Place::InUnit(transport_id)) =
+                    indexes.by_id.get(cargo_id).map(|cargo| &cargo.place)
+                {
+                    *loads.entry(transport_id.clone()).or_insert(0) -= 1;
+                }
+            }
+            Action::Travel {
+                dismounted_from: Some(transport_id),
+                ..
+            } => {
+                *loads.entry(transport_id.clone()).or_insert(0) -= 1;
+            }
+            _ => {}
+        }
+    }
+
+    let mut invalids = loads
+        .into_iter()
+        .filter_map(|(transport_id, load)| {
+            let capacity = indexes
+                .by_id
+                .get(&transport_id)
+                .map(|transport| transport.definition(unit_table).carrying_capacity)
+                .unwrap_or(0);
+
+            if load > capacity as i32 {
+                Some(Invalid::OverCapacity(transport_id))
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<Invalid>>();
+
+    if invalids.is_empty() {
+        Ok(())
+    } else {
+        invalids.sort_by_key(|invalid| invalid.to_string());
+        Err(invalids)
+    }
+}
+
+fn validate_replenishment(
+    player_id: &Id,
+    unit_id: &UnitId,
+    path: &Path,
+    units: &[(UnitId, i16)],
+    depleted_supply_crates: &[(UnitId, i16)],
+    indexes: &Indexes,
+    unit_table: &Table,
+) -> Result<(), Invalid> {
+    let replenishment_pos = match path.last_pos() {
+        Some(pos) => pos,
+        None => return Err(Invalid::ReplenishmentMismatch(unit_id.clone())),
+    };
+
+    let replenishment =
+        Replenishment::calculate(player_id, unit_id, replenishment_pos, indexes, unit_table)
+            .map_err(|_| Invalid::ReplenishmentMismatch(unit_id.clone()))?;
+
+    let sorted = |amounts: &[(UnitId, i16)]| {
+        let mut amounts = amounts.to_vec();
+        amounts.sort();
+        amounts
+    };
+
+    if sorted(units) != sorted(&replenishment.replenished_units)
+        || sorted(depleted_supply_crates) != sorted(&replenishment.depleted_supply_crates)
+    {
+        return Err(Invalid::ReplenishmentMismatch(unit_id.clone()));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_validation {
+    use crate::direction::Direction;
+    use crate::facing_direction::FacingDirection;
+    use crate::game::action::{Action, Attack};
+    use crate::game::conditions::Conditions;
+    use crate::game::unit_index::Indexes;
+    use crate::game::validation::{validate_turn, Invalid};
+    use crate::id::Id;
+    use crate::located;
+    use crate::map::Map;
+    use crate::path::Path;
+    use crate::team_color::TeamColor;
+    use crate::unit;
+    use crate::unit::definition::Table;
+    use crate::unit::{Place, Unit, UnitId};
+    use pretty_assertions::assert_eq;
+    use std::collections::HashSet;
+
+    fn travel(unit_id: &UnitId, dirs: Vec<Direction>) -> Action {
+        Action::Travel {
+            unit_id: unit_id.clone(),
+            path: Path::from_directions_test_only(&located::unit(2, 2), &dirs),
+            dismounted_from: None,
+        }
+    }
+
+    #[test]
+    fn invalid_travel() {
+        let red_player = Id::from_string("red".to_string(), true).unwrap();
+        let blue_player = Id::from_string("blue".to_string(), true).unwrap();
+
+        let infantry_id = UnitId::test("infantry");
+
+        let indexes = Indexes::make(vec![(
+            infantry_id.clone(),
+            unit::Model::new(
+                Unit::Infantry,
+                &red_player,
+                Place::on_map(2, 2, FacingDirection::Right),
+                &TeamColor::Red,
+            ),
+        )]);
+
+        let map = Map::grass_square();
+        let visibility = HashSet::new();
+
+        let valid = vec![travel(&infantry_id, vec![Direction::East, Direction::East])];
+
+        assert_eq!(
+            Ok(()),
+            validate_turn(
+                &red_player,
+                &valid,
+                &visibility,
+                &indexes,
+                &map,
+                &Conditions::test(),
+                Table::bundled()
+            )
+        );
+
+        assert_eq!(
+            Err(vec![Invalid::NotOwnedByPlayer(infantry_id.clone())]),
+            validate_turn(
+                &blue_player,
+                &valid,
+                &visibility,
+                &indexes,
+                &map,
+                &Conditions::test(),
+                Table::bundled()
+            )
+        );
+
+        let too_far = vec![travel(
+            &infantry_id,
+            vec![Direction::East, Direction::East, Direction::East],
+        )];
+
+        assert_eq!(
+            Err(vec![Invalid::PathOutOfReach(infantry_id.clone())]),
+            validate_turn(
+                &red_player,
+                &too_far,
+                &visibility,
+                &indexes,
+                &map,
+                &Conditions::test(),
+                Table::bundled()
+            )
+        );
+
+        let doubling_back = vec![travel(
+            &infantry_id,
+            vec![
+                Direction::East,
+                Direction::West,
+                Direction::East,
+                Direction::West,
+            ],
+        )];
+
+        assert_eq!(
+            Err(vec![Invalid::PathOutOfReach(infantry_id)]),
+            validate_turn(
+                &red_player,
+                &doubling_back,
+                &visibility,
+                &indexes,
+                &map,
+                &Conditions::test(),
+                Table::bundled()
+            )
+        );
+    }
+
+    #[test]
+    fn cannot_pick_up_enemy_or_distant_crates() {
+        let red_player = Id::test("red");
+        let blue_player = Id::test("blue");
+
+        let truck_id = UnitId::test("red truck");
+        let enemy_crate_id = UnitId::test("blue crate");
+        let distant_crate_id = UnitId::test("red crate");
+
+        let indexes = Indexes::make(vec![
+            (
+                truck_id.clone(),
+                unit::Model::new(
+                    Unit::Truck,
+                    &red_player,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                enemy_crate_id.clone(),
+                unit::Model::new(
+                    Unit::SupplyCrate,
+                    &blue_player,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                    &TeamColor::Blue,
+                ),
+            ),
+            (
+                distant_crate_id.clone(),
+                unit::Model::new(
+                    Unit::SupplyCrate,
+                    &red_player,
+                    Place::on_map(12, 12, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+        ]);
+
+        let pick_up = |cargo_id: &UnitId| {
+            vec![Action::PickUp {
+                unit_id: truck_id.clone(),
+                cargo_id: cargo_id.clone(),
+                path: Path::from_directions_test_only(&located::unit(2, 2), &vec![]),
+            }]
+        };
+
+        let validate = |actions: &Vec<Action>| {
+            validate_turn(
+                &red_player,
+                actions,
+                &HashSet::new(),
+                &indexes,
+                &Map::grass_square(),
+                &Conditions::test(),
+                Table::bundled(),
+            )
+        };
+
+        assert_eq!(
+            Err(vec![Invalid::NotOwnedByPlayer(enemy_crate_id.clone())]),
+            validate(&pick_up(&enemy_crate_id))
+        );
+
+        assert_eq!(
+            Err(vec![Invalid::CarryOutOfReach {
+                transport_id: truck_id.clone(),
+                cargo_id: distant_crate_id.clone(),
+            }]),
+            validate(&pick_up(&distant_crate_id))
+        );
+    }
+
+    #[test]
+    fn one_action_per_unit_and_only_ones_it_can_do() {
+        let red_player = Id::test("red");
+
+        let artillery_id = UnitId::test("artillery");
+        let crate_id = UnitId::test("crate");
+
+        let indexes = Indexes::make(vec![
+            (
+                artillery_id.clone(),
+                unit::Model::new(
+                    Unit::Artillery,
+                    &red_player,
+                    Place::on_map(2, 2, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+            (
+                crate_id.clone(),
+                unit::Model::new(
+                    Unit::SupplyCrate,
+                    &red_player,
+                    Place::on_map(2, 3, FacingDirection::Right),
+                    &TeamColor::Red,
+                ),
+            ),
+        ]);
+
+        let validate = |actions: &Vec<Action>| {
+            validate_turn(
+                &red_player,
+                actions,
+                &HashSet::new(),
+                &indexes,
+                &Map::grass_square(),
+                &Conditions::test(),
+                Table::bundled(),
+            )
+        };
+
+        let bombard = Action::Bombard {
+            unit_id: artillery_id.clone(),
+            target: located::unit(4, 2),
+        };
+
+        assert_eq!(Ok(()), validate(&vec![bombard.clone()]));
+
+        assert_eq!(
+            Err(vec![Invalid::MoreThanOneAction(artillery_id.clone())]),
+            validate(&vec![travel(&artillery_id, vec![Direction::East]), bombard])
+        );
+
+        assert_eq!(
+            Err(vec![Invalid::CannotEntrench(crate_id.clone())]),
+            validate(&vec![Action::Entrench {
+                unit_id: crate_id.clone(),
+                against: Direction::North,
+            }])
+        );
+
+        assert_eq!(
+            Err(vec![Invalid::CannotAttack(crate_id.clone())]),
+            validate(&vec![Action::Attack(Attack {
+                unit_id: crate_id.clone(),
+                path: Path::from_directions_test_only(&located::unit(2, 3), &vec![]),
+            })])
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::rng::RandGen;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Weather {
+    Clear,
+    // Rain turns the ground to mud, which bogs
+    // down anything on wheels or tracks
+    Mud,
+    // Cold weather makes every unit burn through
+    // more supplies just to stay alive
+    Snow,
+    Fog,
+}
+
+// Out of six, how likely the weather is to
+// carry on into the next turn unchanged
+const CHANCE_TO_PERSIST: u8 = 4;
+
+impl Weather {
+    pub fn next(&self, rng: &mut RandGen) -> Weather {
+        if rng.gen::<u8>(0, 6) < CHANCE_TO_PERSIST {
+            return self.clone();
+        }
+
+        ALL[rng.gen::<usize>(0, ALL.len())].clone()
+    }
+
+    pub fn visibility_multiplier(&self) -> f32 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Mud => 1.0,
+            Weather::Snow => 0.75,
+            Weather::Fog => 0.5,
+        }
+    }
+
+    pub fn baseline_cost_multiplier(&self) -> f32 {
+        match self {
+            Weather::Clear => 1.0,
+            Weather::Mud => 1.0,
+            Weather::Snow => 1.5,
+            Weather::Fog => 1.0,
+        }
+    }
+}
+
+impl fmt::Display for Weather {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Weather::Clear => write!(f, "clear"),
+            Weather::Mud => write!(f, "mud"),
+            Weather::Snow => write!(f, "snow"),
+            Weather::Fog => write!(f, "fog"),
+        }
+    }
+}
+
+pub const ALL: [Weather; 4] = [Weather::Clear, Weather::Mud, Weather::Snow, Weather::Fog];
+
+#[cfg(test)]
+mod test_weather {
+    use crate::game::weather::Weather;
+    use crate::rng::RandGen;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn weather_is_the_same_for_the_same_seed() {
+        let forecast = |mut rng: RandGen| {
+            let mut weather = Weather::Clear;
+
+            (0..16)
+                .map(|_| {
+                    weather = weather.next(&mut rng);
+                    weather.clone()
+                })
+                .collect::<Vec<Weather>>()
+        };
+
+        let first = forecast(RandGen::test());
+
+        assert_eq!(first, forecast(RandGen::test()));
+        assert!(first.iter().any(|weather| weather != &Weather::Clear));
+    }
+}
@@ -38,6 +38,43 @@ impl<T> Located<T> {
         dx + dy
     }
 
+    // The tiles a straight line crosses on the way to the
+    // other location, not counting the tiles at either end.
+    // This only uses integer math, so every machine draws
+    // the same line
+    pub fn tiles_between<U>(&self, other: &Located<U>) -> Vec<Located<()>> {
+        let (end_x, end_y) = (other.x as i32, other.y as i32);
+        let (mut x, mut y) = (self.x as i32, self.y as i32);
+
+        let dx = (end_x - x).abs();
+        let dy = -(end_y - y).abs();
+        let step_x = if x < end_x { 1 } else { -1 };
+        let step_y = if y < end_y { 1 } else { -1 };
+
+        let mut err = dx + dy;
+        let mut tiles = vec![];
+
+        while x != end_x || y != end_y {
+            let doubled_err = 2 * err;
+
+            if doubled_err >= dy {
+                err += dy;
+                x += step_x;
+            }
+
+            if doubled_err <= dx {
+                err += dx;
+                y += step_y;
+            }
+
+            if x != end_x || y != end_y {
+                tiles.push(unit(x as u16, y as u16));
+            }
+        }
+
+        tiles
+    }
+
     pub fn to_unit(&self) -> Located<()> {
         unit(self.x, self.y)
     }
@@ -1,4 +1,5 @@
 use crate::facing_direction::FacingDirection;
+use crate::id::Id;
 use crate::located::Located;
 use crate::tile::Tile;
 use crate::unit::Unit;
@@ -14,6 +15,41 @@ pub struct Map {
     pub grid: Vec<Vec<Located<Tile>>>,
     pub width: u16,
     pub height: u16,
+    // Structures engineers are part way through building
+    pub constructions: HashMap<Located<()>, Construction>,
+    // Finished structures, and the terrain they were built over
+    pub structures: HashMap<Located<()>, Structure>,
+    // Roads sit on top of the tile, so a road can run
+    // through a forest without clearing it
+    pub roads: HashSet<Located<()>>,
+    pub towns: HashMap<Located<()>, Town>,
+    // How many times each tile has been foraged lately. Tiles
+    // grow back one turn at a time once they are left alone
+    pub foraged: HashMap<Located<()>, u8>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Construction {
+    pub structure: Tile,
+    pub owner: Id,
+    pub progress: u8,
+}
+
+// Towns with no owner are neutral
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Town {
+    pub owner: Option<Id>,
+}
+
+// How many supplies a town sends out to its owner's
+// crates and trucks each turn
+pub const TOWN_SUPPLIES_PER_TURN: i16 = 512;
+
+// Structures that came with the map have no owner
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Structure {
+    pub owner: Option<Id>,
+    pub replaced: Tile,
 }
 
 impl Map {
@@ -24,6 +60,11 @@ impl Map {
             base_tile: Tile::GrassPlain,
             features,
             grid: Vec::new(),
+            constructions: HashMap::new(),
+            structures: HashMap::new(),
+            roads: HashSet::new(),
+            towns: HashMap::new(),
+            foraged: HashMap::new(),
             width: 16,
             height: 16,
         }
@@ -51,6 +92,11 @@ impl Map {
             base_tile: Tile::GrassPlain,
             features,
             grid: Vec::new(),
+            constructions: HashMap::new(),
+            structures: HashMap::new(),
+            roads: HashSet::new(),
+            towns: HashMap::new(),
+            foraged: HashMap::new(),
             width: size,
             height: size,
         }
@@ -64,6 +110,11 @@ impl Map {
             base_tile: Tile::GrassPlain,
             features,
             grid: Vec::new(),
+            constructions: HashMap::new(),
+            structures: HashMap::new(),
+            roads: HashSet::new(),
+            towns: HashMap::new(),
+            foraged: HashMap::new(),
             width: 16,
             height: 16,
         }
@@ -91,6 +142,11 @@ impl Map {
             base_tile: Tile::GrassPlain,
             features,
             grid: Vec::new(),
+            constructions: HashMap::new(),
+            structures: HashMap::new(),
+            roads: HashSet::new(),
+            towns: HashMap::new(),
+            foraged: HashMap::new(),
             width: size,
             height: size,
         }
@@ -129,6 +185,124 @@ impl Map {
             .cloned()
             .unwrap_or_else(|| self.base_tile.clone())
     }
+
+    // A road across a river is cut once its bridge is gone
+    pub fn has_road(&self, loc: &Located<()>) -> bool {
+        self.roads.contains(loc) && self.get_tile(loc) != Tile::Water
+    }
+
+    pub fn set_tile(&mut self, loc: &Located<()>, tile: Tile) {
+        if let Some(loc_tile) = self
+            .grid
+            .get_mut(loc.y as usize)
+            .and_then(|row| row.get_mut(loc.x as usize))
+        {
+            loc_tile.value = tile.clone();
+        }
+
+        self.features.insert(loc.clone(), tile);
+    }
+
+    // Puts one turn of work into a structure. Returns
+    // true if the structure was finished and is now on
+    // the map
+    pub fn work_on(
+        &mut self,
+        loc: &Located<()>,
+        structure: &Tile,
+        owner: &Id,
+    ) -> Result<bool, String> {
+        let build_turns = match structure.build_turns() {
+            Some(turns) => turns,
+            None => return Err("tile is not a structure that can be built".to_string()),
+        };
+
+        let current_tile = self.get_tile(loc);
+
+        if !current_tile.can_build(structure) {
+            return Err("structure cannot be built on this tile".to_string());
+        }
+
+        let progress = match self.constructions.get(loc) {
+            // Work towards some other structure is thrown away
+            Some(construction)
+                if construction.structure == *structure && construction.owner == *owner =>
+            {
+                construction.progress + 1
+            }
+            _ => 1,
+        };
+
+        if progress < build_turns {
+            self.constructions.insert(
+                loc.clone(),
+                Construction {
+                    structure: structure.clone(),
+                    owner: owner.clone(),
+                    progress,
+                },
+            );
+
+            return Ok(false);
+        }
+
+        self.constructions.remove(loc);
+        self.structures.insert(
+            loc.clone(),
+            Structure {
+                owner: Some(owner.clone()),
+                replaced: current_tile,
+            },
+        );
+        self.set_tile(loc, structure.clone());
+
+        Ok(true)
+    }
+
+    pub fn capture_town(&mut self, loc: &Located<()>, owner: &Id) -> Result<(), String> {
+        match self.towns.get_mut(loc) {
+            Some(town) => {
+                town.owner = Some(owner.clone());
+
+                Ok(())
+            }
+            None => Err("there is no town to capture".to_string()),
+        }
+    }
+
+    pub fn times_foraged(&self, loc: &Located<()>) -> u8 {
+        self.foraged.get(loc).cloned().unwrap_or(0)
+    }
+
+    pub fn forage(&mut self, loc: &Located<()>) {
+        let times_foraged = self.foraged.entry(loc.clone()).or_insert(0);
+
+        *times_foraged = times_foraged.saturating_add(1);
+    }
+
+    // Tiles nobody foraged this turn grow back a little
+    pub fn regrow_forage(&mut self, foraged_now: &HashSet<Located<()>>) {
+        for (loc, times_foraged) in self.foraged.iter_mut() {
+            if !foraged_now.contains(loc) {
+                *times_foraged = times_foraged.saturating_sub(1);
+            }
+        }
+
+        self.foraged.retain(|_, times_foraged| *times_foraged > 0);
+    }
+
+    // Removes a structure, returning the tile that
+    // was underneath it
+    pub fn demolish(&mut self, loc: &Located<()>) -> Result<Tile, String> {
+        match self.structures.remove(loc) {
+            Some(structure) => {
+                self.set_tile(loc, structure.replaced.clone());
+
+                Ok(structure.replaced)
+            }
+            None => Err("there is no structure to demolish".to_string()),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
@@ -169,6 +343,11 @@ impl MapOpt {
                         x: 4,
                         y: 2,
                     },
+                    Located::<(FacingDirection, Unit)> {
+                        value: (FacingDirection::Right, Unit::SupplyDepot),
+                        x: 0,
+                        y: 2,
+                    },
                 ],
                 second_player_military: vec![
                     Located::<(FacingDirection, Unit)> {
@@ -186,6 +365,11 @@ impl MapOpt {
                         x: map.width - 5,
                         y: map.height - 3,
                     },
+                    Located::<(FacingDirection, Unit)> {
+                        value: (FacingDirection::Left, Unit::SupplyDepot),
+                        x: map.width - 1,
+                        y: map.height - 3,
+                    },
                 ],
                 rest_players_militatries: vec![],
             },
@@ -226,6 +410,11 @@ impl MapOpt {
                         x: 8,
                         y: 6,
                     },
+                    Located::<(FacingDirection, Unit)> {
+                        value: (FacingDirection::Right, Unit::SupplyDepot),
+                        x: 0,
+                        y: 2,
+                    },
                 ],
                 second_player_military: vec![
                     Located::<(FacingDirection, Unit)> {
@@ -253,6 +442,11 @@ impl MapOpt {
                         x: map.width - 6,
                         y: map.height - 3,
                     },
+                    Located::<(FacingDirection, Unit)> {
+                        value: (FacingDirection::Left, Unit::SupplyDepot),
+                        x: map.width - 1,
+                        y: map.height - 3,
+                    },
                 ],
                 rest_players_militatries: vec![],
             },
@@ -395,10 +589,10 @@ F     HHHH     G
 F      HHF F   G
 F   F  FF  F   G
 F  FF      F   G
-G      FF      G
+grrrrrrffrrrrrrg
 G    H FF H    G
 G    H FF H    G
-G   HHHFFHHH   G
+G  THHHFFHHHT  G
 F      FHHHHH  G
 F HHH   F  HH  G
 F   HH      H  G
@@ -461,13 +655,44 @@ impl TryFrom<DevFlags> for Map {
         let width = row_lengths.first().unwrap();
 
         let mut features = HashMap::new();
+        let mut structures = HashMap::new();
+        let mut roads = HashSet::new();
+        let mut towns = HashMap::new();
         for (ri, row) in rows.iter().enumerate() {
             for (ci, col) in row.chars().enumerate() {
-                let tile = match col {
+                // Lowercase letters are the same tile with a
+                // road running through it
+                if col.is_ascii_lowercase() {
+                    roads.insert(located::unit(ci as u16, ri as u16));
+                }
+
+                let tile = match col.to_ascii_uppercase() {
                     ' ' => flags.base_tile.clone(),
+                    'R' => flags.base_tile.clone(),
                     'G' => Tile::GrassPlain,
                     'H' => Tile::Hills,
                     'F' => Tile::Forest,
+                    'W' if col == 'w' => {
+                        return Err("roads cannot run over water".to_string());
+                    }
+                    'W' => Tile::Water,
+                    'S' => Tile::Shallows,
+                    'T' => {
+                        towns.insert(located::unit(ci as u16, ri as u16), Town { owner: None });
+
+                        Tile::Town
+                    }
+                    'B' => {
+                        structures.insert(
+                            located::unit(ci as u16, ri as u16),
+                            Structure {
+                                owner: None,
+                                replaced: Tile::Water,
+                            },
+                        );
+
+                        Tile::Bridge
+                    }
                     _ => {
                         let mut err_msg = "unrecognized char for making dev map: ".to_string();
 
@@ -485,6 +710,11 @@ impl TryFrom<DevFlags> for Map {
             base_tile: flags.base_tile,
             features,
             grid: vec![],
+            constructions: HashMap::new(),
+            structures,
+            roads,
+            towns,
+            foraged: HashMap::new(),
             width: *width as u16,
             height: rows.len() as u16,
         }
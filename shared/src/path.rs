@@ -1,9 +1,11 @@
 use crate::arrow::Arrow;
 use crate::direction::Direction;
+use crate::game::conditions::Conditions;
 use crate::located::Located;
 use crate::map::Map;
-use crate::unit::Unit;
+use crate::unit::definition::Definition;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
 pub struct Path {
@@ -34,6 +36,71 @@ impl Path {
         None
     }
 
+    // The path up until, but not including, the given location.
+    // The returned path is empty if the location is the origin or
+    // the next step after the origin
+    pub fn until(&self, loc: &Located<()>) -> Path {
+        let mut steps = Vec::new();
+
+        for step in self.steps.iter() {
+            if step.is_same_pos_as(loc) {
+                break;
+            }
+
+            steps.push(step.clone());
+        }
+
+        if steps.len() < 2 {
+            steps = Vec::new();
+        }
+
+        Path { steps }
+    }
+
+    // The path up until, and including, the given location.
+    // The returned path is empty if the location is the origin
+    pub fn through(&self, loc: &Located<()>) -> Path {
+        let mut steps = Vec::new();
+
+        for step in self.steps.iter() {
+            steps.push(step.clone());
+
+            if step.is_same_pos_as(loc) {
+                break;
+            }
+        }
+
+        if steps.len() < 2 {
+            steps = Vec::new();
+        }
+
+        Path { steps }
+    }
+
+    // The path up until, and including, the first location
+    // after the origin that is in the given zone
+    pub fn halted_by(&self, zone: &HashSet<Located<()>>) -> Path {
+        match self
+            .steps
+            .iter()
+            .skip(1)
+            .find(|step| zone.contains(&step.to_unit()))
+        {
+            Some(step) => self.through(&step.to_unit()),
+            None => self.clone(),
+        }
+    }
+
+    // The direction the path was travelling when it arrived
+    // at the given location
+    pub fn direction_into(&self, loc: &Located<()>) -> Option<Direction> {
+        self.steps
+            .iter()
+            .skip(1)
+            .find(|step| step.is_same_pos_as(loc))
+            .map(|step| step.value.direction.clone())
+    }
+
     pub fn first_pos(&self) -> Option<Located<()>> {
         self.steps.first().map(|loc_step| loc_step.to_unit())
     }
@@ -47,17 +114,20 @@ impl Path {
             None
         }
     }
-    pub fn supply_cost(&self, map: &Map, unit: &Unit) -> i16 {
+    pub fn supply_cost(&self, map: &Map, definition: &Definition, conditions: &Conditions) -> i16 {
         let mut cost: i16 = 0;
 
         for loc_step in self.steps.iter() {
-            let tile = map.get_tile(&loc_step.to_unit());
-
-            cost += tile.travel_supply_cost(unit);
+            cost += definition.travel_supply_cost_at(map, &loc_step.to_unit(), conditions);
         }
 
         cost
     }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
     pub fn last_pos(&self) -> Option<Located<()>> {
         self.steps.last().map(|loc_step| loc_step.to_unit())
     }
@@ -227,6 +297,33 @@ mod test_path {
         assert_eq!(want, got);
     }
 
+    #[test]
+    fn until() {
+        let path = Path::from_directions_test_only(
+            &located::unit(2, 2),
+            &vec![Direction::East, Direction::East, Direction::East],
+        );
+
+        let got = path.until(&located::unit(4, 2));
+
+        let want = Path::from_directions_test_only(&located::unit(2, 2), &vec![Direction::East]);
+
+        assert_eq!(want, got);
+
+        let got_through = path.through(&located::unit(4, 2));
+
+        let want_through = Path::from_directions_test_only(
+            &located::unit(2, 2),
+            &vec![Direction::East, Direction::East],
+        );
+
+        assert_eq!(want_through, got_through);
+
+        let got_direction = path.direction_into(&located::unit(4, 2));
+
+        assert_eq!(Some(Direction::East), got_direction);
+    }
+
     #[test]
     fn do_notcross() {
         let path_1 = Path::from_directions_test_only(
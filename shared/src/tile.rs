@@ -1,70 +1,126 @@
-use crate::unit::Unit;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Tile {
     GrassPlain,
     Hills,
     Forest,
+    Water,
+    Shallows,
+    // Towns send supplies to whoever holds them
+    Town,
+    // Structures built by engineers
+    Fortification,
+    ClearedPath,
+    Bridge,
 }
 
 impl Tile {
-    pub fn visibility_cost(&self) -> f32 {
+    // How high the ground itself is. Units on high
+    // ground see further, and over things that would
+    // otherwise block their view
+    pub fn elevation(&self) -> u8 {
         match self {
-            Tile::GrassPlain => 1.0,
-            Tile::Hills => 2.0,
-            Tile::Forest => 3.5,
+            Tile::GrassPlain => 0,
+            Tile::Hills => 1,
+            Tile::Forest => 0,
+            Tile::Water => 0,
+            Tile::Shallows => 0,
+            Tile::Town => 0,
+            Tile::Fortification => 0,
+            Tile::ClearedPath => 0,
+            Tile::Bridge => 0,
         }
     }
 
-    pub fn mobility_cost(&self, unit: &Unit) -> f32 {
+    // How high this tile stands in the way of someone
+    // looking past it, counting trees and buildings
+    pub fn height(&self) -> u8 {
+        match self {
+            Tile::GrassPlain => 0,
+            Tile::Hills => 1,
+            Tile::Forest => 1,
+            Tile::Water => 0,
+            Tile::Shallows => 0,
+            Tile::Town => 1,
+            Tile::Fortification => 0,
+            Tile::ClearedPath => 0,
+            Tile::Bridge => 0,
+        }
+    }
+
+    // Whether units inside this tile can only be seen
+    // from right next to it
+    pub fn conceals(&self) -> bool {
+        matches!(self, Tile::Forest)
+    }
+
+    // Whether there is anything on this tile for
+    // units to live off of
+    pub fn can_forage(&self) -> bool {
+        matches!(self, Tile::GrassPlain | Tile::Forest)
+    }
+
+    // How much harder it is to dislodge a unit
+    // defending on this tile
+    pub fn defense_bonus(&self) -> f32 {
         match self {
             Tile::GrassPlain => 1.0,
             Tile::Hills => 1.5,
-            Tile::Forest => match unit {
-                Unit::Infantry => 1.5,
-                Unit::Tank => 2.5,
-                Unit::Truck => 4.0,
-                Unit::SupplyCrate => 512.0,
-            },
+            Tile::Forest => 1.75,
+            Tile::Water => 1.0,
+            Tile::Shallows => 0.75,
+            Tile::Town => 1.75,
+            Tile::Fortification => 2.0,
+            Tile::ClearedPath => 1.25,
+            Tile::Bridge => 0.75,
+        }
+    }
+
+    // How many turns of work it takes to build this
+    // structure. None means it is not a structure
+    pub fn build_turns(&self) -> Option<u8> {
+        match self {
+            Tile::GrassPlain => None,
+            Tile::Hills => None,
+            Tile::Forest => None,
+            Tile::Water => None,
+            Tile::Shallows => None,
+            Tile::Town => None,
+            Tile::Fortification => Some(2),
+            Tile::ClearedPath => Some(2),
+            Tile::Bridge => Some(3),
         }
     }
 
-    pub fn travel_supply_cost(&self, unit: &Unit) -> i16 {
-        match unit.active_supply_cost() {
-            Some(cost_per_tile) => {
-                let cost: f32 = match unit {
-                    Unit::Infantry => match self {
-                        Tile::GrassPlain => cost_per_tile,
-                        Tile::Hills => cost_per_tile,
-                        Tile::Forest => cost_per_tile,
-                    },
-                    Unit::Tank => {
-                        let base = match self {
-                            Tile::GrassPlain => 1.0,
-                            Tile::Hills => 1.25,
-                            Tile::Forest => 1.5,
-                        };
-
-                        cost_per_tile * base
-                    }
-                    Unit::Truck => {
-                        let base = match self {
-                            Tile::GrassPlain => 1.0,
-                            Tile::Hills => 1.25,
-                            Tile::Forest => 1.75,
-                        };
-
-                        cost_per_tile * base
-                    }
-                    Unit::SupplyCrate => 0.0,
-                };
-
-                let multiplier = 1.0;
-
-                (multiplier * cost).floor() as i16
-            }
-            None => 0,
+    // Whether the given structure can be built on top
+    // of this tile
+    pub fn can_build(&self, structure: &Tile) -> bool {
+        matches!(
+            (self, structure),
+            (Tile::GrassPlain, Tile::Fortification)
+                | (Tile::Hills, Tile::Fortification)
+                | (Tile::Forest, Tile::Fortification)
+                | (Tile::Forest, Tile::ClearedPath)
+                | (Tile::Water, Tile::Bridge)
+                | (Tile::Shallows, Tile::Bridge)
+        )
+    }
+
+    // How much of a unit's mobility budget entering this
+    // tile takes, unless the unit's definition says otherwise
+    pub fn mobility_cost(&self) -> f32 {
+        match self {
+            Tile::GrassPlain => 1.0,
+            Tile::Hills => 1.5,
+            Tile::Forest => 1.5,
+            // Ground units cannot cross water without a bridge
+            Tile::Water => f32::INFINITY,
+            Tile::Shallows => 3.0,
+            Tile::Town => 1.0,
+            Tile::Fortification => 1.5,
+            Tile::ClearedPath => 1.0,
+            Tile::Bridge => 1.0,
         }
     }
 }
@@ -75,17 +131,44 @@ pub const PIXEL_HEIGHT: u16 = 16;
 pub const PIXEL_WIDTH_FL: f64 = 16.0;
 pub const PIXEL_HEIGHT_FL: f64 = 16.0;
 
-pub const ALL: [Tile; 3] = [Tile::GrassPlain, Tile::Hills, Tile::Forest];
+pub const ALL: [Tile; 9] = [
+    Tile::GrassPlain,
+    Tile::Hills,
+    Tile::Forest,
+    Tile::Water,
+    Tile::Shallows,
+    Tile::Town,
+    Tile::Fortification,
+    Tile::ClearedPath,
+    Tile::Bridge,
+];
 
 #[cfg(test)]
 mod test_tiles {
     use crate::tile::Tile;
+    use crate::unit::definition::Table;
     use crate::unit::Unit;
     use pretty_assertions::assert_eq;
 
     #[test]
     fn infantry_mobility_cost() {
         let want = 10;
-        assert_eq!(want, Tile::GrassPlain.travel_supply_cost(&Unit::Infantry));
+        assert_eq!(
+            want,
+            Table::bundled()
+                .get(&Unit::Infantry)
+                .travel_supply_cost(&Tile::GrassPlain)
+        );
+    }
+
+    #[test]
+    fn water_is_impassable_but_shallows_can_be_forded() {
+        let infantry = Table::bundled().get(&Unit::Infantry);
+
+        assert!(infantry.mobility_cost(&Tile::Water).is_infinite());
+        assert!(
+            infantry.mobility_cost(&Tile::Shallows) > infantry.mobility_cost(&Tile::GrassPlain)
+        );
+        assert!(infantry.mobility_cost(&Tile::Shallows).is_finite());
     }
 }
@@ -1,11 +1,17 @@
 use crate::direction::Direction;
 use crate::facing_direction::FacingDirection;
+use crate::game::conditions::Conditions;
+use crate::game::supply_line;
 use crate::id::Id;
 use crate::located::Located;
 use crate::rng::RandGen;
 use crate::team_color::TeamColor;
+use crate::unit::definition::{Definition, Table};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::fmt;
+
+pub mod definition;
 
 ///////////////////////////////////////////////////////////////
 // Types
@@ -21,6 +27,9 @@ pub struct Model {
     pub supplies: i16,
     pub entrenched_against: Option<Direction>,
     pub health: Health,
+    // Whether a depot could get supplies to this unit
+    // at the end of the last turn
+    pub in_supply: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
@@ -31,23 +40,158 @@ pub enum Health {
     Low,
 }
 
+impl Health {
+    // None means the unit did not survive the damage
+    pub fn damaged(&self, damage: u8) -> Option<Health> {
+        let mut health = Some(self.clone());
+
+        for _ in 0..damage {
+            health = match health {
+                Some(Health::Full) => Some(Health::High),
+                Some(Health::High) => Some(Health::Medium),
+                Some(Health::Medium) => Some(Health::Low),
+                Some(Health::Low) => None,
+                None => None,
+            };
+        }
+
+        health
+    }
+
+    pub fn recovered(&self) -> Health {
+        match self {
+            Health::Full => Health::Full,
+            Health::High => Health::Full,
+            Health::Medium => Health::High,
+            Health::Low => Health::Medium,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        matches!(self, Health::Full)
+    }
+
+    pub fn strength_multiplier(&self) -> f32 {
+        match self {
+            Health::Full => 1.0,
+            Health::High => 0.8,
+            Health::Medium => 0.6,
+            Health::Low => 0.4,
+        }
+    }
+
+    pub fn mobility_multiplier(&self) -> f32 {
+        match self {
+            Health::Full => 1.0,
+            Health::High => 1.0,
+            Health::Medium => 0.8,
+            Health::Low => 0.6,
+        }
+    }
+
+    pub fn visibility_multiplier(&self) -> f32 {
+        match self {
+            Health::Full => 1.0,
+            Health::High => 1.0,
+            Health::Medium => 0.85,
+            Health::Low => 0.7,
+        }
+    }
+}
+
+impl fmt::Display for Health {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Health::Full => write!(f, "full"),
+            Health::High => write!(f, "high"),
+            Health::Medium => write!(f, "medium"),
+            Health::Low => write!(f, "low"),
+        }
+    }
+}
+
 impl Model {
-    pub fn supplies_percent(&self) -> f32 {
-        (self.supplies as f32) / (self.unit.max_supplies() as f32)
+    // Stats are not kept on the unit itself, but looked up
+    // in the table for the game the unit is in
+    pub fn definition<'a>(&self, table: &'a Table) -> &'a Definition {
+        table.get(&self.unit)
+    }
+
+    pub fn supplies_percent(&self, table: &Table) -> f32 {
+        (self.supplies as f32) / (self.definition(table).max_supplies as f32)
+    }
+
+    // The stats below are the unit's base stats, adjusted
+    // for how damaged the unit is
+
+    pub fn mobility_budget(&self, table: &Table) -> f32 {
+        self.definition(table).mobility_budget * self.health.mobility_multiplier()
+    }
+
+    pub fn visibility_budget(&self, table: &Table) -> f32 {
+        self.definition(table).visibility_budget * self.health.visibility_multiplier()
+    }
+
+    pub fn attack_strength(&self, table: &Table) -> f32 {
+        self.definition(table).attack_strength * self.health.strength_multiplier()
+    }
+
+    pub fn defense_strength(&self, table: &Table) -> f32 {
+        self.definition(table).defense_strength * self.health.strength_multiplier()
+    }
+
+    // What the unit uses up at the start of each turn just
+    // by existing. Units cut off from their depots use more
+    pub fn baseline_supply_cost(&self, table: &Table, conditions: &Conditions) -> Option<i16> {
+        self.definition(table)
+            .baseline_supply_cost_in(conditions)
+            .map(|cost| {
+                if self.in_supply {
+                    cost
+                } else {
+                    cost * supply_line::OUT_OF_SUPPLY_BASELINE_MULTIPLIER
+                }
+            })
+            .map(|cost| cost.ceil() as i16)
+    }
+
+    // Damaged units recover when they hold still with
+    // plenty of supplies
+    pub fn can_recover(&self, table: &Table) -> bool {
+        !self.health.is_full() && self.supplies_percent(table) >= 0.5
     }
 
     pub fn new(unit: Unit, owner_id: &Id, place: Place, color: &TeamColor) -> Model {
+        let supplies = Table::bundled().get(&unit).max_supplies;
+
         Model {
-            unit: unit.clone(),
+            unit,
             owner: owner_id.clone(),
             place,
             color: color.clone(),
             name: None,
-            supplies: unit.max_supplies(),
+            supplies,
             entrenched_against: None,
             health: Health::Full,
+            in_supply: true,
         }
     }
+
+    // Units are made with the bundled stats, so this moves
+    // their supplies over to the given table. Units that were
+    // fully supplied stay fully supplied
+    pub fn with_table(mut self, table: &Table) -> Model {
+        let bundled_max = self.definition(Table::bundled()).max_supplies;
+        let max_supplies = self.definition(table).max_supplies;
+
+        self.supplies = if self.supplies == bundled_max {
+            max_supplies
+        } else {
+            self.supplies.min(max_supplies)
+        };
+
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
@@ -150,152 +294,81 @@ impl Ord for UnitId {
     }
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Unit {
     Infantry,
     Tank,
+    Cavalry,
     Truck,
+    Artillery,
+    Engineer,
     SupplyCrate,
+    SupplyDepot,
 }
 
-impl Unit {
-    pub fn mobility_budget(&self) -> f32 {
-        match self {
-            Unit::Infantry => 3.0,
-            Unit::Tank => 6.0,
-            Unit::Truck => 8.0,
-            Unit::SupplyCrate => 0.0,
-        }
-    }
-
-    pub fn replenishable(&self) -> bool {
-        match self {
-            Unit::Infantry => true,
-            Unit::Tank => true,
-            Unit::Truck => true,
-            Unit::SupplyCrate => false,
-        }
-    }
-
-    pub fn is_rideable(&self) -> bool {
-        match self {
-            Unit::Infantry => false,
-            Unit::Tank => false,
-            Unit::Truck => true,
-            Unit::SupplyCrate => false,
-        }
-    }
-
-    pub fn can_attack(&self) -> bool {
-        match self {
-            Unit::Infantry => true,
-            Unit::Tank => true,
-            Unit::Truck => false,
-            Unit::SupplyCrate => false,
-        }
-    }
-
-    pub fn can_pick_up_supply_crates(&self) -> bool {
-        match self {
-            Unit::Infantry => false,
-            Unit::Tank => false,
-            Unit::Truck => true,
-            Unit::SupplyCrate => false,
-        }
-    }
-
-    pub fn is_supply_crate(&self) -> bool {
-        match self {
-            Unit::Infantry => false,
-            Unit::Tank => false,
-            Unit::Truck => false,
-            Unit::SupplyCrate => true,
-        }
-    }
-
-    pub fn carrying_capacity(&self) -> usize {
-        match self {
-            Unit::Infantry => 0,
-            Unit::Tank => 0,
-            Unit::Truck => 1,
-            Unit::SupplyCrate => 0,
-        }
-    }
-
-    pub fn can_carry(&self, carry_unit: &Unit) -> bool {
-        match self {
-            Unit::Infantry => false,
-            Unit::Tank => false,
-            Unit::Truck => match carry_unit {
-                Unit::Infantry => true,
-                Unit::Tank => false,
-                Unit::Truck => false,
-                Unit::SupplyCrate => true,
-            },
-            Unit::SupplyCrate => false,
-        }
-    }
-
-    pub fn visibility_budget(&self) -> f32 {
-        match self {
-            Unit::Infantry => 3.5,
-            Unit::Tank => 2.0,
-            Unit::Truck => 3.0,
-            Unit::SupplyCrate => 0.0,
-        }
-    }
-
-    pub fn max_supplies(&self) -> i16 {
-        match self {
-            Unit::Infantry => 1024,
-            Unit::Tank => 3072,
-            Unit::Truck => 2048,
-            Unit::SupplyCrate => 8192,
-        }
-    }
-
-    // The cost of moving one tile, without any adjustments
-    // for terrain type
-    pub fn active_supply_cost(&self) -> Option<f32> {
-        match self {
-            Unit::Infantry => Some(10.3333),
-            Unit::Tank => Some(56.8889),
-            Unit::Truck => Some(25.6),
-            Unit::SupplyCrate => None,
-        }
-    }
-
-    // The cost of just existing for one turn, whether
-    // the unit did anything or not
-    pub fn baseline_supply_cost(&self) -> Option<f32> {
-        match self {
-            Unit::Infantry => Some(21.3333),
-            Unit::Tank => Some(64.0),
-            Unit::Truck => Some(10.6667),
-            Unit::SupplyCrate => None,
-        }
-    }
-}
+// Every kind of unit, each of which needs a definition
+// in the unit table
+pub const ALL_UNITS: [Unit; 8] = [
+    Unit::Infantry,
+    Unit::Tank,
+    Unit::Cavalry,
+    Unit::Truck,
+    Unit::Artillery,
+    Unit::Engineer,
+    Unit::SupplyCrate,
+    Unit::SupplyDepot,
+];
 
 impl ToString for Unit {
     fn to_string(&self) -> String {
         match self {
             Unit::Infantry => "infantry".to_string(),
             Unit::Tank => "tank".to_string(),
+            Unit::Cavalry => "cavalry".to_string(),
             Unit::Truck => "truck".to_string(),
+            Unit::Artillery => "artillery".to_string(),
+            Unit::Engineer => "engineer".to_string(),
             Unit::SupplyCrate => "supply crate".to_string(),
+            Unit::SupplyDepot => "supply depot".to_string(),
         }
     }
 }
 
 #[cfg(test)]
 mod test_units {
-    use crate::unit::Unit;
+    use crate::tile::Tile;
+    use crate::unit::definition::Table;
+    use crate::unit::{Health, Unit};
     use pretty_assertions::assert_eq;
 
     #[test]
     fn infantry_mobility() {
         let want = Some(21.3333);
-        assert_eq!(want, Unit::Infantry.baseline_supply_cost());
+        assert_eq!(
+            want,
+            Table::bundled().get(&Unit::Infantry).baseline_supply_cost
+        );
+    }
+
+    #[test]
+    fn health_damage_and_recovery() {
+        assert_eq!(Some(Health::Medium), Health::Full.damaged(2));
+        assert_eq!(None, Health::Medium.damaged(3));
+        assert_eq!(Health::High, Health::Medium.recovered());
+        assert_eq!(Health::Full, Health::Full.recovered());
+    }
+
+    #[test]
+    fn cavalry_outruns_tanks_on_grass_but_not_in_forest() {
+        let reach = |unit: Unit, tile: Tile| {
+            let definition = Table::bundled().get(&unit);
+
+            definition.mobility_budget / definition.mobility_cost(&tile)
+        };
+        let grass_reach = |unit: Unit| reach(unit, Tile::GrassPlain);
+        let forest_reach = |unit: Unit| reach(unit, Tile::Forest);
+
+        assert!(grass_reach(Unit::Cavalry) > grass_reach(Unit::Tank));
+        assert!(forest_reach(Unit::Cavalry) < grass_reach(Unit::Cavalry) / 2.0);
     }
 }
@@ -0,0 +1,507 @@
+use crate::game::conditions::Conditions;
+use crate::game::weather::Weather;
+use crate::located::Located;
+use crate::map::Map;
+use crate::tile::Tile;
+use crate::unit::{Unit, ALL_UNITS};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+///////////////////////////////////////////////////////////////
+// Types
+///////////////////////////////////////////////////////////////
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Definition {
+    pub mobility_budget: f32,
+    pub visibility_budget: f32,
+    pub attack_strength: f32,
+    pub defense_strength: f32,
+    pub max_supplies: i16,
+    pub carrying_capacity: usize,
+    pub can_carry: Vec<Unit>,
+    pub replenishable: bool,
+    pub is_rideable: bool,
+    pub can_attack: bool,
+    // Enemies cannot move past this unit without stopping
+    // next to it
+    pub exerts_zone_of_control: bool,
+    pub can_pick_up_supply_crates: bool,
+    pub can_capture: bool,
+    pub can_capture_towns: bool,
+    pub capturable: bool,
+    pub is_supply_crate: bool,
+    // The cost of moving one tile, without any adjustments
+    // for terrain type
+    pub active_supply_cost: Option<f32>,
+    // The cost of just existing for one turn, whether
+    // the unit did anything or not
+    pub baseline_supply_cost: Option<f32>,
+    // The cost of spending a turn digging in. None
+    // means the unit cannot entrench
+    pub entrench_supply_cost: Option<f32>,
+    // How many tiles away the unit can bombard. None
+    // means the unit cannot bombard
+    pub bombard_range: Option<u16>,
+    pub bombard_supply_cost: Option<f32>,
+    // The cost of one turn of building or demolishing
+    // a structure. None means the unit cannot build
+    pub build_supply_cost: Option<f32>,
+    // The cost of fighting one battle, whether attacking
+    // or defending
+    pub battle_supply_cost: Option<f32>,
+    // What a wheeled unit pays to move along a road, in
+    // place of the terrain underneath. None means roads
+    // do nothing for this unit
+    pub road_mobility_cost: Option<f32>,
+    pub road_travel_cost: Option<f32>,
+    // How many times harder it is for this unit to get
+    // through mud, off the roads. None means mud does not
+    // slow this unit down
+    pub mud_mobility_multiplier: Option<f32>,
+    pub mud_travel_multiplier: Option<f32>,
+    // How many supplies a depot adds to its stockpile each
+    // turn. None means the unit is not a depot
+    pub supply_production: Option<i16>,
+    // How many supplies the unit can gather off the land in
+    // a turn spent standing still. None means it cannot forage
+    pub forage_supplies: Option<i16>,
+    pub mobility_costs: HashMap<Tile, f32>,
+    pub travel_costs: HashMap<Tile, f32>,
+}
+
+// None of the stats are ever NaN, so a definition is
+// always equal to itself
+impl Eq for Definition {}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct Table {
+    definitions: HashMap<Unit, Definition>,
+}
+
+const BUNDLED_SRC: &str = include_str!("units.txt");
+
+///////////////////////////////////////////////////////////////
+// Helpers
+///////////////////////////////////////////////////////////////
+
+impl Definition {
+    pub fn mobility_cost(&self, tile: &Tile) -> f32 {
+        self.mobility_costs
+            .get(tile)
+            .cloned()
+            .unwrap_or_else(|| tile.mobility_cost())
+    }
+
+    pub fn travel_supply_cost(&self, tile: &Tile) -> i16 {
+        let multiplier = self.travel_costs.get(tile).cloned().unwrap_or(1.0);
+
+        self.active_supply_cost_times(multiplier)
+    }
+
+    pub fn mobility_cost_at(&self, map: &Map, loc: &Located<()>, conditions: &Conditions) -> f32 {
+        match self.road_mobility_cost {
+            Some(cost) if map.has_road(loc) => cost,
+            _ => {
+                self.mobility_cost(&map.get_tile(loc))
+                    * self.mud(self.mud_mobility_multiplier, conditions)
+            }
+        }
+    }
+
+    pub fn travel_supply_cost_at(
+        &self,
+        map: &Map,
+        loc: &Located<()>,
+        conditions: &Conditions,
+    ) -> i16 {
+        let multiplier = match self.road_travel_cost {
+            Some(multiplier) if map.has_road(loc) => multiplier,
+            _ => {
+                let tile_multiplier = self
+                    .travel_costs
+                    .get(&map.get_tile(loc))
+                    .cloned()
+                    .unwrap_or(1.0);
+
+                tile_multiplier * self.mud(self.mud_travel_multiplier, conditions)
+            }
+        };
+
+        self.active_supply_cost_times(multiplier * conditions.time_of_day.travel_cost_multiplier())
+    }
+
+    // Roads keep units out of the mud, so this is only
+    // for units moving off of them
+    fn mud(&self, multiplier: Option<f32>, conditions: &Conditions) -> f32 {
+        match (&conditions.weather, multiplier) {
+            (Weather::Mud, Some(multiplier)) => multiplier,
+            _ => 1.0,
+        }
+    }
+
+    pub fn baseline_supply_cost_in(&self, conditions: &Conditions) -> Option<f32> {
+        self.baseline_supply_cost
+            .map(|cost| cost * conditions.weather.baseline_cost_multiplier())
+    }
+
+    fn active_supply_cost_times(&self, multiplier: f32) -> i16 {
+        match self.active_supply_cost {
+            Some(cost_per_tile) => (cost_per_tile * multiplier).floor() as i16,
+            None => 0,
+        }
+    }
+
+    fn from_stats(mut stats: HashMap<String, String>) -> Result<Definition, String> {
+        let mut mobility_costs = HashMap::new();
+        let mut travel_costs = HashMap::new();
+
+        for (key, value) in stats.iter() {
+            let costs = if let Some(tile_key) = key.strip_prefix("mobility_cost.") {
+                Some((&mut mobility_costs, tile_key))
+            } else {
+                key.strip_prefix("travel_cost.")
+                    .map(|tile_key| (&mut travel_costs, tile_key))
+            };
+
+            if let Some((costs, tile_key)) = costs {
+                costs.insert(parse_tile(tile_key)?, parse(key, value)?);
+            }
+        }
+
+        stats.retain(|key, _| {
+            !key.starts_with("mobility_cost.") && !key.starts_with("travel_cost.")
+        });
+
+        let mut take = |key: &str| -> Result<String, String> {
+            stats
+                .remove(key)
+                .ok_or_else(|| format!("missing stat {}", key))
+        };
+
+        let can_carry = take("can_carry")?
+            .split(',')
+            .map(|unit_key| unit_key.trim())
+            .filter(|unit_key| !unit_key.is_empty())
+            .map(parse_unit)
+            .collect::<Result<Vec<Unit>, String>>()?;
+
+        let definition = Definition {
+            mobility_budget: parse("mobility_budget", &take("mobility_budget")?)?,
+            visibility_budget: parse("visibility_budget", &take("visibility_budget")?)?,
+            attack_strength: parse("attack_strength", &take("attack_strength")?)?,
+            defense_strength: parse("defense_strength", &take("defense_strength")?)?,
+            max_supplies: parse("max_supplies", &take("max_supplies")?)?,
+            carrying_capacity: parse("carrying_capacity", &take("carrying_capacity")?)?,
+            can_carry,
+            replenishable: parse("replenishable", &take("replenishable")?)?,
+            is_rideable: parse("is_rideable", &take("is_rideable")?)?,
+            can_attack: parse("can_attack", &take("can_attack")?)?,
+            exerts_zone_of_control: parse(
+                "exerts_zone_of_control",
+                &take("exerts_zone_of_control")?,
+            )?,
+            can_pick_up_supply_crates: parse(
+                "can_pick_up_supply_crates",
+                &take("can_pick_up_supply_crates")?,
+            )?,
+            can_capture: parse("can_capture", &take("can_capture")?)?,
+            can_capture_towns: parse("can_capture_towns", &take("can_capture_towns")?)?,
+            capturable: parse("capturable", &take("capturable")?)?,
+            is_supply_crate: parse("is_supply_crate", &take("is_supply_crate")?)?,
+            active_supply_cost: parse_opt("active_supply_cost", &take("active_supply_cost")?)?,
+            baseline_supply_cost: parse_opt(
+                "baseline_supply_cost",
+                &take("baseline_supply_cost")?,
+            )?,
+            entrench_supply_cost: parse_opt(
+                "entrench_supply_cost",
+                &take("entrench_supply_cost")?,
+            )?,
+            bombard_range: parse_opt("bombard_range", &take("bombard_range")?)?,
+            bombard_supply_cost: parse_opt("bombard_supply_cost", &take("bombard_supply_cost")?)?,
+            build_supply_cost: parse_opt("build_supply_cost", &take("build_supply_cost")?)?,
+            battle_supply_cost: parse_opt("battle_supply_cost", &take("battle_supply_cost")?)?,
+            road_mobility_cost: parse_opt("road_mobility_cost", &take("road_mobility_cost")?)?,
+            road_travel_cost: parse_opt("road_travel_cost", &take("road_travel_cost")?)?,
+            mud_mobility_multiplier: parse_opt(
+                "mud_mobility_multiplier",
+                &take("mud_mobility_multiplier")?,
+            )?,
+            mud_travel_multiplier: parse_opt(
+                "mud_travel_multiplier",
+                &take("mud_travel_multiplier")?,
+            )?,
+            supply_production: parse_opt("supply_production", &take("supply_production")?)?,
+            forage_supplies: parse_opt("forage_supplies", &take("forage_supplies")?)?,
+            mobility_costs,
+            travel_costs,
+        };
+
+        let mut unknown_keys = stats.into_keys().collect::<Vec<String>>();
+        unknown_keys.sort();
+
+        match unknown_keys.first() {
+            Some(unknown_key) => Err(format!("unrecognized stat {}", unknown_key)),
+            None => Ok(definition),
+        }
+    }
+}
+
+impl Table {
+    // The unit definitions the game ships with
+    pub fn bundled() -> &'static Table {
+        static BUNDLED: OnceLock<Table> = OnceLock::new();
+
+        BUNDLED.get_or_init(|| Table::from_src(BUNDLED_SRC).unwrap())
+    }
+
+    pub fn from_src(src: &str) -> Result<Table, String> {
+        let mut sections: Vec<(Unit, HashMap<String, String>)> = vec![];
+
+        for (line_index, line) in src.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let line_err = |err: String| format!("line {}: {}", line_index + 1, err);
+
+            if let Some(unit_key) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let unit = parse_unit(unit_key).map_err(line_err)?;
+
+                if sections.iter().any(|(defined, _)| defined == &unit) {
+                    return Err(line_err(format!("{} is defined twice", unit_key)));
+                }
+
+                sections.push((unit, HashMap::new()));
+
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some(key_value) => key_value,
+                None => return Err(line_err("expected a stat like key = value".to_string())),
+            };
+
+            match sections.last_mut() {
+                Some((_, stats)) => {
+                    stats.insert(key.trim().to_string(), value.trim().to_string());
+                }
+                None => return Err(line_err("stat is not under a unit".to_string())),
+            }
+        }
+
+        let mut definitions = HashMap::new();
+
+        for (unit, stats) in sections {
+            let definition = Definition::from_stats(stats)
+                .map_err(|err| format!("{}: {}", unit.to_string(), err))?;
+
+            definitions.insert(unit, definition);
+        }
+
+        // Every unit is checked for a definition here, so
+        // looking one up never fails
+        for unit in ALL_UNITS.iter() {
+            if !definitions.contains_key(unit) {
+                return Err(format!("{} has no definition", unit.to_string()));
+            }
+        }
+
+        Ok(Table { definitions })
+    }
+
+    pub fn get(&self, unit: &Unit) -> &Definition {
+        &self.definitions[unit]
+    }
+}
+
+// Units are listed in the file by name, with
+// underscores in place of spaces
+fn parse_unit(key: &str) -> Result<Unit, String> {
+    ALL_UNITS
+        .iter()
+        .find(|unit| unit.to_string().replace(' ', "_") == key)
+        .cloned()
+        .ok_or_else(|| format!("unrecognized unit {}", key))
+}
+
+fn parse_tile(key: &str) -> Result<Tile, String> {
+    match key {
+        "grass_plain" => Ok(Tile::GrassPlain),
+        "hills" => Ok(Tile::Hills),
+        "forest" => Ok(Tile::Forest),
+        "water" => Ok(Tile::Water),
+        "shallows" => Ok(Tile::Shallows),
+        "town" => Ok(Tile::Town),
+        "fortification" => Ok(Tile::Fortification),
+        "cleared_path" => Ok(Tile::ClearedPath),
+        "bridge" => Ok(Tile::Bridge),
+        _ => Err(format!("unrecognized tile {}", key)),
+    }
+}
+
+fn parse<T: FromStr>(key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .map_err(|_| format!("could not read {} for {}", value, key))
+}
+
+fn parse_opt<T: FromStr>(key: &str, value: &str) -> Result<Option<T>, String> {
+    if value == "none" {
+        Ok(None)
+    } else {
+        parse(key, value).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test_unit_definitions {
+    use crate::game::conditions::Conditions;
+    use crate::game::day::TimeOfDay;
+    use crate::game::weather::Weather;
+    use crate::located;
+    use crate::map::{DevFlags, Map};
+    use crate::tile::Tile;
+    use crate::unit::definition::{Table, BUNDLED_SRC};
+    use crate::unit::Unit;
+    use pretty_assertions::assert_eq;
+    use std::convert::TryInto;
+
+    #[test]
+    fn bundled_table_parses() {
+        let table = Table::bundled();
+
+        assert_eq!(
+            Some(21.3333),
+            table.get(&Unit::Infantry).baseline_supply_cost
+        );
+        assert!(table.get(&Unit::Tank).can_carry.is_empty());
+        assert_eq!(2.5, table.get(&Unit::Tank).mobility_cost(&Tile::Forest));
+        assert_eq!(1.0, table.get(&Unit::Tank).mobility_cost(&Tile::GrassPlain));
+    }
+
+    #[test]
+    fn table_can_be_rebalanced() {
+        let src = BUNDLED_SRC.replace("mobility_budget = 6.0", "mobility_budget = 5.0");
+
+        let table = Table::from_src(src.as_str()).unwrap();
+
+        assert_eq!(5.0, table.get(&Unit::Tank).mobility_budget);
+    }
+
+    #[test]
+    fn table_rejects_incomplete_definitions() {
+        let src = BUNDLED_SRC.replace("build_supply_cost = 128.0\n", "");
+
+        assert_eq!(
+            Err("engineer: missing stat build_supply_cost".to_string()),
+            Table::from_src(src.as_str())
+        );
+    }
+
+    #[test]
+    fn roads_only_help_wheeled_units() {
+        let map: Map = DevFlags {
+            base_tile: Tile::GrassPlain,
+            src: "fFb".to_string(),
+        }
+        .try_into()
+        .unwrap();
+
+        let road = located::unit(0, 0);
+        let no_road = located::unit(1, 0);
+
+        let truck = Table::bundled().get(&Unit::Truck);
+        let infantry = Table::bundled().get(&Unit::Infantry);
+
+        assert!(
+            truck.mobility_cost_at(&map, &road, &Conditions::test())
+                < truck.mobility_cost_at(&map, &no_road, &Conditions::test())
+        );
+        assert!(
+            truck.travel_supply_cost_at(&map, &road, &Conditions::test())
+                < truck.travel_supply_cost_at(&map, &no_road, &Conditions::test())
+        );
+        assert_eq!(
+            infantry.mobility_cost_at(&map, &road, &Conditions::test()),
+            infantry.mobility_cost_at(&map, &no_road, &Conditions::test())
+        );
+    }
+
+    #[test]
+    fn roads_are_cut_with_their_bridge() {
+        let mut map: Map = DevFlags {
+            base_tile: Tile::GrassPlain,
+            src: "Gb".to_string(),
+        }
+        .try_into()
+        .unwrap();
+
+        let bridge = located::unit(1, 0);
+
+        assert!(map.has_road(&bridge));
+
+        map.demolish(&bridge).unwrap();
+
+        assert!(!map.has_road(&bridge));
+    }
+
+    #[test]
+    fn travelling_at_night_costs_more() {
+        let map = Map::grass_square();
+        let loc = located::unit(0, 0);
+        let infantry = Table::bundled().get(&Unit::Infantry);
+        let at_midnight = Conditions {
+            time_of_day: TimeOfDay::Midnight,
+            ..Conditions::test()
+        };
+
+        assert!(
+            infantry.travel_supply_cost_at(&map, &loc, &at_midnight)
+                > infantry.travel_supply_cost_at(&map, &loc, &Conditions::test())
+        );
+    }
+
+    #[test]
+    fn mud_bogs_down_trucks_but_not_on_roads() {
+        let map: Map = DevFlags {
+            base_tile: Tile::GrassPlain,
+            src: "Gg".to_string(),
+        }
+        .try_into()
+        .unwrap();
+
+        let off_road = located::unit(0, 0);
+        let road = located::unit(1, 0);
+
+        let in_mud = Conditions {
+            weather: Weather::Mud,
+            ..Conditions::test()
+        };
+
+        let truck = Table::bundled().get(&Unit::Truck);
+        let infantry = Table::bundled().get(&Unit::Infantry);
+
+        assert!(
+            truck.mobility_cost_at(&map, &off_road, &in_mud)
+                > truck.mobility_cost_at(&map, &off_road, &Conditions::test())
+        );
+        assert!(
+            truck.travel_supply_cost_at(&map, &off_road, &in_mud)
+                > truck.travel_supply_cost_at(&map, &off_road, &Conditions::test())
+        );
+        assert_eq!(
+            truck.mobility_cost_at(&map, &road, &in_mud),
+            truck.mobility_cost_at(&map, &road, &Conditions::test())
+        );
+        assert_eq!(
+            infantry.mobility_cost_at(&map, &off_road, &in_mud),
+            infantry.mobility_cost_at(&map, &off_road, &Conditions::test())
+        );
+    }
+}
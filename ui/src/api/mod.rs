@@ -1,6 +1,8 @@
 use seed::prelude::{fetch, Method, Request};
 use shared::api::endpoint::Endpoint;
 
+const UNPROCESSABLE_ENTITY: u16 = 422;
+
 async fn send_request(method: Method, url: String, bytes: Vec<u8>) -> fetch::Result<Vec<u8>> {
     Request::new(url.as_str())
         .method(method)
@@ -16,6 +18,26 @@ pub async fn post(endpoint: Endpoint, bytes: Vec<u8>) -> fetch::Result<Vec<u8>>
     send_request(Method::Post, endpoint.to_url(), bytes).await
 }
 
+// Like post, except that a request the server understood but
+// would not accept comes back as an Err holding the body, for
+// endpoints that explain what was wrong with the request
+pub async fn post_or_rejected(
+    endpoint: Endpoint,
+    bytes: Vec<u8>,
+) -> fetch::Result<Result<Vec<u8>, Vec<u8>>> {
+    let response = Request::new(endpoint.to_url().as_str())
+        .method(Method::Post)
+        .text(hex::encode(bytes))
+        .fetch()
+        .await?;
+
+    if response.status().code == UNPROCESSABLE_ENTITY {
+        return Ok(Err(response.bytes().await?));
+    }
+
+    Ok(Ok(response.check_status()?.bytes().await?))
+}
+
 pub async fn get(endpoint: Endpoint) -> fetch::Result<Vec<u8>> {
     Request::new(endpoint.to_url().as_str())
         .method(Method::Get)
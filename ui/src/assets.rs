@@ -23,6 +23,9 @@ pub enum MiscSpriteRow {
     GrassPlain,
     Hills,
     Forest,
+    Water,
+    Shallows,
+    Bridge,
     MobilitySpace,
     Arrow { arrow: ArrowRow, moved: bool },
     Cursor(ViewStyle),
@@ -177,6 +180,9 @@ impl MiscSpriteRow {
             MiscSpriteRow::GrassPlain => 0.0,
             MiscSpriteRow::Hills => 24.0,
             MiscSpriteRow::Forest => 25.0,
+            MiscSpriteRow::Water => 29.0,
+            MiscSpriteRow::Shallows => 30.0,
+            MiscSpriteRow::Bridge => 31.0,
             MiscSpriteRow::MobilitySpace => 3.0,
             MiscSpriteRow::Arrow { arrow, moved } => {
                 let mut r = match arrow {
@@ -239,6 +245,14 @@ impl From<&Tile> for MiscSpriteRow {
             Tile::GrassPlain => MiscSpriteRow::GrassPlain,
             Tile::Hills => MiscSpriteRow::Hills,
             Tile::Forest => MiscSpriteRow::Forest,
+            Tile::Water => MiscSpriteRow::Water,
+            Tile::Shallows => MiscSpriteRow::Shallows,
+            Tile::Bridge => MiscSpriteRow::Bridge,
+            // There are no sprites for towns or the other structures
+            // yet; towns are coloured in over grass when drawn
+            Tile::Town => MiscSpriteRow::GrassPlain,
+            Tile::Fortification => MiscSpriteRow::Hills,
+            Tile::ClearedPath => MiscSpriteRow::GrassPlain,
         }
     }
 }
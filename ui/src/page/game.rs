@@ -19,6 +19,7 @@ use crate::page::game::replenishment::Replenishment;
 use crate::page::game::stage::animating_moves;
 use crate::page::game::stage::taking_turn::Sidebar;
 use crate::page::game::unit_change::UnitChange;
+use crate::page::game::view::unit_row;
 use crate::page::game::view_style::ViewStyle;
 use crate::view::button::Button;
 use crate::view::card::Card;
@@ -27,8 +28,8 @@ use crate::web_sys::HtmlCanvasElement;
 use crate::{api, assets, core_ext, global, Row, Style, Toast};
 use seed::app::CmdHandle;
 use seed::prelude::{
-    cmds, el_ref, streams, At, El, ElRef, Ev, IndexMap, JsCast, Node, Orders, St, StreamHandle,
-    ToClasses, UpdateEl,
+    cmds, el_ref, streams, At, El, ElRef, Ev, IndexMap, JsCast, JsValue, Node, Orders, St,
+    StreamHandle, ToClasses, UpdateEl,
 };
 use seed::{attrs, canvas, div, style, C};
 use shared::api::endpoint::Endpoint;
@@ -37,10 +38,13 @@ use shared::arrow::Arrow;
 use shared::direction::Direction;
 use shared::facing_direction::FacingDirection;
 use shared::frame_count::FrameCount;
-use shared::game::{calculate_player_visibility, mobility, unit_index, Game, GameId, Turn};
+use shared::game::validation::Invalid;
+use shared::game::{
+    calculate_player_visibility, mobility, supply_line, unit_index, Game, GameId, Turn,
+};
 use shared::id::Id;
 use shared::located::Located;
-use shared::map::Map;
+use shared::map::{Map, Town};
 use shared::path::Path;
 use shared::point::Point;
 use shared::team_color::TeamColor;
@@ -74,13 +78,26 @@ fn set_to_moving_unit_mode(model: &mut Model, unit_id: UnitId) -> Result<(), Err
             }
         };
 
+        let visibility = model
+            .game
+            .get_players_visibility(&unit_model.owner)
+            .map_err(|err_msg| {
+                Error::new("could not get visibility of unit".to_string(), err_msg)
+            })?;
+
         return match mobility::get_units_mobility(
             &model.game.map,
             &unit_id.clone(),
             &model.game.indexes,
+            visibility,
+            &model.game.conditions(),
+            &model.game.unit_table,
         ) {
             Ok(mobility) => {
-                if !unit_model.unit.is_supply_crate() {
+                if !unit_model
+                    .definition(&model.game.unit_table)
+                    .is_supply_crate
+                {
                     sub_model.mode = Mode::MovingUnit(mode::moving::Model::init(unit_id, mobility));
                 }
 
@@ -96,8 +113,122 @@ fn set_to_moving_unit_mode(model: &mut Model, unit_id: UnitId) -> Result<(), Err
     Ok(())
 }
 
+fn set_to_bombarding_mode(model: &mut Model, unit_id: UnitId) -> Result<(), Error> {
+    if let Stage::TakingTurn(sub_model) = &mut model.stage {
+        let unit_model: &unit::Model = match model.game.get_unit(&unit_id) {
+            Some(u) => u,
+            None => {
+                return Ok(());
+            }
+        };
+
+        let (loc, range) = match (
+            unit_model.place.to_map_loc(),
+            unit_model.definition(&model.game.unit_table).bombard_range,
+        ) {
+            (Some(loc), Some(range)) => (loc, range),
+            _ => {
+                return Ok(());
+            }
+        };
+
+        let map = &model.game.map;
+
+        let mut targets = HashSet::new();
+
+        for x in loc.x.saturating_sub(range)..=(loc.x + range) {
+            for y in loc.y.saturating_sub(range)..=(loc.y + range) {
+                let target = located::unit(x, y);
+
+                if x < map.width && y < map.height && loc.distance_from(&target) <= range {
+                    targets.insert(target);
+                }
+            }
+        }
+
+        sub_model.mode = Mode::Bombarding(mode::bombarding::Model::init(unit_id, targets));
+
+        return draw_mode(model);
+    }
+
+    Ok(())
+}
+
+fn set_to_building_mode(
+    model: &mut Model,
+    unit_id: UnitId,
+    work: mode::building::Work,
+) -> Result<(), Error> {
+    if let Stage::TakingTurn(sub_model) = &mut model.stage {
+        let unit_model: &unit::Model = match model.game.get_unit(&unit_id) {
+            Some(u) => u,
+            None => {
+                return Ok(());
+            }
+        };
+
+        let loc = match unit_model.place.to_map_loc() {
+            Some(loc) => loc,
+            None => {
+                return Ok(());
+            }
+        };
+
+        let map = &model.game.map;
+
+        let mut targets = HashSet::new();
+
+        // Engineers work on their own tile or the ones
+        // right next to it
+        for x in loc.x.saturating_sub(1)..=(loc.x + 1) {
+            for y in loc.y.saturating_sub(1)..=(loc.y + 1) {
+                let target = located::unit(x, y);
+
+                if x >= map.width || y >= map.height || loc.distance_from(&target) > 1 {
+                    continue;
+                }
+
+                let workable = match &work {
+                    mode::building::Work::Build(structure) => {
+                        map.get_tile(&target).can_build(structure)
+                    }
+                    mode::building::Work::Demolish => map
+                        .structures
+                        .get(&target)
+                        .map(|structure| structure.owner.as_ref() != Some(&unit_model.owner))
+                        .unwrap_or(false),
+                };
+
+                if workable {
+                    targets.insert(target);
+                }
+            }
+        }
+
+        sub_model.mode = Mode::Building(mode::building::Model::init(unit_id, work, targets));
+
+        return draw_mode(model);
+    }
+
+    Ok(())
+}
+
 const MIN_RENDER_TIME: u32 = 256;
 
+const ROAD_COLOR: &str = "#837339";
+const ROAD_WIDTH: f64 = 4.0;
+
+const TOWN_COLOR: &str = "#9c8466";
+
+const TOWN_BORDER_WIDTH: f64 = 2.0;
+const NEUTRAL_TOWN_COLOR: &str = "#d8d8d8";
+const RED_TEAM_COLOR: &str = "#c83737";
+const BLUE_TEAM_COLOR: &str = "#3757c8";
+
+const SUPPLY_NETWORK_COLOR: &str = "rgba(255, 214, 64, 0.35)";
+
+const UNIT_MARKER_SIZE: f64 = 4.0;
+
 ///////////////////////////////////////////////////////////////
 // Types //
 ///////////////////////////////////////////////////////////////
@@ -127,6 +258,9 @@ pub struct Model {
     dialog: Option<Dialog>,
     status: Status,
     view_style: ViewStyle,
+    // The tiles the viewer's supply lines reach, while
+    // the overlay is switched on
+    supply_network: Option<HashSet<Located<()>>>,
     #[allow(dead_code)]
     key_press_stream: StreamHandle,
 }
@@ -166,7 +300,12 @@ pub enum Status {
 }
 
 enum Dialog {
-    ConfirmTurnSubmit,
+    ConfirmTurnSubmit {
+        all_units_moved: bool,
+        // Units that will not have enough supplies
+        // for their upkeep next turn
+        starving_units: Vec<String>,
+    },
 }
 
 impl Model {
@@ -258,6 +397,27 @@ impl Model {
         movable_units.is_subset(&moved_units)
     }
 
+    fn starving_units(&self, player_id: &Id) -> Vec<String> {
+        self.game
+            .get_units_by_player_id(player_id)
+            .unwrap_or(&vec![])
+            .iter()
+            .filter(|(unit_id, unit_model)| {
+                unit_row::forecast(unit_id, unit_model, &self.moves_index_by_unit, &self.game)
+                    .starves_next_turn()
+            })
+            .map(
+                |(unit_id, unit_model)| match self.unit_changes.get(unit_id) {
+                    Some(UnitChange::Name { name }) => name.clone(),
+                    _ => unit_model
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| unit_model.unit.to_string()),
+                },
+            )
+            .collect()
+    }
+
     fn is_ready(&self) -> bool {
         self.status == Status::Ready
     }
@@ -285,6 +445,7 @@ pub enum Msg {
     ClickedSubmitTurnConfirm,
     ClickedCancelSubmitTurn,
     GotTurnSubmitResponse(Box<Result<submit_turn::Response, String>>),
+    GotTurnRejection(Vec<Invalid>),
     GotGame(Box<Result<shared::api::game::get::Response, String>>),
     GameReloadTimeExpired,
     GroupSelectedSidebar(group_selected::Msg),
@@ -292,6 +453,7 @@ pub enum Msg {
     MovingFlyout(mode::moving::ClickMsg),
     EnterPressed,
     EscapePressed,
+    SPressed,
     ScrolledCanvasContainer(Result<Point<i16>, String>),
 }
 
@@ -335,6 +497,7 @@ pub fn init(
         match key.as_str() {
             "Enter" => Some(Msg::EnterPressed),
             "Escape" => Some(Msg::EscapePressed),
+            "s" => Some(Msg::SPressed),
             _ => None,
         }
     }));
@@ -397,6 +560,27 @@ pub fn init(
                 Action::Attack { unit_id, .. } => {
                     moves_index_ret.insert(unit_id.clone(), action.clone());
                 }
+                Action::Entrench { unit_id, .. } => {
+                    moves_index_ret.insert(unit_id.clone(), action.clone());
+                }
+                Action::Bombard { unit_id, .. } => {
+                    moves_index_ret.insert(unit_id.clone(), action.clone());
+                }
+                Action::Build { unit_id, .. } => {
+                    moves_index_ret.insert(unit_id.clone(), action.clone());
+                }
+                Action::Demolish { unit_id, .. } => {
+                    moves_index_ret.insert(unit_id.clone(), action.clone());
+                }
+                Action::TransferSupplies { unit_id, .. } => {
+                    moves_index_ret.insert(unit_id.clone(), action.clone());
+                }
+                Action::SplitCrate { crate_id, .. } => {
+                    moves_index_ret.insert(crate_id.clone(), action.clone());
+                }
+                Action::MergeCrates { crate_id, .. } => {
+                    moves_index_ret.insert(crate_id.clone(), action.clone());
+                }
             }
         }
 
@@ -431,6 +615,7 @@ pub fn init(
         dialog: None,
         status: Status::Ready,
         view_style,
+        supply_network: None,
         key_press_stream,
     };
 
@@ -526,7 +711,11 @@ pub fn update(
 
             if let Stage::AnimatingMoves(sub_model) = &mut model.stage {
                 match sub_model
-                    .progress_animation(&global.viewer_id(), &model.game.map)
+                    .progress_animation(
+                        &global.viewer_id(),
+                        &model.game.map,
+                        &model.game.unit_table,
+                    )
                     .map_err(|err_msg| Error::new("progressing animation".to_string(), err_msg))
                 {
                     Ok(finished) => {
@@ -547,10 +736,16 @@ pub fn update(
             model.handle_minimum_framerate_timeout = wait_for_render_timeout(orders);
         }
         Msg::ClickedSubmitTurn => {
-            if model.all_units_moved(global.viewer_id()) {
+            let all_units_moved = model.all_units_moved(global.viewer_id());
+            let starving_units = model.starving_units(&global.viewer_id());
+
+            if all_units_moved && starving_units.is_empty() {
                 submit_turn(global, model, orders);
             } else {
-                model.dialog = Some(Dialog::ConfirmTurnSubmit);
+                model.dialog = Some(Dialog::ConfirmTurnSubmit {
+                    all_units_moved,
+                    starving_units,
+                });
             }
         }
         Msg::ClickedSubmitTurnConfirm => {
@@ -566,7 +761,14 @@ pub fn update(
                 };
                 model.status = Status::Ready;
 
+                let turn_errors = res.get_turn_errors(&global.viewer_id());
+                let new_turn = model.game.turn_number != res.game.turn_number;
+
                 refetch_game(&global.viewer_id(), model, res.game, orders);
+
+                if new_turn {
+                    toast_turn_errors(global, turn_errors);
+                }
             }
             Err(err) => {
                 global.toast(
@@ -576,9 +778,47 @@ pub fn update(
                 );
             }
         },
+        Msg::GotTurnRejection(invalids) => {
+            model.status = Status::Ready;
+
+            let more_info = invalids
+                .iter()
+                .map(|invalid| {
+                    let unit_name = invalid
+                        .unit_id()
+                        .and_then(|unit_id| model.game.get_unit(unit_id))
+                        .map(|unit_model| {
+                            unit_model
+                                .name
+                                .clone()
+                                .unwrap_or_else(|| unit_model.unit.to_string())
+                        });
+
+                    match unit_name {
+                        Some(unit_name) => format!("{} : {}", unit_name, invalid.to_string()),
+                        None => invalid.to_string(),
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+
+            global.toast(
+                Toast::init("error", "some of your orders were not accepted")
+                    .error()
+                    .with_more_info(more_info),
+            );
+        }
         Msg::GotGame(result) => match *result {
             Ok(res) => {
-                refetch_game(&global.viewer_id(), model, res.get_game(), orders);
+                let turn_errors = res.get_turn_errors(&global.viewer_id());
+                let fetched_game = res.get_game();
+                let new_turn = model.game.turn_number != fetched_game.turn_number;
+
+                refetch_game(&global.viewer_id(), model, fetched_game, orders);
+
+                if new_turn {
+                    toast_turn_errors(global, turn_errors);
+                }
             }
             Err(err) => {
                 global.toast(
@@ -626,11 +866,21 @@ pub fn update(
         Msg::EnterPressed => match &model.dialog {
             None => {}
             Some(dialog) => match dialog {
-                Dialog::ConfirmTurnSubmit => {
+                Dialog::ConfirmTurnSubmit { .. } => {
                     submit_turn(global, model, orders);
                 }
             },
         },
+        Msg::SPressed => {
+            model.supply_network = match model.supply_network {
+                Some(_) => None,
+                None => Some(supply_network(&global.viewer_id(), &model.game)),
+            };
+
+            if let Err(error) = draw_mode(model) {
+                global.toast_error(error);
+            }
+        }
         Msg::EscapePressed => {
             model.dialog = None;
             match &mut model.stage {
@@ -730,6 +980,9 @@ fn handle_unit_selected_sidebar_msg(
         unit_selected::Msg::UpdatedUnitNameField(new_field) => {
             sub_model.name_field = new_field;
         }
+        unit_selected::Msg::UpdatedSupplyAmountField(new_field) => {
+            sub_model.supply_amount_field = new_field;
+        }
         unit_selected::Msg::ClickedSetName => {
             if !sub_model.name_submitted {
                 sub_model.name_submitted = true;
@@ -749,9 +1002,70 @@ fn handle_unit_selected_sidebar_msg(
                 draw_mode(model)?;
             }
         }
+        unit_selected::Msg::ClickedEntrench(against) => {
+            model.moves_index_by_unit.insert(
+                sub_model.unit_id.clone(),
+                Action::Entrench {
+                    unit_id: sub_model.unit_id.clone(),
+                    against,
+                },
+            );
+
+            return model.clear_mode_and_sidebar();
+        }
+        unit_selected::Msg::ClickedBombard => {
+            return set_to_bombarding_mode(model, sub_model.unit_id.clone());
+        }
+        unit_selected::Msg::ClickedBuild(structure) => {
+            return set_to_building_mode(
+                model,
+                sub_model.unit_id.clone(),
+                mode::building::Work::Build(structure),
+            );
+        }
+        unit_selected::Msg::ClickedDemolish => {
+            return set_to_building_mode(
+                model,
+                sub_model.unit_id.clone(),
+                mode::building::Work::Demolish,
+            );
+        }
+        unit_selected::Msg::ClickedTransferSupplies(receiving_unit_id, amount) => {
+            model.moves_index_by_unit.insert(
+                sub_model.unit_id.clone(),
+                Action::TransferSupplies {
+                    unit_id: sub_model.unit_id.clone(),
+                    receiving_unit_id,
+                    amount,
+                },
+            );
+
+            return model.clear_mode_and_sidebar();
+        }
+        unit_selected::Msg::ClickedSplitCrate(crate_id, amount) => {
+            model
+                .moves_index_by_unit
+                .insert(crate_id.clone(), Action::SplitCrate { crate_id, amount });
+
+            return model.clear_mode_and_sidebar();
+        }
+        unit_selected::Msg::ClickedMergeCrates(into_crate_id) => {
+            model.moves_index_by_unit.insert(
+                sub_model.unit_id.clone(),
+                Action::MergeCrates {
+                    crate_id: sub_model.unit_id.clone(),
+                    into_crate_id,
+                },
+            );
+
+            return model.clear_mode_and_sidebar();
+        }
         unit_selected::Msg::UnitRow(view::unit_row::Msg::Clicked(cargo_unit_id)) => {
             if let Some(cargo_unit_model) = model.game.get_unit(&cargo_unit_id) {
-                if cargo_unit_model.unit.is_supply_crate() {
+                if cargo_unit_model
+                    .definition(&model.game.unit_table)
+                    .is_supply_crate
+                {
                     model.moves_index_by_unit.insert(
                         cargo_unit_id.clone(),
                         Action::DropOff {
@@ -870,6 +1184,7 @@ fn handle_moving_flyout_msg(
                 &sub_model.unit_id,
                 replenishment_pos,
                 &model.game.indexes,
+                &model.game.unit_table,
             ) {
                 Ok(replenishment) => {
                     model.moves_index_by_unit.insert(
@@ -923,14 +1238,23 @@ fn refetch_game(
                     .flat_map(Animation::from_event)
                     .collect::<Vec<Animation>>();
 
-                let visibility =
-                    calculate_player_visibility(viewer_id, &model.game.map, &indices.by_id);
+                let visibility = calculate_player_visibility(
+                    viewer_id,
+                    &model.game.map,
+                    &indices.by_id,
+                    &model.game.conditions(),
+                    &model.game.unit_table,
+                );
 
+                // Towns keep their old owners until the
+                // capture is animated
                 let sub_model = animating_moves::Model::init(
                     indices.clone(),
                     animations,
                     visibility,
+                    model.game.map.towns.clone(),
                     model.game.day(),
+                    model.game.conditions(),
                 );
 
                 Stage::AnimatingMoves(sub_model)
@@ -942,7 +1266,41 @@ fn refetch_game(
         model.moves = Vec::new();
     }
 
+    // Engineers can change the map from one turn to the next
+    let terrain_changed = model.game.map != fetched_game.map;
+
     model.game = fetched_game;
+
+    if model.supply_network.is_some() {
+        model.supply_network = Some(supply_network(viewer_id, &model.game));
+    }
+
+    if terrain_changed {
+        draw_terrain(model);
+    }
+}
+
+fn supply_network(viewer_id: &Id, game: &Game) -> HashSet<Located<()>> {
+    supply_line::network(
+        viewer_id,
+        &game.indexes,
+        &game.map,
+        &game.conditions(),
+        &game.unit_table,
+    )
+    .reach
+}
+
+// Let the player know which of their orders could
+// not be carried out last turn
+fn toast_turn_errors(global: &mut global::Model, turn_errors: Vec<String>) {
+    if !turn_errors.is_empty() {
+        global.toast(
+            Toast::init("error", "some of your orders failed")
+                .error()
+                .with_more_info(turn_errors.join("\n")),
+        );
+    }
 }
 
 fn submit_turn(global: &mut global::Model, model: &mut Model, orders: &mut impl Orders<Msg>) {
@@ -951,50 +1309,7 @@ fn submit_turn(global: &mut global::Model, model: &mut Model, orders: &mut impl
     let req_moves: Vec<game::action::Action> = model
         .moves_index_by_unit
         .iter()
-        .map(|(unit_id, action)| match action {
-            Action::TraveledTo {
-                path,
-                dismounted_from,
-                ..
-            } => game::action::Action::Travel {
-                unit_id: unit_id.clone(),
-                path: path.clone(),
-                dismounted_from: dismounted_from.clone(),
-            },
-            Action::LoadInto {
-                load_into, path, ..
-            } => game::action::Action::LoadInto {
-                unit_id: unit_id.clone(),
-                load_into: load_into.clone(),
-                path: path.clone(),
-            },
-            Action::PickUp { cargo_id, path, .. } => game::action::Action::PickUp {
-                unit_id: unit_id.clone(),
-                cargo_id: cargo_id.clone(),
-                path: path.clone(),
-            },
-            Action::DropOff { cargo_id } => game::action::Action::DropOff {
-                cargo_id: cargo_id.clone(),
-            },
-            Action::Replenish {
-                replenishing_unit_id,
-                units,
-                depleted_supply_crates,
-                path,
-                ..
-            } => game::action::Action::Replenish {
-                replenishing_unit_id: replenishing_unit_id.clone(),
-                units: units.clone(),
-                depleted_supply_crates: depleted_supply_crates.clone(),
-                path: path.clone(),
-            },
-            Action::Attack { unit_id, path, .. } => {
-                game::action::Action::Attack(game::action::Attack {
-                    unit_id: unit_id.clone(),
-                    path: path.clone(),
-                })
-            }
-        })
+        .map(|(unit_id, action)| action.to_game_action(unit_id))
         .collect();
 
     let req_changes: Vec<game::Change> = model
@@ -1018,9 +1333,17 @@ fn submit_turn(global: &mut global::Model, model: &mut Model, orders: &mut impl
 
             orders.skip().perform_cmd({
                 async {
-                    let result = match api::post(url, bytes).await {
-                        Ok(res_bytes) => submit_turn::Response::from_bytes(res_bytes)
+                    let result = match api::post_or_rejected(url, bytes).await {
+                        Ok(Ok(res_bytes)) => submit_turn::Response::from_bytes(res_bytes)
                             .map_err(|err| err.to_string()),
+                        Ok(Err(rejection_bytes)) => {
+                            match submit_turn::Rejection::from_bytes(rejection_bytes) {
+                                Ok(rejection) => {
+                                    return Msg::GotTurnRejection(rejection.invalids);
+                                }
+                                Err(err) => Err(err.to_string()),
+                            }
+                        }
                         Err(error) => {
                             let fetch_error = core_ext::http::fetch_error_to_string(error);
                             Err(fetch_error)
@@ -1063,6 +1386,46 @@ fn handle_click_on_screen_during_turn(
                     model.clear_mode_and_sidebar()
                 }
             }
+            Mode::Bombarding(bombarding_model) => {
+                let mouse_loc = located::unit(x, y);
+
+                if bombarding_model.targets.contains(&mouse_loc) {
+                    let unit_id = bombarding_model.unit_id.clone();
+
+                    model.moves_index_by_unit.insert(
+                        unit_id.clone(),
+                        Action::Bombard {
+                            unit_id,
+                            target: mouse_loc,
+                        },
+                    );
+                }
+
+                model.clear_mode_and_sidebar()
+            }
+            Mode::Building(building_model) => {
+                let mouse_loc = located::unit(x, y);
+
+                if building_model.targets.contains(&mouse_loc) {
+                    let unit_id = building_model.unit_id.clone();
+
+                    let action = match &building_model.work {
+                        mode::building::Work::Build(structure) => Action::Build {
+                            unit_id: unit_id.clone(),
+                            target: mouse_loc,
+                            structure: structure.clone(),
+                        },
+                        mode::building::Work::Demolish => Action::Demolish {
+                            unit_id: unit_id.clone(),
+                            target: mouse_loc,
+                        },
+                    };
+
+                    model.moves_index_by_unit.insert(unit_id, action);
+                }
+
+                model.clear_mode_and_sidebar()
+            }
         };
     }
 
@@ -1137,7 +1500,11 @@ fn handle_click_on_screen_when_move_mode(
             .get_players_visibility(&viewer_id)
             .map_err(|err| Error::new(error_title, err))?;
 
-        if unit_model.unit.can_pick_up_supply_crates() && visibility.contains(mouse_loc) {
+        if unit_model
+            .definition(&model.game.unit_table)
+            .can_pick_up_supply_crates
+            && visibility.contains(mouse_loc)
+        {
             if let Some(supply_crates) = model.game.get_supply_crates_by_location(mouse_loc) {
                 ride_options.append(
                     &mut supply_crates
@@ -1160,7 +1527,11 @@ fn handle_click_on_screen_when_move_mode(
         if let Some(cargo) = model.game.indexes.by_transport.get(&unit_id) {
             let crates = cargo
                 .iter()
-                .filter(|(_, cargo_unit_model)| cargo_unit_model.unit.is_supply_crate())
+                .filter(|(_, cargo_unit_model)| {
+                    cargo_unit_model
+                        .definition(&model.game.unit_table)
+                        .is_supply_crate
+                })
                 .collect::<Vec<_>>();
 
             if !crates.is_empty() {
@@ -1175,7 +1546,11 @@ fn handle_click_on_screen_when_move_mode(
                         .map(|units| {
                             let filtered_units = units
                                 .iter()
-                                .map(|(_, _, unit_model)| !unit_model.unit.is_supply_crate())
+                                .map(|(_, _, unit_model)| {
+                                    !unit_model
+                                        .definition(&model.game.unit_table)
+                                        .is_supply_crate
+                                })
                                 .collect::<Vec<_>>();
 
                             filtered_units.is_empty()
@@ -1185,7 +1560,7 @@ fn handle_click_on_screen_when_move_mode(
             }
         }
 
-        if unit_model.unit.can_attack() {
+        if unit_model.definition(&model.game.unit_table).can_attack {
             ride_options.push(mode::moving::RideOption::Attack);
         }
 
@@ -1281,6 +1656,8 @@ fn handle_mouse_move_for_mode(model: &mut Model, mouse_loc: Located<()>) -> Resu
 
     match mode {
         Mode::None => {}
+        Mode::Bombarding(_) => {}
+        Mode::Building(_) => {}
         Mode::MovingUnit(moving_model) => {
             if moving_model.ride_options.is_some() {
                 return Ok(());
@@ -1333,7 +1710,7 @@ fn handle_mouse_move_for_mode(model: &mut Model, mouse_loc: Located<()>) -> Resu
                         mouse_point,
                         &mobility,
                         existing_path,
-                        unit_model.unit.mobility_budget().ceil() as usize,
+                        unit_model.mobility_budget(&model.game.unit_table).ceil() as usize,
                     )
                     .map_err(|msg| Error::new("calculate arrow".to_string(), msg))?
                 };
@@ -1369,6 +1746,13 @@ fn draw(viewer_id: &Id, model: &Model) -> Result<(), Error> {
 
     draw_units(visibility, model);
 
+    let towns = match &model.stage {
+        Stage::AnimatingMoves(sub_model) => &sub_model.towns,
+        _ => &model.game.map.towns,
+    };
+
+    draw_town_owners(towns, model);
+
     draw_visibility(visibility, model);
 
     Ok(())
@@ -1430,6 +1814,26 @@ fn draw_mode(model: &Model) -> Result<(), Error> {
 
     match &mode {
         Mode::None => {}
+        Mode::Bombarding(bombarding_model) => {
+            for target in bombarding_model.targets.iter() {
+                let _ = model.assets.draw_misc_sprite(
+                    &ctx,
+                    MiscSpriteRow::MobilitySpace,
+                    target.x,
+                    target.y,
+                );
+            }
+        }
+        Mode::Building(building_model) => {
+            for target in building_model.targets.iter() {
+                let _ = model.assets.draw_misc_sprite(
+                    &ctx,
+                    MiscSpriteRow::MobilitySpace,
+                    target.x,
+                    target.y,
+                );
+            }
+        }
         Mode::MovingUnit(moving_model) => {
             let error_title = "rendering mobility range".to_string();
             for mobility_space in moving_model.mobility.iter() {
@@ -1463,9 +1867,31 @@ fn draw_mode(model: &Model) -> Result<(), Error> {
         }
     }
 
+    if let Some(supply_network) = &model.supply_network {
+        draw_supply_network(&ctx, supply_network);
+    }
+
     Ok(())
 }
 
+// Tint every tile the supply lines reach, so players
+// can see which ground their depots can support
+fn draw_supply_network(
+    ctx: &web_sys::CanvasRenderingContext2d,
+    supply_network: &HashSet<Located<()>>,
+) {
+    ctx.set_fill_style(&JsValue::from_str(SUPPLY_NETWORK_COLOR));
+
+    for loc in supply_network.iter() {
+        ctx.fill_rect(
+            loc.x as f64 * tile::PIXEL_WIDTH_FL,
+            loc.y as f64 * tile::PIXEL_HEIGHT_FL,
+            tile::PIXEL_WIDTH_FL,
+            tile::PIXEL_HEIGHT_FL,
+        );
+    }
+}
+
 fn draw_arrow(
     arrow_canvas_ctx: &web_sys::CanvasRenderingContext2d,
     assets: &assets::Model,
@@ -1675,8 +2101,18 @@ fn draw_units(visibility: &HashSet<Located<()>>, model: &Model) {
                 let mut sy = match unit_model.unit {
                     Unit::Infantry => 0.0,
                     Unit::Tank => 2.0,
+                    Unit::Cavalry => 8.0,
                     Unit::Truck => 4.0,
+                    // There are no artillery sprites yet, so
+                    // draw_unit_marker tells them apart from tanks
+                    Unit::Artillery => 2.0,
+                    // Nor engineer sprites, so they are
+                    // marked apart from infantry
+                    Unit::Engineer => 0.0,
                     Unit::SupplyCrate => 6.0,
+                    // Depots are drawn as crates until they get a sprite,
+                    // and draw_unit_marker tells them apart
+                    Unit::SupplyDepot => 6.0,
                 };
 
                 if unit_model.color == TeamColor::Blue {
@@ -1712,15 +2148,20 @@ fn draw_units(visibility: &HashSet<Located<()>>, model: &Model) {
                     y,
                 );
 
+                draw_unit_marker(&ctx, &model.view_style, &unit_model.unit, x, y);
+
                 draw_units_move(maybe_units_move);
 
                 if let Some(loaded_units) = indices.by_transport.get(unit_id) {
-                    let misc_sheet_row =
-                        if loaded_units.len() >= unit_model.unit.carrying_capacity() {
-                            MiscSpriteRow::FullyLoadedCargoIndicator
-                        } else {
-                            MiscSpriteRow::PartiallyLoadedCargoIndicator
-                        };
+                    let misc_sheet_row = if loaded_units.len()
+                        >= unit_model
+                            .definition(&model.game.unit_table)
+                            .carrying_capacity
+                    {
+                        MiscSpriteRow::FullyLoadedCargoIndicator
+                    } else {
+                        MiscSpriteRow::PartiallyLoadedCargoIndicator
+                    };
 
                     let multiplier = match model.view_style {
                         ViewStyle::Normal => 1,
@@ -1750,7 +2191,8 @@ fn draw_units(visibility: &HashSet<Located<()>>, model: &Model) {
                     }
                 };
 
-                let supplies_below_25_percent = 0.25 > unit_model.supplies_percent();
+                let supplies_below_25_percent =
+                    0.25 > unit_model.supplies_percent(&model.game.unit_table);
 
                 match model.frame_count {
                     FrameCount::F1 | FrameCount::F2 => {
@@ -1795,6 +2237,14 @@ fn draw_units(visibility: &HashSet<Located<()>>, model: &Model) {
                         mini_x,
                         mini_y,
                     );
+
+                    draw_unit_marker(
+                        &mini_units_ctx,
+                        &model.view_style,
+                        &unit_model.unit,
+                        mini_x,
+                        mini_y,
+                    );
                 }
             } else {
                 let mut colors = HashSet::new();
@@ -1911,6 +2361,145 @@ fn draw_terrain(model: &Model) {
             }
         }
     }
+
+    let scale = match model.view_style {
+        ViewStyle::Normal => 1.0,
+        ViewStyle::TinySpacedUnits => 2.0,
+        ViewStyle::SpacedUnits => 2.0,
+    };
+
+    for row in grid {
+        for loc_tile in row {
+            draw_tile_fill(&ctx, loc_tile, scale);
+        }
+    }
+
+    for road_loc in model.game.map.roads.iter() {
+        draw_road(&ctx, &model.game.map, road_loc, scale);
+    }
+}
+
+// Tiles without a sprite of their own are coloured in
+// over the grass beneath them
+fn draw_tile_fill(ctx: &web_sys::CanvasRenderingContext2d, loc_tile: &Located<Tile>, scale: f64) {
+    let color = match loc_tile.value {
+        Tile::Town => TOWN_COLOR,
+        _ => return,
+    };
+
+    let tile_size = tile::PIXEL_WIDTH_FL * scale;
+
+    ctx.set_fill_style(&JsValue::from_str(color));
+    ctx.fill_rect(
+        loc_tile.x as f64 * tile_size,
+        loc_tile.y as f64 * tile_size,
+        tile_size,
+        tile_size,
+    );
+}
+
+// Roads are drawn as a band through the middle of the tile,
+// reaching out to whichever neighbours also have a road
+fn draw_road(ctx: &web_sys::CanvasRenderingContext2d, map: &Map, loc: &Located<()>, scale: f64) {
+    if !map.has_road(loc) {
+        return;
+    }
+
+    let tile_size = tile::PIXEL_WIDTH_FL * scale;
+    let road_width = ROAD_WIDTH * scale;
+    let inset = (tile_size - road_width) / 2.0;
+    let left = loc.x as f64 * tile_size;
+    let top = loc.y as f64 * tile_size;
+
+    ctx.set_fill_style(&JsValue::from_str(ROAD_COLOR));
+    ctx.fill_rect(left + inset, top + inset, road_width, road_width);
+
+    if loc.y > 0 && map.has_road(&located::unit(loc.x, loc.y - 1)) {
+        ctx.fill_rect(left + inset, top, road_width, inset);
+    }
+
+    if map.has_road(&located::unit(loc.x, loc.y + 1)) {
+        ctx.fill_rect(left + inset, top + inset + road_width, road_width, inset);
+    }
+
+    if loc.x > 0 && map.has_road(&located::unit(loc.x - 1, loc.y)) {
+        ctx.fill_rect(left, top + inset, inset, road_width);
+    }
+
+    if map.has_road(&located::unit(loc.x + 1, loc.y)) {
+        ctx.fill_rect(left + inset + road_width, top + inset, inset, road_width);
+    }
+}
+
+// Towns are outlined in the colour of whoever holds them
+fn draw_town_owners(towns: &HashMap<Located<()>, Town>, model: &Model) {
+    let ctx = match model.units_canvas.get() {
+        Some(c) => seed::canvas_context_2d(&c),
+        None => {
+            return;
+        }
+    };
+
+    let scale = match model.view_style {
+        ViewStyle::Normal => 1.0,
+        ViewStyle::TinySpacedUnits => 2.0,
+        ViewStyle::SpacedUnits => 2.0,
+    };
+
+    let tile_size = tile::PIXEL_WIDTH_FL * scale;
+    let border = TOWN_BORDER_WIDTH * scale;
+
+    for (loc, town) in towns.iter() {
+        let color = match town
+            .owner
+            .as_ref()
+            .and_then(|owner| model.game.get_players_color(owner))
+        {
+            Some(TeamColor::Red) => RED_TEAM_COLOR,
+            Some(TeamColor::Blue) => BLUE_TEAM_COLOR,
+            None => NEUTRAL_TOWN_COLOR,
+        };
+
+        let left = loc.x as f64 * tile_size;
+        let top = loc.y as f64 * tile_size;
+
+        ctx.set_fill_style(&JsValue::from_str(color));
+        ctx.fill_rect(left, top, tile_size, border);
+        ctx.fill_rect(left, top + tile_size - border, tile_size, border);
+        ctx.fill_rect(left, top, border, tile_size);
+        ctx.fill_rect(left + tile_size - border, top, border, tile_size);
+    }
+}
+
+// Units that borrow another unit's sprite get a small
+// coloured square in the corner so they can be told apart
+fn draw_unit_marker(
+    ctx: &web_sys::CanvasRenderingContext2d,
+    view_style: &ViewStyle,
+    unit: &Unit,
+    x: f64,
+    y: f64,
+) {
+    let color = match unit {
+        Unit::Artillery => "#d03030",
+        Unit::Engineer => "#e08020",
+        Unit::SupplyDepot => "#30a040",
+        _ => return,
+    };
+
+    let (multiplier, adjustment) = match view_style {
+        ViewStyle::Normal => (1.0, 0.0),
+        ViewStyle::TinySpacedUnits => (2.0, tile::PIXEL_WIDTH_FL / 2.0),
+        ViewStyle::SpacedUnits => (2.0, tile::PIXEL_WIDTH_FL / 2.0),
+    };
+
+    ctx.set_fill_style(&JsValue::from_str(color));
+    ctx.fill_rect(
+        x * multiplier + adjustment,
+        y * multiplier + adjustment,
+        UNIT_MARKER_SIZE,
+        UNIT_MARKER_SIZE,
+    );
 }
 
 ///////////////////////////////////////////////////////////////
@@ -2257,11 +2846,17 @@ fn sidebar_content(viewer_id: Id, model: &Model) -> Vec<Cell<Msg>> {
 }
 
 fn day_view(model: &Model) -> Cell<Msg> {
-    let day = match &model.stage {
-        Stage::AnimatingMoves(sub_model) => sub_model.day.clone(),
-        _ => model.game.day(),
+    let (day, weather) = match &model.stage {
+        Stage::AnimatingMoves(sub_model) => {
+            (sub_model.day.clone(), sub_model.conditions.weather.clone())
+        }
+        _ => (model.game.day(), model.game.weather.clone()),
     };
 
+    let mut label = day.to_string();
+    label.push_str(", ");
+    label.push_str(weather.to_string().as_str());
+
     Cell::from_str(
         vec![
             Style::Absolute,
@@ -2272,7 +2867,7 @@ fn day_view(model: &Model) -> Cell<Msg> {
             Style::BorderBContent0,
             Style::BorderLContent2,
         ],
-        day.to_string().as_str(),
+        label.as_str(),
     )
 }
 
@@ -2280,11 +2875,35 @@ fn dialog_view(model: &Model) -> Cell<Msg> {
     match &model.dialog {
         None => Cell::none(),
         Some(dialog) => match dialog {
-            Dialog::ConfirmTurnSubmit => {
-                let message = "You have not moved all your units, are you sure you would like to submit your turn?";
+            Dialog::ConfirmTurnSubmit {
+                all_units_moved,
+                starving_units,
+            } => {
+                let unmoved_message = if *all_units_moved {
+                    Cell::none()
+                } else {
+                    Cell::from_str(vec![], "You have not moved all your units.")
+                };
 
-                let card_content =
-                    Row::from_cells(vec![Style::W9], vec![Cell::from_str(vec![], message)]);
+                let starving_message = if starving_units.is_empty() {
+                    Cell::none()
+                } else {
+                    let message = format!(
+                        "These units will run out of supplies next turn: {}.",
+                        starving_units.join(", ")
+                    );
+
+                    Cell::from_str(vec![Style::TextImportant4], message.as_str())
+                };
+
+                let card_content = Row::from_cells(
+                    vec![Style::W9, Style::FlexCol, Style::G4],
+                    vec![
+                        unmoved_message,
+                        starving_message,
+                        Cell::from_str(vec![], "Are you sure you would like to submit your turn?"),
+                    ],
+                );
 
                 Cell::group(
                     vec![
@@ -1,7 +1,9 @@
 use shared::arrow::Arrow;
 use shared::direction::Direction;
 use shared::game;
+use shared::located::Located;
 use shared::path::Path;
+use shared::tile::Tile;
 use shared::unit::UnitId;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -39,6 +41,36 @@ pub enum Action {
         path: Path,
         arrows: Vec<(Direction, Arrow)>,
     },
+    Entrench {
+        unit_id: UnitId,
+        against: Direction,
+    },
+    Bombard {
+        unit_id: UnitId,
+        target: Located<()>,
+    },
+    Build {
+        unit_id: UnitId,
+        target: Located<()>,
+        structure: Tile,
+    },
+    Demolish {
+        unit_id: UnitId,
+        target: Located<()>,
+    },
+    TransferSupplies {
+        unit_id: UnitId,
+        receiving_unit_id: UnitId,
+        amount: i16,
+    },
+    SplitCrate {
+        crate_id: UnitId,
+        amount: i16,
+    },
+    MergeCrates {
+        crate_id: UnitId,
+        into_crate_id: UnitId,
+    },
 }
 
 impl Action {
@@ -108,12 +140,138 @@ impl Action {
                         path: path.clone(),
                         arrows: path.with_arrows(),
                     }),
+                game::action::Action::Entrench { unit_id, against } => {
+                    moves_ret.push(Action::Entrench { unit_id, against })
+                }
+                game::action::Action::Bombard { unit_id, target } => {
+                    moves_ret.push(Action::Bombard { unit_id, target })
+                }
+                game::action::Action::Build {
+                    unit_id,
+                    target,
+                    structure,
+                } => moves_ret.push(Action::Build {
+                    unit_id,
+                    target,
+                    structure,
+                }),
+                game::action::Action::Demolish { unit_id, target } => {
+                    moves_ret.push(Action::Demolish { unit_id, target })
+                }
+                game::action::Action::TransferSupplies {
+                    unit_id,
+                    receiving_unit_id,
+                    amount,
+                } => moves_ret.push(Action::TransferSupplies {
+                    unit_id,
+                    receiving_unit_id,
+                    amount,
+                }),
+                game::action::Action::SplitCrate { crate_id, amount } => {
+                    moves_ret.push(Action::SplitCrate { crate_id, amount })
+                }
+                game::action::Action::MergeCrates {
+                    crate_id,
+                    into_crate_id,
+                } => moves_ret.push(Action::MergeCrates {
+                    crate_id,
+                    into_crate_id,
+                }),
             }
         }
 
         moves_ret
     }
 
+    // The order sent to the server for the given unit
+    pub fn to_game_action(&self, unit_id: &UnitId) -> game::action::Action {
+        match self {
+            Action::TraveledTo {
+                path,
+                dismounted_from,
+                ..
+            } => game::action::Action::Travel {
+                unit_id: unit_id.clone(),
+                path: path.clone(),
+                dismounted_from: dismounted_from.clone(),
+            },
+            Action::LoadInto {
+                load_into, path, ..
+            } => game::action::Action::LoadInto {
+                unit_id: unit_id.clone(),
+                load_into: load_into.clone(),
+                path: path.clone(),
+            },
+            Action::PickUp { cargo_id, path, .. } => game::action::Action::PickUp {
+                unit_id: unit_id.clone(),
+                cargo_id: cargo_id.clone(),
+                path: path.clone(),
+            },
+            Action::DropOff { cargo_id } => game::action::Action::DropOff {
+                cargo_id: cargo_id.clone(),
+            },
+            Action::Replenish {
+                replenishing_unit_id,
+                units,
+                depleted_supply_crates,
+                path,
+                ..
+            } => game::action::Action::Replenish {
+                replenishing_unit_id: replenishing_unit_id.clone(),
+                units: units.clone(),
+                depleted_supply_crates: depleted_supply_crates.clone(),
+                path: path.clone(),
+            },
+            Action::Attack { unit_id, path, .. } => {
+                game::action::Action::Attack(game::action::Attack {
+                    unit_id: unit_id.clone(),
+                    path: path.clone(),
+                })
+            }
+            Action::Entrench { unit_id, against } => game::action::Action::Entrench {
+                unit_id: unit_id.clone(),
+                against: against.clone(),
+            },
+            Action::Bombard { unit_id, target } => game::action::Action::Bombard {
+                unit_id: unit_id.clone(),
+                target: target.clone(),
+            },
+            Action::Build {
+                unit_id,
+                target,
+                structure,
+            } => game::action::Action::Build {
+                unit_id: unit_id.clone(),
+                target: target.clone(),
+                structure: structure.clone(),
+            },
+            Action::Demolish { unit_id, target } => game::action::Action::Demolish {
+                unit_id: unit_id.clone(),
+                target: target.clone(),
+            },
+            Action::TransferSupplies {
+                unit_id,
+                receiving_unit_id,
+                amount,
+            } => game::action::Action::TransferSupplies {
+                unit_id: unit_id.clone(),
+                receiving_unit_id: receiving_unit_id.clone(),
+                amount: *amount,
+            },
+            Action::SplitCrate { crate_id, amount } => game::action::Action::SplitCrate {
+                crate_id: crate_id.clone(),
+                amount: *amount,
+            },
+            Action::MergeCrates {
+                crate_id,
+                into_crate_id,
+            } => game::action::Action::MergeCrates {
+                crate_id: crate_id.clone(),
+                into_crate_id: into_crate_id.clone(),
+            },
+        }
+    }
+
     pub fn arrows(&self) -> Option<&Vec<(Direction, Arrow)>> {
         match self {
             Action::TraveledTo { arrows, .. } => Some(arrows),
@@ -122,6 +280,13 @@ impl Action {
             Action::DropOff { .. } => None,
             Action::Replenish { arrows, .. } => Some(arrows),
             Action::Attack { arrows, .. } => Some(arrows),
+            Action::Entrench { .. } => None,
+            Action::Bombard { .. } => None,
+            Action::Build { .. } => None,
+            Action::Demolish { .. } => None,
+            Action::TransferSupplies { .. } => None,
+            Action::SplitCrate { .. } => None,
+            Action::MergeCrates { .. } => None,
         }
     }
 }
@@ -4,6 +4,7 @@ use shared::game::unit_index;
 use shared::id::Id;
 use shared::located::Located;
 use shared::path::Path;
+use shared::tile::Tile;
 use shared::unit::UnitId;
 
 #[derive(Clone, Debug)]
@@ -26,6 +27,42 @@ pub enum Animation {
         replenishing_unit_id: UnitId,
         units: Vec<UnitId>,
     },
+    Battle {
+        attacker_id: UnitId,
+        defender_id: UnitId,
+        loc: Located<()>,
+    },
+    Destroyed {
+        unit_id: UnitId,
+    },
+    Routed {
+        unit_id: UnitId,
+    },
+    Captured {
+        unit_id: UnitId,
+        capturer_id: UnitId,
+    },
+    Bombard {
+        unit_id: UnitId,
+        target: Located<()>,
+    },
+    TerrainChanged {
+        unit_id: UnitId,
+        loc: Located<()>,
+        tile: Tile,
+    },
+    TownCaptured {
+        unit_id: UnitId,
+        loc: Located<()>,
+    },
+    ProducedSupplyCrate {
+        unit_id: UnitId,
+        crate_id: UnitId,
+    },
+    SplitCrate {
+        unit_id: UnitId,
+        new_crate_id: UnitId,
+    },
 }
 
 impl Animation {
@@ -38,6 +75,15 @@ impl Animation {
                 replenishing_unit_id,
                 ..
             } => replenishing_unit_id,
+            Animation::Battle { attacker_id, .. } => attacker_id,
+            Animation::Destroyed { unit_id } => unit_id,
+            Animation::Routed { unit_id } => unit_id,
+            Animation::Captured { capturer_id, .. } => capturer_id,
+            Animation::Bombard { unit_id, .. } => unit_id,
+            Animation::TerrainChanged { unit_id, .. } => unit_id,
+            Animation::TownCaptured { unit_id, .. } => unit_id,
+            Animation::ProducedSupplyCrate { unit_id, .. } => unit_id,
+            Animation::SplitCrate { unit_id, .. } => unit_id,
         }
     }
     pub fn moving_subject_unit_id(&self) -> Option<Located<&UnitId>> {
@@ -53,6 +99,20 @@ impl Animation {
         player_id: Id,
         unit_index: &unit_index::by_id::Index,
     ) -> Result<bool, String> {
+        // Players should know when their units are attacked or
+        // captured, even if they could not see the enemy coming
+        let maybe_victim_id = match &self {
+            Animation::Battle { defender_id, .. } => Some(defender_id),
+            Animation::Captured { unit_id, .. } => Some(unit_id),
+            _ => None,
+        };
+
+        if let Some(victim) = maybe_victim_id.and_then(|victim_id| unit_index.get(victim_id)) {
+            if victim.owner == player_id {
+                return Ok(true);
+            }
+        }
+
         let unit_id = self.subject_unit_id();
 
         match unit_index.get(&unit_id) {
@@ -126,6 +186,63 @@ impl Animation {
             Event::Perished { unit_id } => {
                 vec![Animation::Perish { unit_id }]
             }
+            Event::Battle {
+                attacker_id,
+                defender_id,
+                loc,
+            } => vec![Animation::Battle {
+                attacker_id,
+                defender_id,
+                loc,
+            }],
+            Event::Damaged { .. } => vec![],
+            Event::Destroyed { unit_id } => {
+                vec![Animation::Destroyed { unit_id }]
+            }
+            Event::ConsumedBattleSupplies { .. } => vec![],
+            Event::Recovered { .. } => vec![],
+            Event::Entrenched { .. } => vec![],
+            Event::Retreated { unit_id, path } => vec![Animation::Travel {
+                unit_id,
+                path,
+                loads_into: None,
+                picks_up: None,
+            }],
+            Event::Routed { unit_id } => vec![Animation::Routed { unit_id }],
+            Event::Captured {
+                unit_id,
+                capturer_id,
+            } => vec![Animation::Captured {
+                unit_id,
+                capturer_id,
+            }],
+            Event::Bombarded {
+                unit_id, target, ..
+            } => vec![Animation::Bombard { unit_id, target }],
+            Event::Built { .. } => vec![],
+            Event::Demolished { .. } => vec![],
+            Event::TerrainChanged { unit_id, loc, tile } => {
+                vec![Animation::TerrainChanged { unit_id, loc, tile }]
+            }
+            Event::TownCaptured { unit_id, loc } => {
+                vec![Animation::TownCaptured { unit_id, loc }]
+            }
+            Event::SuppliedByTown { .. } => vec![],
+            Event::Stockpiled { .. } => vec![],
+            Event::ProducedSupplyCrate { unit_id, crate_id } => {
+                vec![Animation::ProducedSupplyCrate { unit_id, crate_id }]
+            }
+            Event::TransferredSupplies { .. } => vec![],
+            Event::SplitCrate {
+                unit_id,
+                new_crate_id,
+                ..
+            } => vec![Animation::SplitCrate {
+                unit_id,
+                new_crate_id,
+            }],
+            Event::MergedCrates { .. } => vec![],
+            Event::Foraged { .. } => vec![],
         }
     }
 }
@@ -44,7 +44,7 @@ pub fn sidebar_content(
     for unit_id in &model.units {
         if let Some(unit_model) = game.get_unit(unit_id) {
             unit_rows.push(
-                unit_row::view(unit_id, unit_model, moves_index, unit_changes)
+                unit_row::view(unit_id, unit_model, moves_index, unit_changes, game)
                     .map_msg(Msg::UnitRow),
             );
         }
@@ -1,7 +1,11 @@
+pub mod bombarding;
+pub mod building;
 pub mod moving;
 
 #[derive(Debug, Clone)]
 pub enum Mode {
     None,
     MovingUnit(moving::Model),
+    Bombarding(bombarding::Model),
+    Building(building::Model),
 }
@@ -0,0 +1,19 @@
+use shared::located::Located;
+use shared::unit::UnitId;
+use std::collections::HashSet;
+
+///////////////////////////////////////////////////////////////
+// Types
+///////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone)]
+pub struct Model {
+    pub unit_id: UnitId,
+    pub targets: HashSet<Located<()>>,
+}
+
+impl Model {
+    pub fn init(unit_id: UnitId, targets: HashSet<Located<()>>) -> Model {
+        Model { unit_id, targets }
+    }
+}
@@ -0,0 +1,31 @@
+use shared::located::Located;
+use shared::tile::Tile;
+use shared::unit::UnitId;
+use std::collections::HashSet;
+
+///////////////////////////////////////////////////////////////
+// Types
+///////////////////////////////////////////////////////////////
+
+#[derive(Debug, Clone)]
+pub struct Model {
+    pub unit_id: UnitId,
+    pub work: Work,
+    pub targets: HashSet<Located<()>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Work {
+    Build(Tile),
+    Demolish,
+}
+
+impl Model {
+    pub fn init(unit_id: UnitId, work: Work, targets: HashSet<Located<()>>) -> Model {
+        Model {
+            unit_id,
+            work,
+            targets,
+        }
+    }
+}
@@ -1,21 +1,26 @@
 use crate::page::game::animation::Animation;
 use crate::view::cell::Cell;
 use shared::facing_direction::FacingDirection;
+use shared::game::conditions::Conditions;
 use shared::game::day::Time;
 use shared::game::unit_index::Indexes;
 use shared::game::{calculate_player_visibility, unit_index};
 use shared::id::Id;
 use shared::located::Located;
-use shared::map::Map;
-use shared::unit::{Place, UnitId};
-use std::collections::HashSet;
+use shared::map::{Map, Town};
+use shared::unit;
+use shared::unit::definition::Table;
+use shared::unit::{Place, Unit, UnitId};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug)]
 pub struct Model {
     pub indices: Indexes,
     animations: Vec<Animation>,
     pub visibility: HashSet<Located<()>>,
+    pub towns: HashMap<Located<()>, Town>,
     pub day: Time,
+    pub conditions: Conditions,
 }
 
 impl Model {
@@ -23,13 +28,17 @@ impl Model {
         indices: Indexes,
         animations: Vec<Animation>,
         visibility: HashSet<Located<()>>,
+        towns: HashMap<Located<()>, Town>,
         day: Time,
+        conditions: Conditions,
     ) -> Model {
         Model {
             indices,
             animations,
             visibility,
+            towns,
             day,
+            conditions,
         }
     }
     pub fn currently_animating_unit(&self) -> Option<Located<&UnitId>> {
@@ -37,7 +46,12 @@ impl Model {
             .first()
             .and_then(|animation| animation.moving_subject_unit_id())
     }
-    pub fn progress_animation(&mut self, viewer_id: &Id, map: &Map) -> Result<bool, String> {
+    pub fn progress_animation(
+        &mut self,
+        viewer_id: &Id,
+        map: &Map,
+        unit_table: &Table,
+    ) -> Result<bool, String> {
         let ret = match self.animations.first_mut() {
             None => Ok(true),
             Some(animation) => match animation {
@@ -132,12 +146,144 @@ impl Model {
 
                     // Animate!
 
+                    Ok(false)
+                }
+                Animation::Battle { .. } => {
+                    self.animations.remove(0);
+
+                    // Animate!
+
+                    Ok(false)
+                }
+                Animation::Destroyed { .. } => {
+                    self.animations.remove(0);
+
+                    // Animate!
+
+                    Ok(false)
+                }
+                Animation::Routed { .. } => {
+                    self.animations.remove(0);
+
+                    // Animate!
+
+                    Ok(false)
+                }
+                Animation::Bombard { .. } => {
+                    self.animations.remove(0);
+
+                    // Animate!
+
+                    Ok(false)
+                }
+                Animation::TerrainChanged { .. } => {
+                    self.animations.remove(0);
+
+                    // Animate!
+
+                    Ok(false)
+                }
+                Animation::TownCaptured { unit_id, loc } => {
+                    let owner = match self.indices.by_id.get(unit_id) {
+                        Some(capturer) => capturer.owner.clone(),
+                        None => return Err("could not find unit capturing town".to_string()),
+                    };
+
+                    self.towns.insert(loc.clone(), Town { owner: Some(owner) });
+
+                    self.animations.remove(0);
+
+                    Ok(false)
+                }
+                Animation::ProducedSupplyCrate { unit_id, crate_id } => {
+                    let depot = match self.indices.by_id.get(unit_id) {
+                        Some(depot) => depot,
+                        None => return Err("could not find supply depot".to_string()),
+                    };
+
+                    let supply_crate = unit::Model::new(
+                        Unit::SupplyCrate,
+                        &depot.owner,
+                        depot.place.clone(),
+                        &depot.color,
+                    )
+                    .with_table(unit_table);
+
+                    self.indices
+                        .by_id
+                        .units
+                        .insert(crate_id.clone(), supply_crate);
+
+                    self.indices.by_location = unit_index::by_location::make(&self.indices.by_id);
+
+                    self.animations.remove(0);
+
+                    Ok(false)
+                }
+                Animation::SplitCrate {
+                    unit_id,
+                    new_crate_id,
+                } => {
+                    let transport = match self
+                        .indices
+                        .by_id
+                        .get(unit_id)
+                        .and_then(|crate_model| crate_model.place.in_unit_loc())
+                        .and_then(|transport_id| self.indices.by_id.get(transport_id))
+                    {
+                        Some(transport) => transport,
+                        None => return Err("could not find transport of supply crate".to_string()),
+                    };
+
+                    let supply_crate = unit::Model::new(
+                        Unit::SupplyCrate,
+                        &transport.owner,
+                        transport.place.clone(),
+                        &transport.color,
+                    )
+                    .with_table(unit_table);
+
+                    self.indices
+                        .by_id
+                        .units
+                        .insert(new_crate_id.clone(), supply_crate);
+
+                    self.indices.by_location = unit_index::by_location::make(&self.indices.by_id);
+
+                    self.animations.remove(0);
+
+                    Ok(false)
+                }
+                Animation::Captured {
+                    unit_id,
+                    capturer_id,
+                } => {
+                    let (owner, color) = match self.indices.by_id.get(capturer_id) {
+                        Some(capturer) => (capturer.owner.clone(), capturer.color.clone()),
+                        None => return Err("could not find capturing unit".to_string()),
+                    };
+
+                    if let Some(unit_model) = self.indices.by_id.get_mut(unit_id) {
+                        unit_model.owner = owner;
+                        unit_model.color = color;
+                    }
+
+                    self.indices.by_location = unit_index::by_location::make(&self.indices.by_id);
+
+                    self.animations.remove(0);
+
                     Ok(false)
                 }
             },
         };
 
-        self.visibility = calculate_player_visibility(viewer_id, map, &self.indices.by_id);
+        self.visibility = calculate_player_visibility(
+            viewer_id,
+            map,
+            &self.indices.by_id,
+            &self.conditions,
+            unit_table,
+        );
 
         ret
     }
@@ -191,6 +337,184 @@ pub fn sidebar_view<Msg: 'static>(
 
                         vec![Cell::from_str(vec![], msg.as_str())]
                     }
+                    Animation::Destroyed { unit_id } => {
+                        let msg = match unit_index.get(unit_id) {
+                            None => "error: could not find unit".to_string(),
+                            Some(unit_model) => {
+                                let mut unit_name_msg = unit_model
+                                    .name
+                                    .clone()
+                                    .unwrap_or_else(|| unit_model.unit.to_string());
+
+                                unit_name_msg.push_str(" was destroyed");
+
+                                unit_name_msg
+                            }
+                        };
+
+                        vec![Cell::from_str(vec![], msg.as_str())]
+                    }
+                    Animation::Bombard { unit_id, .. } => {
+                        let msg = match unit_index.get(unit_id) {
+                            None => "error: could not find unit".to_string(),
+                            Some(unit_model) => {
+                                let mut unit_name_msg = unit_model
+                                    .name
+                                    .clone()
+                                    .unwrap_or_else(|| unit_model.unit.to_string());
+
+                                unit_name_msg.push_str(" bombarded");
+
+                                unit_name_msg
+                            }
+                        };
+
+                        vec![Cell::from_str(vec![], msg.as_str())]
+                    }
+                    Animation::TerrainChanged { unit_id, .. } => {
+                        let msg = match unit_index.get(unit_id) {
+                            None => "error: could not find unit".to_string(),
+                            Some(unit_model) => {
+                                let mut unit_name_msg = unit_model
+                                    .name
+                                    .clone()
+                                    .unwrap_or_else(|| unit_model.unit.to_string());
+
+                                unit_name_msg.push_str(" changed the terrain");
+
+                                unit_name_msg
+                            }
+                        };
+
+                        vec![Cell::from_str(vec![], msg.as_str())]
+                    }
+                    Animation::TownCaptured { unit_id, .. } => {
+                        let msg = match unit_index.get(unit_id) {
+                            None => "error: could not find unit".to_string(),
+                            Some(unit_model) => {
+                                let mut unit_name_msg = unit_model
+                                    .name
+                                    .clone()
+                                    .unwrap_or_else(|| unit_model.unit.to_string());
+
+                                unit_name_msg.push_str(" captured a town");
+
+                                unit_name_msg
+                            }
+                        };
+
+                        vec![Cell::from_str(vec![], msg.as_str())]
+                    }
+                    Animation::ProducedSupplyCrate { unit_id, .. } => {
+                        let msg = match unit_index.get(unit_id) {
+                            None => "error: could not find unit".to_string(),
+                            Some(unit_model) => {
+                                let mut unit_name_msg = unit_model
+                                    .name
+                                    .clone()
+                                    .unwrap_or_else(|| unit_model.unit.to_string());
+
+                                unit_name_msg.push_str(" sent out a supply crate");
+
+                                unit_name_msg
+                            }
+                        };
+
+                        vec![Cell::from_str(vec![], msg.as_str())]
+                    }
+                    Animation::SplitCrate { unit_id, .. } => {
+                        let msg = match unit_index.get(unit_id) {
+                            None => "error: could not find unit".to_string(),
+                            Some(unit_model) => {
+                                let mut unit_name_msg = unit_model
+                                    .name
+                                    .clone()
+                                    .unwrap_or_else(|| unit_model.unit.to_string());
+
+                                unit_name_msg.push_str(" was split in two");
+
+                                unit_name_msg
+                            }
+                        };
+
+                        vec![Cell::from_str(vec![], msg.as_str())]
+                    }
+                    Animation::Routed { unit_id } => {
+                        let msg = match unit_index.get(unit_id) {
+                            None => "error: could not find unit".to_string(),
+                            Some(unit_model) => {
+                                let mut unit_name_msg = unit_model
+                                    .name
+                                    .clone()
+                                    .unwrap_or_else(|| unit_model.unit.to_string());
+
+                                unit_name_msg.push_str(" was routed");
+
+                                unit_name_msg
+                            }
+                        };
+
+                        vec![Cell::from_str(vec![], msg.as_str())]
+                    }
+                    Animation::Captured {
+                        unit_id,
+                        capturer_id,
+                    } => {
+                        let msg = match (unit_index.get(capturer_id), unit_index.get(unit_id)) {
+                            (Some(capturer), Some(captured)) => {
+                                let mut capture_msg = capturer
+                                    .name
+                                    .clone()
+                                    .unwrap_or_else(|| capturer.unit.to_string());
+
+                                capture_msg.push_str(" captured ");
+
+                                capture_msg.push_str(
+                                    captured
+                                        .name
+                                        .clone()
+                                        .unwrap_or_else(|| captured.unit.to_string())
+                                        .as_str(),
+                                );
+
+                                capture_msg
+                            }
+                            (None, _) => "error: could not find capturing unit".to_string(),
+                            (_, None) => "error: could not find captured unit".to_string(),
+                        };
+
+                        vec![Cell::from_str(vec![], msg.as_str())]
+                    }
+                    Animation::Battle {
+                        attacker_id,
+                        defender_id,
+                        ..
+                    } => {
+                        let msg = match (unit_index.get(attacker_id), unit_index.get(defender_id)) {
+                            (Some(attacker), Some(defender)) => {
+                                let mut battle_msg = attacker
+                                    .name
+                                    .clone()
+                                    .unwrap_or_else(|| attacker.unit.to_string());
+
+                                battle_msg.push_str(" attacked ");
+
+                                battle_msg.push_str(
+                                    defender
+                                        .name
+                                        .clone()
+                                        .unwrap_or_else(|| defender.unit.to_string())
+                                        .as_str(),
+                                );
+
+                                battle_msg
+                            }
+                            (None, _) => "error: could not find attacking unit".to_string(),
+                            (_, None) => "error: could not find defending unit".to_string(),
+                        };
+
+                        vec![Cell::from_str(vec![], msg.as_str())]
+                    }
                     Animation::DropOff {
                         cargo_id,
                         transport_id,
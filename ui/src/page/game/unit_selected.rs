@@ -6,7 +6,9 @@ use crate::style::Style;
 use crate::view::button::Button;
 use crate::view::cell::Cell;
 use crate::view::text_field::TextField;
+use shared::direction::Direction;
 use shared::game::Game;
+use shared::tile::Tile;
 use shared::unit::UnitId;
 use shared::{game, unit};
 use std::collections::HashMap;
@@ -19,6 +21,9 @@ pub struct Model {
     pub unit_id: UnitId,
     pub name_field: String,
     pub name_submitted: bool,
+    // How many supplies to hand over or drop. Left
+    // empty, half of what there is goes
+    pub supply_amount_field: String,
     pub from_group: Option<group_selected::Model>,
 }
 
@@ -35,6 +40,7 @@ impl From<Flags> for Model {
             unit_id: flags.unit_id,
             name_field: flags.existing_name.unwrap_or_default(),
             name_submitted: flags.name_already_submitted,
+            supply_amount_field: String::new(),
             from_group: flags.from_group,
         }
     }
@@ -44,8 +50,16 @@ impl From<Flags> for Model {
 
 pub enum Msg {
     UpdatedUnitNameField(String),
+    UpdatedSupplyAmountField(String),
     ClickedSetName,
     ClickedBackToGroup,
+    ClickedEntrench(Direction),
+    ClickedBombard,
+    ClickedBuild(Tile),
+    ClickedDemolish,
+    ClickedTransferSupplies(UnitId, i16),
+    ClickedSplitCrate(UnitId, i16),
+    ClickedMergeCrates(UnitId),
     UnitRow(unit_row::Msg),
 }
 
@@ -102,7 +116,7 @@ pub fn sidebar_content(
             for (unit_id, _) in loaded_units {
                 if let Some(unit_model) = game.get_unit(unit_id) {
                     unit_rows.push(
-                        unit_row::view(unit_id, unit_model, moves_index, unit_changes)
+                        unit_row::view(unit_id, unit_model, moves_index, unit_changes, game)
                             .map_msg(Msg::UnitRow),
                     );
                 }
@@ -119,7 +133,9 @@ pub fn sidebar_content(
     let supply_view = {
         let supply_block_num: u16 = {
             let percent_of_max: f32 = if unit_model.supplies > 0 {
-                ((unit_model.supplies as f32) / (unit_model.unit.max_supplies() as f32)) * 16.0
+                ((unit_model.supplies as f32)
+                    / (unit_model.definition(&game.unit_table).max_supplies as f32))
+                    * 16.0
             } else {
                 0.0
             };
@@ -171,6 +187,110 @@ pub fn sidebar_content(
         )
     };
 
+    let forecast_view = unit_row::forecast_view(&unit_row::forecast(
+        &model.unit_id,
+        unit_model,
+        moves_index,
+        game,
+    ));
+
+    let supply_line_view = if unit_model.in_supply {
+        Cell::none()
+    } else {
+        Cell::from_str(vec![Style::TextImportant4], "out of supply")
+    };
+
+    let entrench_view = if unit_model
+        .definition(&game.unit_table)
+        .entrench_supply_cost
+        .is_some()
+    {
+        let entrench_button = |label: &str, direction: Direction| {
+            Button::simple(label)
+                .on_click(move |_| Msg::ClickedEntrench(direction.clone()))
+                .cell()
+        };
+
+        Cell::group(
+            vec![Style::FlexCol, Style::G4],
+            vec![
+                Cell::from_str(vec![], "entrench against"),
+                Cell::group(
+                    vec![Style::FlexRow, Style::G4],
+                    vec![
+                        entrench_button("north", Direction::North),
+                        entrench_button("east", Direction::East),
+                        entrench_button("south", Direction::South),
+                        entrench_button("west", Direction::West),
+                    ],
+                ),
+            ],
+        )
+    } else {
+        Cell::none()
+    };
+
+    let bombard_view = if unit_model
+        .definition(&game.unit_table)
+        .bombard_range
+        .is_some()
+        && unit_model.place.is_on_map()
+    {
+        Button::simple("bombard")
+            .on_click(|_| Msg::ClickedBombard)
+            .cell()
+    } else {
+        Cell::none()
+    };
+
+    let build_view = if unit_model
+        .definition(&game.unit_table)
+        .build_supply_cost
+        .is_some()
+        && unit_model.place.is_on_map()
+    {
+        Cell::group(
+            vec![Style::FlexRow, Style::G4],
+            vec![
+                Button::simple("fortify")
+                    .on_click(|_| Msg::ClickedBuild(Tile::Fortification))
+                    .cell(),
+                Button::simple("clear path")
+                    .on_click(|_| Msg::ClickedBuild(Tile::ClearedPath))
+                    .cell(),
+                Button::simple("demolish")
+                    .on_click(|_| Msg::ClickedDemolish)
+                    .cell(),
+            ],
+        )
+    } else {
+        Cell::none()
+    };
+
+    let supply_handling_view = supply_handling_view(model, unit_model, game);
+
+    let forage_view = match (
+        unit_model.definition(&game.unit_table).forage_supplies,
+        unit_model.place.to_map_loc(),
+    ) {
+        (Some(forage_supplies), Some(loc)) if game.map.get_tile(&loc.to_unit()).can_forage() => {
+            let times_foraged = game.map.times_foraged(&loc.to_unit());
+
+            Cell::from_str(
+                vec![],
+                format!(
+                    "forages up to {} by staying put",
+                    forage_supplies / (1 + times_foraged as i16)
+                )
+                .as_str(),
+            )
+        }
+        _ => Cell::none(),
+    };
+
+    let health_label = Cell::from_str(vec![], "health");
+    let health_view = Cell::from_str(vec![], unit_model.health.to_string().as_str());
+
     vec![
         back_button_row,
         name_view,
@@ -178,5 +298,150 @@ pub fn sidebar_content(
         transporting_view,
         supplies_label,
         supply_view,
+        forecast_view,
+        supply_line_view,
+        forage_view,
+        supply_handling_view,
+        health_label,
+        health_view,
+        entrench_view,
+        bombard_view,
+        build_view,
     ]
 }
+
+// Trucks can hand supplies to the transports right next to
+// them, or leave some behind as a crate of their own, and
+// crates left on the same tile can be gathered back together
+fn supply_handling_view(model: &Model, unit_model: &unit::Model, game: &Game) -> Cell<Msg> {
+    let unit_id = &model.unit_id;
+
+    let unit_loc = match game.indexes.position_of_unit_or_transport(unit_id) {
+        Ok(loc) => loc,
+        Err(_) => return Cell::none(),
+    };
+
+    let unit_name = |unit_model: &unit::Model| {
+        unit_model
+            .name
+            .clone()
+            .unwrap_or_else(|| unit_model.unit.to_string())
+    };
+
+    let chosen_amount = model
+        .supply_amount_field
+        .trim()
+        .parse::<i16>()
+        .ok()
+        .filter(|amount| *amount > 0);
+
+    let amount_label = |amount: i16| match chosen_amount {
+        Some(_) => amount.to_string(),
+        None => "half".to_string(),
+    };
+
+    let mut buttons = Vec::new();
+
+    if unit_model
+        .definition(&game.unit_table)
+        .can_pick_up_supply_crates
+    {
+        let carried_crates = game
+            .indexes
+            .carried_supply_crates(unit_id, &game.unit_table);
+
+        let carried_supplies: i16 = carried_crates
+            .iter()
+            .filter_map(|crate_id| game.get_unit(crate_id))
+            .map(|crate_model| crate_model.supplies)
+            .sum();
+
+        if carried_supplies > 1 {
+            buttons.push(
+                TextField::simple(
+                    model.supply_amount_field.as_str(),
+                    Msg::UpdatedSupplyAmountField,
+                )
+                .with_placeholder("amount".to_string())
+                .cell(),
+            );
+
+            for (other_id, other_model) in game.indexes.by_id.iter() {
+                let is_adjacent = game
+                    .indexes
+                    .position_of_unit_or_transport(other_id)
+                    .map(|other_loc| other_loc.distance_from(&unit_loc) <= 1)
+                    .unwrap_or(false);
+
+                if other_id != unit_id
+                    && is_adjacent
+                    && other_model.owner == unit_model.owner
+                    && other_model
+                        .definition(&game.unit_table)
+                        .can_pick_up_supply_crates
+                {
+                    let receiving_unit_id = other_id.clone();
+                    let amount = chosen_amount.unwrap_or(carried_supplies / 2);
+
+                    buttons.push(
+                        Button::simple(
+                            format!(
+                                "give {} to {}",
+                                amount_label(amount),
+                                unit_name(other_model)
+                            )
+                            .as_str(),
+                        )
+                        .on_click(move |_| {
+                            Msg::ClickedTransferSupplies(receiving_unit_id.clone(), amount)
+                        })
+                        .disable(amount > carried_supplies)
+                        .cell(),
+                    );
+                }
+            }
+        }
+
+        for crate_id in carried_crates {
+            if let Some(crate_model) = game.get_unit(&crate_id) {
+                if crate_model.supplies > 1 {
+                    let amount = chosen_amount.unwrap_or(crate_model.supplies / 2);
+
+                    buttons.push(
+                        Button::simple(
+                            format!("drop {} of a crate", amount_label(amount)).as_str(),
+                        )
+                        .on_click(move |_| Msg::ClickedSplitCrate(crate_id.clone(), amount))
+                        .disable(amount >= crate_model.supplies)
+                        .cell(),
+                    );
+                }
+            }
+        }
+    }
+
+    if unit_model.definition(&game.unit_table).is_supply_crate && unit_model.place.is_on_map() {
+        if let Some(units_at_loc) = game.indexes.get_units_by_location(&unit_loc) {
+            for (other_id, _, other_model) in units_at_loc {
+                if other_id != unit_id
+                    && other_model.owner == unit_model.owner
+                    && other_model.definition(&game.unit_table).is_supply_crate
+                {
+                    let into_crate_id = other_id.clone();
+
+                    buttons.push(
+                        Button::simple(format!("merge into {}", unit_name(other_model)).as_str())
+                            .on_click(move |_| Msg::ClickedMergeCrates(into_crate_id.clone()))
+                            .cell(),
+                    );
+                }
+            }
+        }
+    }
+
+    if buttons.is_empty() {
+        Cell::none()
+    } else {
+        Cell::group(vec![Style::FlexCol, Style::G4], buttons)
+    }
+}
@@ -3,6 +3,8 @@ use crate::page::game::unit_change::UnitChange;
 use crate::style::Style;
 use crate::view::cell::Cell;
 use shared::api::endpoint::Endpoint;
+use shared::game::forecast::Forecast;
+use shared::game::Game;
 use shared::unit;
 use shared::unit::UnitId;
 use std::collections::HashMap;
@@ -25,6 +27,7 @@ pub fn view(
     unit_model: &unit::Model,
     moves_index: &HashMap<UnitId, Action>,
     unit_changes: &HashMap<UnitId, UnitChange>,
+    game: &Game,
 ) -> Cell<Msg> {
     let label = match unit_changes.get(unit_id) {
         Some(UnitChange::Name { name }) => name.clone(),
@@ -78,7 +81,73 @@ pub fn view(
                 ],
                 label.as_str(),
             ),
+            forecast_view(&forecast(unit_id, unit_model, moves_index, game)),
+            if unit_model.in_supply {
+                Cell::none()
+            } else {
+                Cell::from_str(
+                    vec![
+                        Style::TextImportant4,
+                        Style::TextSelectNone,
+                        Style::FlexCol,
+                        Style::JustifyCenter,
+                    ],
+                    "out of supply",
+                )
+            },
         ],
     )
     .on_click(|_| Msg::Clicked(clicked_unit_id))
 }
+
+// How long the unit has left if nobody resupplies it,
+// counting whatever it has been ordered to do this turn
+pub fn forecast(
+    unit_id: &UnitId,
+    unit_model: &unit::Model,
+    moves_index: &HashMap<UnitId, Action>,
+    game: &Game,
+) -> Forecast {
+    let conditions = game.conditions();
+
+    let queued_supply_cost = match moves_index.get(unit_id) {
+        Some(action) => action.to_game_action(unit_id).supply_cost_for(
+            unit_id,
+            unit_model,
+            &game.map,
+            &conditions,
+            &game.unit_table,
+        ),
+        None => 0,
+    };
+
+    Forecast::for_unit(
+        unit_model,
+        queued_supply_cost,
+        &conditions,
+        &game.unit_table,
+    )
+}
+
+pub fn forecast_view<M: 'static>(forecast: &Forecast) -> Cell<M> {
+    match forecast {
+        Forecast::Indefinite => Cell::none(),
+        Forecast::PerishesIn(_) => {
+            let text_color = if forecast.starves_next_turn() {
+                Style::TextImportant4
+            } else {
+                Style::TextContent2
+            };
+
+            Cell::from_str(
+                vec![
+                    text_color,
+                    Style::TextSelectNone,
+                    Style::FlexCol,
+                    Style::JustifyCenter,
+                ],
+                format!("starves {}", forecast.to_string()).as_str(),
+            )
+        }
+    }
+}